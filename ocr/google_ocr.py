@@ -243,6 +243,9 @@ def parse_mrz(text: str) -> dict | None:
     date_of_expiry = line2[21:27]
     personal_number = line2[28:42].replace("<", "")
 
+    def _digit(ch: str) -> int | None:
+        return int(ch) if ch.isdigit() else None
+
     dob_parts = _yymmdd_to_parts(date_of_birth)
     exp_parts = _yymmdd_to_parts(date_of_expiry, future=True)
 
@@ -262,6 +265,9 @@ def parse_mrz(text: str) -> dict | None:
         "age": _calculate_age(date_of_birth),
         "issuingCountry": issuing_country,
         "personalNumber": personal_number,
+        "documentNumberCheckDigit": _digit(line2[9]),
+        "birthDateCheckDigit": _digit(line2[19]),
+        "expiryDateCheckDigit": _digit(line2[27]),
     }
 
 
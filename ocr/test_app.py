@@ -345,6 +345,17 @@ class TestParseMrz:
         assert result["personalNumber"] == ""
         assert isinstance(result["age"], int)
 
+    def test_check_digits_returned(self):
+        result = google_ocr.parse_mrz(_IRISH_MRZ_TEXT)
+        assert result["documentNumberCheckDigit"] == 6
+        assert result["birthDateCheckDigit"] == 9
+        assert result["expiryDateCheckDigit"] == 4
+        # A filler where a check digit belongs is reported as missing, not as a digit
+        line2 = _IRISH_LINE2[:9] + "<" + _IRISH_LINE2[10:]
+        result = google_ocr.parse_mrz(f"{_IRISH_LINE1}\n{line2}\n")
+        assert result["documentNumberCheckDigit"] is None
+        assert result["birthDateCheckDigit"] == 9
+
     def test_no_mrz_returns_none(self):
         assert google_ocr.parse_mrz("PASSPORT\nNAME: JOHN\nNO MRZ") is None
 
@@ -100,9 +100,9 @@ function Verify() {
               dataToDecode = '0x' + pvHex.slice(66);
             }
 
-            const [, , , isOverMinAge, proofMinAge, isNationalityMatch, proofNationality, isSexMatch, proofSex, proofTimestamp] =
+            const [, , , isOverMinAge, proofMinAge, , isNationalityMatch, proofNationality, isSexMatch, proofSex, proofTimestamp] =
               coder.decode(
-                ['bytes32', 'address', 'address', 'bool', 'uint256', 'bool', 'string', 'bool', 'string', 'uint256'],
+                ['bytes32', 'address', 'address', 'bool', 'uint256', 'uint256', 'bool', 'string', 'bool', 'string', 'uint256'],
                 dataToDecode
               );
 
@@ -0,0 +1,32 @@
+#![no_main]
+sp1_zkvm::entrypoint!(main);
+
+use alloy_sol_types::SolValue;
+use passport_verifier_lib::aggregate::aggregate_age_outputs;
+use passport_verifier_lib::*;
+use sha2::{Digest, Sha256};
+
+// Folds compressed passport proofs into one. The host writes each inner proof with
+// SP1Stdin::write_proof in the same order as its public values, so verify_sp1_proof consumes them
+// one by one. What an inner proof must have proved is checked in aggregate_age_outputs.
+pub fn main() {
+    let passport_vkey = sp1_zkvm::io::read::<[u32; 8]>();
+    let min_age = sp1_zkvm::io::read::<u16>();
+    let public_values = sp1_zkvm::io::read::<Vec<Vec<u8>>>();
+
+    let outputs: Vec<PassportVerificationOutput> = public_values
+        .iter()
+        .map(|values| {
+            sp1_zkvm::lib::verify::verify_sp1_proof(&passport_vkey, &Sha256::digest(values).into());
+            PassportVerificationOutput::abi_decode(values).expect("inner proof has malformed public values")
+        })
+        .collect();
+
+    let mut vkey = [0u8; 32];
+    for (chunk, word) in vkey.chunks_exact_mut(4).zip(passport_vkey) {
+        chunk.copy_from_slice(&word.to_be_bytes());
+    }
+    let output = aggregate_age_outputs(vkey, min_age, &outputs);
+    let bytes = AggregateAgeOutput::abi_encode(&output);
+    sp1_zkvm::io::commit_slice(&bytes);
+}
@@ -7,7 +7,7 @@ import uuid
 import tempfile
 import socket
 import http.client
-from flask import Flask, request, jsonify
+from flask import Flask, Response, request, jsonify
 from flask_cors import CORS
 from dotenv import load_dotenv
 from web3 import Web3
@@ -35,6 +35,50 @@ _proof_jobs_lock = threading.Lock()
 _JOB_TTL_SECONDS = 3 * 60 * 60
 
 
+# Prometheus metrics for the evm prover runs. Only Groth16 is produced, so it is the one mode.
+_PROOF_MODE = "groth16"
+_PROVING_DURATION_BUCKETS = (60, 120, 300, 600, 1200, 1800, 3600, 7200)
+_metrics = {
+    "proofs_generated": 0,
+    "proof_failures": 0,
+    "duration_bucket_counts": [0] * len(_PROVING_DURATION_BUCKETS),
+    "duration_sum": 0.0,
+    "duration_count": 0,
+}
+_metrics_lock = threading.Lock()
+
+
+def _record_proof_result(succeeded: bool, duration_seconds: float):
+    with _metrics_lock:
+        _metrics["proofs_generated" if succeeded else "proof_failures"] += 1
+        for i, bound in enumerate(_PROVING_DURATION_BUCKETS):
+            if duration_seconds <= bound:
+                _metrics["duration_bucket_counts"][i] += 1
+        _metrics["duration_sum"] += duration_seconds
+        _metrics["duration_count"] += 1
+
+
+def _render_metrics() -> str:
+    label = f'mode="{_PROOF_MODE}"'
+    with _metrics_lock:
+        lines = [
+            "# HELP passport_proofs_generated_total Proofs generated successfully.",
+            "# TYPE passport_proofs_generated_total counter",
+            f"passport_proofs_generated_total{{{label}}} {_metrics['proofs_generated']}",
+            "# HELP passport_proof_failures_total Proof jobs whose prover run failed or timed out.",
+            "# TYPE passport_proof_failures_total counter",
+            f"passport_proof_failures_total{{{label}}} {_metrics['proof_failures']}",
+            "# HELP passport_proving_duration_seconds Wall-clock time of the evm prover run.",
+            "# TYPE passport_proving_duration_seconds histogram",
+        ]
+        for bound, count in zip(_PROVING_DURATION_BUCKETS, _metrics["duration_bucket_counts"]):
+            lines.append(f'passport_proving_duration_seconds_bucket{{{label},le="{bound}"}} {count}')
+        lines.append(f'passport_proving_duration_seconds_bucket{{{label},le="+Inf"}} {_metrics["duration_count"]}')
+        lines.append(f"passport_proving_duration_seconds_sum{{{label}}} {_metrics['duration_sum']}")
+        lines.append(f"passport_proving_duration_seconds_count{{{label}}} {_metrics['duration_count']}")
+    return "\n".join(lines) + "\n"
+
+
 def _prune_expired_jobs():
     cutoff = time.monotonic() - _JOB_TTL_SECONDS
     with _proof_jobs_lock:
@@ -48,6 +92,26 @@ def _update_job(job_id: str, data: dict):
         _proof_jobs[job_id].update(data)
 
 
+def _to_passport_input(passport: dict) -> dict:
+    return {
+        "documentNumber": passport.get("documentNumber", ""),
+        "birthYear":      passport.get("birthYear", 0),
+        "birthMonth":     passport.get("birthMonth", 0),
+        "birthDay":       passport.get("birthDay", 0),
+        "expiryYear":     passport.get("expiryYear", 0),
+        "expiryMonth":    passport.get("expiryMonth", 0),
+        "expiryDay":      passport.get("expiryDay", 0),
+        "nationality":    passport.get("nationality", ""),
+        "name":           passport.get("name", ""),
+        "sex":            passport.get("sex", ""),
+        "documentNumberCheckDigit": passport.get("documentNumberCheckDigit"),
+        "birthDateCheckDigit":      passport.get("birthDateCheckDigit"),
+        "expiryDateCheckDigit":     passport.get("expiryDateCheckDigit"),
+        "documentSignature":        passport.get("documentSignature"),
+        "issuingCountry":           passport.get("issuingCountry"),
+    }
+
+
 def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_address: str):
     print(f"\n[job:{job_id}] Started")
     print(f"[job:{job_id}] Wallet:   {wallet_address}")
@@ -91,18 +155,11 @@ def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_ad
         json.dump(reqs, f, indent=4)
     print(f"[job:{job_id}] Written requirements to {reqs_path}")
 
-    passport_input = {
-        "documentNumber": passport.get("documentNumber", ""),
-        "birthYear":      passport.get("birthYear", 0),
-        "birthMonth":     passport.get("birthMonth", 0),
-        "birthDay":       passport.get("birthDay", 0),
-        "expiryYear":     passport.get("expiryYear", 0),
-        "expiryMonth":    passport.get("expiryMonth", 0),
-        "expiryDay":      passport.get("expiryDay", 0),
-        "nationality":    passport.get("nationality", ""),
-        "name":           passport.get("name", ""),
-        "sex":            passport.get("sex", ""),
-    }
+    # A list means the holder sent several passports; the proof shows one qualifies, not which.
+    if isinstance(passport, list):
+        passport_input = [_to_passport_input(p) for p in passport]
+    else:
+        passport_input = _to_passport_input(passport)
     print(f"[job:{job_id}] Passport input: {passport_input}")
 
     env = os.environ.copy()
@@ -122,6 +179,7 @@ def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_ad
             print(f"[job:{job_id}][{label}] {line}", flush=True)
             collected.append(line)
 
+    prove_started = time.monotonic()
     try:
         proc = subprocess.Popen(
             [EVM_BINARY],
@@ -147,6 +205,7 @@ def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_ad
         proc.kill()
         t_out.join()
         t_err.join()
+        _record_proof_result(False, time.monotonic() - prove_started)
         _update_job(job_id, {"status": "error", "error": "Proof generation timed out after 2 hours"})
         return
     finally:
@@ -154,13 +213,22 @@ def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_ad
             os.unlink(reqs_path)
         except OSError:
             pass
+    prove_seconds = time.monotonic() - prove_started
+
+    # 3 means the prover environment (artifacts, memory, backend) failed rather than the input.
+    if returncode == 3:
+        _record_proof_result(False, prove_seconds)
+        _update_job(job_id, {"status": "error", "error": "Prover unavailable", "stderr": "\n".join(stderr_lines[-20:])})
+        return
 
     if returncode != 0:
+        _record_proof_result(False, prove_seconds)
         _update_job(job_id, {"status": "error", "error": "Proof generation failed", "stderr": "\n".join(stderr_lines[-20:])})
         return
 
     proof_path = os.path.join(PROOF_DIR, f"passport_proof_evm_{job_id}.json")
     if not os.path.exists(proof_path):
+        _record_proof_result(False, prove_seconds)
         _update_job(job_id, {"status": "error", "error": "Proof file not generated"})
         return
 
@@ -172,6 +240,7 @@ def _run_proof_job(job_id: str, passport: dict, wallet_address: str, verifier_ad
     except OSError:
         pass
 
+    _record_proof_result(True, prove_seconds)
     print(f"[job:{job_id}] Done!")
     print(f"[job:{job_id}] proof:        {proof_data.get('proof', '')}")
     print(f"[job:{job_id}] publicValues: {proof_data.get('publicValues', '')}")
@@ -226,6 +295,11 @@ def health():
     return jsonify({"status": "healthy" if binary_ok else "degraded", "evm_binary": EVM_BINARY, "binary_found": binary_ok})
 
 
+@app.route("/metrics", methods=["GET"])
+def metrics():
+    return Response(_render_metrics(), mimetype="text/plain; version=0.0.4")
+
+
 @app.route("/generate-proof", methods=["POST"])
 def generate_proof():
     body = request.get_json(silent=True)
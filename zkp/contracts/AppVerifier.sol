@@ -2,6 +2,7 @@
 pragma solidity ^0.8.20;
 
 import "./PassportRegistry.sol";
+import "./PassportTypes.sol";
 
 interface ISP1Verifier {
     function verifyProof(
@@ -18,6 +19,15 @@ contract AppVerifier {
     address public immutable sp1Verifier;
     bytes32 public immutable passportVKey;
 
+    /// @notice The identity hash configuration every proof must have been made with: `evm`'s
+    /// defaults of SHA-256 over date of birth, nationality and names, kept whole, at commitment
+    /// version 3. The registry binds one wallet per identityHash across every app, so a proof free
+    /// to pick another hash, field set, length or version would give one passport many identities.
+    uint8 public constant IDENTITY_COMMITMENT_HASH = 0;
+    bytes32 public constant IDENTITY_FIELD_MASK = bytes32(uint256(14));
+    uint8 public constant IDENTITY_COMMITMENT_BYTES = 32;
+    uint8 public constant IDENTITY_COMMITMENT_VERSION = 3;
+
     bool public requireAge;
     bool public requireNationality;
     bool public requireSex;
@@ -56,6 +66,11 @@ contract AppVerifier {
     error WalletMismatch();
     error TimestampTooOld();
     error RequirementNotMet();
+    error NotARebind();
+    error UntrustedDocument();
+    error ProofExpired();
+    error DocumentExpired();
+    error IdentityConfigMismatch();
 
     event ClaimVerified(
         bytes32 indexed identityHash,
@@ -64,6 +79,8 @@ contract AppVerifier {
         uint256 timestamp
     );
 
+    event WalletRebound(bytes32 indexed identityHash, address indexed previousWallet, address indexed newWallet);
+
     function verifyClaim(
         bytes calldata publicValues,
         bytes calldata proofBytes
@@ -90,21 +107,25 @@ contract AppVerifier {
                 address verifierAddress,
                 bool isOverMinAge,
                 uint256 proofMinAge,
+                uint256 ageReferenceTimestamp,
                 bool isNationalityMatch,
                 string memory proofNationality,
                 bool isSexMatch,
                 string memory proofSex,
                 uint256 timestamp
-            ) = abi.decode(dataToDecode, (bytes32, address, address, bool, uint256, bool, string, bool, string, uint256));
+            ) = abi.decode(dataToDecode, (bytes32, address, address, bool, uint256, uint256, bool, string, bool, string, uint256));
 
             if (verifierAddress != address(this)) revert VerifierMismatch();
             if (walletAddress != msg.sender) revert WalletMismatch();
             if (timestamp > block.timestamp || block.timestamp - timestamp > 30 days) {
                 revert TimestampTooOld();
             }
+            _checkOutsideDecodedPrefix(publicValues);
 
             if (requireAge) {
                 if (!isOverMinAge || proofMinAge != minAge) revert RequirementNotMet();
+                // Being old enough at a later date says nothing about today.
+                if (ageReferenceTimestamp > timestamp) revert RequirementNotMet();
             }
             if (requireNationality) {
                 if (!isNationalityMatch || keccak256(bytes(proofNationality)) != keccak256(bytes(targetNationality))) revert RequirementNotMet();
@@ -123,6 +144,69 @@ contract AppVerifier {
         }
     }
 
+    /// @dev Checks on fields committed after the prefix verifyClaim decodes, kept out of
+    /// verifyClaim so the full struct doesn't add to its stack.
+    function _checkOutsideDecodedPrefix(bytes calldata publicValues) private view {
+        PassportVerificationOutput memory output = abi.decode(publicValues, (PassportVerificationOutput));
+        // The prover's own freshness bound
+        uint256 validUntil = output.audience_binding.valid_until_timestamp;
+        if (validUntil != 0 && block.timestamp > validUntil) revert ProofExpired();
+        // An `evm --expired` proof is for renewal services and must never register a holder.
+        if (output.expiry_check.is_expired) revert DocumentExpired();
+        _checkIdentityCommitment(output.identity_commitment);
+    }
+
+    function _checkIdentityCommitment(IdentityCommitment memory commitment) private pure {
+        if (
+            commitment.commitment_hash != IDENTITY_COMMITMENT_HASH ||
+            commitment.commitment_field_mask != IDENTITY_FIELD_MASK ||
+            commitment.commitment_bytes != IDENTITY_COMMITMENT_BYTES ||
+            commitment.commitment_version != IDENTITY_COMMITMENT_VERSION
+        ) revert IdentityConfigMismatch();
+    }
+
+    /// @notice Move the caller's identity from a lost wallet to msg.sender, with a proof from
+    /// `evm --rebind-from`. The passport fields alone aren't secret, so the proof must also carry
+    /// a chip signature over matching data groups from a signer that chains to the registry's CSCAs.
+    function rebindWallet(
+        bytes calldata publicValues,
+        bytes calldata proofBytes
+    ) external {
+        try ISP1Verifier(sp1Verifier).verifyProof(passportVKey, publicValues, proofBytes) {
+        } catch {
+            revert InvalidProof();
+        }
+        PassportVerificationOutput memory output = abi.decode(publicValues, (PassportVerificationOutput));
+
+        if (output.verifier_address != address(this)) revert VerifierMismatch();
+        if (output.wallet_address != msg.sender) revert WalletMismatch();
+        if (output.current_timestamp > block.timestamp || block.timestamp - output.current_timestamp > 30 days) {
+            revert TimestampTooOld();
+        }
+        uint256 validUntil = output.audience_binding.valid_until_timestamp;
+        if (validUntil != 0 && block.timestamp > validUntil) revert ProofExpired();
+        if (output.expiry_check.is_expired) revert DocumentExpired();
+        _checkIdentityCommitment(output.identity_commitment);
+        if (!output.wallet_rebind.is_rebind) revert NotARebind();
+
+        DocumentIntegrity memory integrity = output.document_integrity;
+        bytes32 roots = registry.cscaRootCommitment();
+        if (
+            !integrity.is_valid_signature ||
+            !integrity.is_data_group_consistent ||
+            !integrity.is_trusted_issuer ||
+            roots == bytes32(0) ||
+            integrity.issuer_root_commitment != roots
+        ) revert UntrustedDocument();
+
+        address previousWallet = output.wallet_rebind.previous_wallet;
+        registry.rebind(output.identity_hash, previousWallet, msg.sender);
+
+        verified[previousWallet] = false;
+        verified[msg.sender] = true;
+        emit WalletRebound(output.identity_hash, previousWallet, msg.sender);
+    }
+
     function isVerified(address wallet) external view returns (bool) {
         return verified[wallet];
     }
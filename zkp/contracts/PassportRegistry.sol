@@ -10,6 +10,7 @@ contract PassportRegistry {
     mapping(address => bool) public authorizedVerifiers;
 
     event IdentityRegistered(bytes32 indexed commitment, address indexed wallet);
+    event IdentityRebound(bytes32 indexed commitment, address indexed previousWallet, address indexed newWallet);
 
     error NotOwner();
     error NotAuthorizedVerifier();
@@ -18,6 +19,9 @@ contract PassportRegistry {
 
     address public factory;
 
+    /// @notice csca::root_set_commitment of the CSCA list a wallet rebind must chain to
+    bytes32 public cscaRootCommitment;
+
     error NotOwnerOrFactory();
 
     modifier onlyOwner() {
@@ -38,6 +42,10 @@ contract PassportRegistry {
         factory = _factory;
     }
 
+    function setCscaRootCommitment(bytes32 commitment) external onlyOwner {
+        cscaRootCommitment = commitment;
+    }
+
     function addVerifier(address verifier) external onlyOwnerOrFactory {
         authorizedVerifiers[verifier] = true;
     }
@@ -61,6 +69,16 @@ contract PassportRegistry {
         }
     }
 
+    /// @notice Move a commitment to a new wallet. Called by authorized verifiers only, once they
+    /// have checked a rebind proof naming the currently bound wallet.
+    function rebind(bytes32 commitment, address previousWallet, address newWallet) external {
+        if (!authorizedVerifiers[msg.sender]) revert NotAuthorizedVerifier();
+        if (previousWallet == address(0) || commitments[commitment] != previousWallet) revert WalletMismatch();
+
+        commitments[commitment] = newWallet;
+        emit IdentityRebound(commitment, previousWallet, newWallet);
+    }
+
     function getWallet(bytes32 commitment) external view returns (address) {
         return commitments[commitment];
     }
@@ -0,0 +1,138 @@
+// SPDX-License-Identifier: MIT
+pragma solidity ^0.8.20;
+
+// Generated by `cargo run --bin sol_types` from the sol! types in lib. Do not edit by hand.
+// Decode a proof's public values with abi.decode(publicValues, (PassportVerificationOutput)).
+
+struct MinAgeCheck {
+    bool is_over_min_age;
+    uint256 min_age;
+    uint256 reference_timestamp;
+}
+
+struct AgeGrace {
+    bool within_grace;
+    uint256 grace_days;
+}
+
+struct DocumentIntegrity {
+    bool mrz_checksums_valid;
+    bool is_valid_signature;
+    bool is_data_group_consistent;
+    bool is_trusted_issuer;
+    bytes32 issuer_root_commitment;
+    uint8 signature_status;
+}
+
+struct DrinkingCheck {
+    bool can_drink;
+    string drinking_destination;
+    bytes32 drinking_age_table_hash;
+}
+
+struct ExpiryCheck {
+    uint8 validity_months_remaining;
+    bool is_expired;
+    bool has_min_validity;
+    uint16 min_validity_days;
+}
+
+struct MaxAgeBound {
+    bool is_under_max_age;
+    uint256 max_age;
+    uint8 age_bracket_flags;
+    bool is_over_min_age_months;
+    uint32 min_age_months;
+}
+
+struct NullifierBinding {
+    bytes32 external_nullifier;
+    bytes32 nullifier;
+}
+
+struct IssuerAlliance {
+    bool issuer_in_alliance;
+    bytes32 grouping_id;
+}
+
+struct AudienceBinding {
+    bytes32 audience;
+    bytes32 audience_commitment;
+    uint256 valid_until_timestamp;
+    bytes32 time_oracle;
+}
+
+struct IdentityCommitment {
+    uint8 commitment_hash;
+    bytes32 commitment_field_mask;
+    uint8 commitment_version;
+    uint8 commitment_bytes;
+    bytes32 full_record_commitment;
+    PassportSet passport_set;
+}
+
+struct PassportSet {
+    bool any_satisfies;
+    uint8 passport_count;
+    bytes32 set_commitment;
+}
+
+struct NationalityPolicy {
+    NationalitySet allowed;
+    bool is_not_blocked;
+    NationalitySet blocked;
+    bytes32 target_commitment;
+    bool stateless_by_issuer;
+}
+
+struct NationalitySet {
+    bytes32 commitment;
+    uint16 size;
+}
+
+struct WalletRebind {
+    bool is_rebind;
+    address previous_wallet;
+}
+
+struct DocumentMembership {
+    bool is_member;
+    bytes32 merkle_root;
+}
+
+struct LinkedWallets {
+    bytes32 binding_commitment;
+    LinkedWallet[] wallets;
+}
+
+struct LinkedWallet {
+    uint64 chain_id;
+    bytes wallet;
+}
+
+struct PassportVerificationOutput {
+    bytes32 identity_hash;
+    address wallet_address;
+    address verifier_address;
+    MinAgeCheck min_age_check;
+    bool is_nationality_match;
+    string target_nationality;
+    bool is_sex_match;
+    string target_sex;
+    uint256 current_timestamp;
+    AgeGrace age_grace;
+    DocumentIntegrity document_integrity;
+    DrinkingCheck drinking_check;
+    ExpiryCheck expiry_check;
+    MaxAgeBound max_age_bound;
+    NullifierBinding nullifier_binding;
+    IssuerAlliance issuer_alliance;
+    AudienceBinding audience_binding;
+    IdentityCommitment identity_commitment;
+    uint8 disclosed_flags;
+    NationalityPolicy nationality_policy;
+    WalletRebind wallet_rebind;
+    bytes32 policy_commitment;
+    DocumentMembership document_membership;
+    LinkedWallets linked_wallets;
+}
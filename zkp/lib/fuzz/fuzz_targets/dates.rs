@@ -0,0 +1,26 @@
+// Date::new checks dates from any source, and timestamp_to_date turns any u64 the host or an
+// oracle supplies into one; neither may panic. Input: year (2 bytes little-endian), month, day,
+// then a timestamp (8 bytes little-endian).
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passport_verifier_lib::{days_since_epoch, timestamp_to_date, Date};
+
+fuzz_target!(|data: &[u8]| {
+    let Some((date, rest)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+    if let Ok(date) = Date::new(u16::from_le_bytes([date[0], date[1]]), date[2], date[3]) {
+        // A checked date from 1970 on survives the round trip through a timestamp.
+        let days = days_since_epoch(&date);
+        if days >= 0 {
+            assert_eq!(timestamp_to_date(days as u64 * 86400), date);
+        }
+    }
+
+    if let Some((timestamp, _)) = rest.split_first_chunk::<8>() {
+        let date = timestamp_to_date(u64::from_le_bytes(*timestamp));
+        assert!((1..=12).contains(&date.month));
+        assert!((1..=31).contains(&date.day));
+    }
+});
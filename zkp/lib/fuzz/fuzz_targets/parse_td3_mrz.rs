@@ -0,0 +1,28 @@
+// MRZ lines come from OCR or a typed form, so any bytes can reach the parser. It has to answer
+// with an MrzError, never a panic. Input: the evaluation date as year (2 bytes little-endian),
+// month and day, unchecked like a caller's Date, then line 1 and line 2 separated by '\n'.
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use passport_verifier_lib::mrz::{parse_td3_mrz, td3_check_digit_results};
+use passport_verifier_lib::Date;
+
+fuzz_target!(|data: &[u8]| {
+    let Some((current, lines)) = data.split_first_chunk::<4>() else {
+        return;
+    };
+    let current = Date {
+        year: u16::from_le_bytes([current[0], current[1]]),
+        month: current[2],
+        day: current[3],
+    };
+    let lines = String::from_utf8_lossy(lines);
+    let (line1, line2) = lines.split_once('\n').unwrap_or((&lines, ""));
+
+    let _ = td3_check_digit_results(line2);
+    if let Ok(passport) = parse_td3_mrz(line1, line2, &current) {
+        // Whatever parses has dates Date::new accepts.
+        assert!(Date::new(passport.date_of_birth.year, passport.date_of_birth.month, passport.date_of_birth.day).is_ok());
+        assert!(Date::new(passport.date_of_expiry.year, passport.date_of_expiry.month, passport.date_of_expiry.day).is_ok());
+    }
+});
@@ -0,0 +1,51 @@
+// What the aggregation program commits, from the public values of inner proofs it has already
+// verified. The guest verifies and decodes each proof, then calls aggregate_age_outputs, so the
+// checks on what an inner proof may have proved can be exercised without proving.
+//
+// Panics where the guest would abort the proof: an inner proof that withholds its age check,
+// checked another minimum age or a future date, is in expired mode, or hashed its identity under
+// anything but the default configuration.
+use crate::*;
+use alloy_sol_types::private::U256;
+
+pub fn aggregate_age_outputs(passport_vkey: [u8; 32], min_age: u16, outputs: &[PassportVerificationOutput]) -> AggregateAgeOutput {
+    assert!(!outputs.is_empty(), "no proofs to aggregate");
+
+    let mut identity_hashes = Vec::with_capacity(outputs.len());
+    let mut oldest_timestamp = U256::MAX;
+    for output in outputs {
+        assert!(output.disclosed_flags & Disclosures::AGE != 0, "inner proof withholds its age check");
+        assert!(output.min_age_check.min_age == U256::from(min_age), "inner proof checked a different minimum age");
+        // An age reached only at a future reference date isn't an age the holder has yet.
+        assert!(
+            output.min_age_check.reference_timestamp <= output.current_timestamp,
+            "inner proof checked age as of a future date"
+        );
+        // is_expired is committed even with expiry withheld, as AppVerifier relies on.
+        assert!(!output.expiry_check.is_expired, "inner proof is of an expired passport");
+        // over_min_age_count counts distinct identity hashes, so one passport hashed under two
+        // configurations would count as two holders. Only the defaults AppVerifier pins are taken.
+        let commitment = &output.identity_commitment;
+        assert!(
+            commitment.commitment_hash == CommitmentHash::Sha256 as u8
+                && U256::from_be_bytes(commitment.commitment_field_mask.0) == U256::from(CommitmentFields::default().bits())
+                && commitment.commitment_bytes == 32
+                && commitment.commitment_version == COMMITMENT_VERSION,
+            "inner proof hashed its identity under a non-default configuration"
+        );
+        if output.min_age_check.is_over_min_age {
+            identity_hashes.push(output.identity_hash.0);
+        }
+        oldest_timestamp = oldest_timestamp.min(output.current_timestamp);
+    }
+    let (over_min_age_count, identity_set_commitment) = identity_set_commitment(&identity_hashes);
+
+    AggregateAgeOutput {
+        passport_vkey: passport_vkey.into(),
+        min_age,
+        proof_count: outputs.len() as u32,
+        over_min_age_count,
+        identity_set_commitment: identity_set_commitment.into(),
+        oldest_timestamp,
+    }
+}
@@ -0,0 +1,115 @@
+use crate::dg::{
+    expect_tlv, parse_subject_public_key_info, read_tlv, DgError, BIT_STRING_TAG, EXPLICIT_0_TAG, INTEGER_TAG,
+    SEQUENCE_TAG,
+};
+use crate::{verify_passport_signature, DocumentSignature, SignatureAlgorithm};
+use serde::{Deserialize, Serialize};
+
+// Trust anchors for the document signature: an ICAO master list reduced to the SHA-256 of each
+// Country Signing CA's public key, so the guest takes a flat list of hashes instead of
+// certificates.
+
+// Largest root list the guest takes; the ICAO master list has a few hundred CSCAs.
+pub const MAX_CSCA_ROOTS: usize = 1024;
+
+// The certificates linking a document signature to a CSCA, read from EF.SOD and the master list.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct IssuerCertificates {
+    // The Document Signer Certificate (DER X.509) embedded in EF.SOD
+    pub dsc: Vec<u8>,
+    // The CSCA key that signed the DSC, encoded as DocumentSignature::public_key
+    pub csca_algorithm: SignatureAlgorithm,
+    pub csca_public_key: Vec<u8>,
+}
+
+// The value a root list holds for a CSCA: SHA-256 of its key as DocumentSignature::public_key
+// encodes it (PKCS#1 DER for RSA, a SEC1 point for EC).
+pub fn csca_key_hash(public_key: &[u8]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    Sha256::digest(public_key).into()
+}
+
+// SHA-256 over the root hashes, sorted and deduplicated like nationality_set_commitment. Zero for
+// an empty list, which the guest commits when no roots were supplied.
+pub fn root_set_commitment(roots: &[[u8; 32]]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    if roots.is_empty() {
+        return [0u8; 32];
+    }
+    let mut roots = roots.to_vec();
+    roots.sort_unstable();
+    roots.dedup();
+    let mut hasher = Sha256::new();
+    for root in &roots {
+        hasher.update(root);
+    }
+    hasher.finalize().into()
+}
+
+// The document signer is vouched for by a trusted CSCA: the CSCA key is in roots, it signed the
+// DSC (SHA-256, as verify_passport_signature assumes), and the DSC certifies the very key the
+// document signature was checked against.
+pub fn is_trusted_issuer(
+    document_signature: &DocumentSignature,
+    certificates: &IssuerCertificates,
+    roots: &[[u8; 32]],
+) -> bool {
+    let csca_hash = csca_key_hash(&certificates.csca_public_key);
+    // Checks every entry so the cycle count doesn't reveal which root matched.
+    let is_root = roots.iter().fold(false, |found, root| found | (*root == csca_hash));
+    let Ok(dsc) = DscParts::parse(&certificates.dsc) else {
+        return false;
+    };
+    is_root
+        && dsc.algorithm == document_signature.algorithm
+        && dsc.public_key == document_signature.public_key
+        && verify_passport_signature(&DocumentSignature {
+            algorithm: certificates.csca_algorithm,
+            public_key: certificates.csca_public_key.clone(),
+            signed_attributes: dsc.tbs_certificate.to_vec(),
+            signature: dsc.signature.to_vec(),
+        })
+}
+
+struct DscParts<'a> {
+    // The encoded tbsCertificate, which is what the CSCA signed
+    tbs_certificate: &'a [u8],
+    signature: &'a [u8],
+    algorithm: SignatureAlgorithm,
+    public_key: Vec<u8>,
+}
+
+impl<'a> DscParts<'a> {
+    // Certificate ::= SEQUENCE { tbsCertificate, signatureAlgorithm, signatureValue BIT STRING }.
+    fn parse(dsc: &'a [u8]) -> Result<Self, DgError> {
+        let (certificate, _) = expect_tlv(dsc, SEQUENCE_TAG)?;
+        let (tbs, rest) = expect_tlv(certificate, SEQUENCE_TAG)?;
+        let tbs_certificate = &certificate[..certificate.len() - rest.len()];
+        let (_, rest) = expect_tlv(rest, SEQUENCE_TAG)?;
+        let (signature, _) = expect_tlv(rest, BIT_STRING_TAG)?;
+        let signature = match signature.split_first() {
+            Some((0, signature)) => signature,
+            _ => return Err(DgError::Malformed),
+        };
+
+        // TBSCertificate ::= SEQUENCE { [0] version OPTIONAL, serialNumber, signature, issuer,
+        // validity, subject, subjectPublicKeyInfo, ... }
+        let mut rest = tbs;
+        if let Ok((EXPLICIT_0_TAG, _, after_version)) = read_tlv(rest) {
+            rest = after_version;
+        }
+        (_, rest) = expect_tlv(rest, INTEGER_TAG)?;
+        for _ in 0..4 {
+            (_, rest) = expect_tlv(rest, SEQUENCE_TAG)?;
+        }
+        let (algorithm, public_key) = parse_subject_public_key_info(rest)?;
+        Ok(DscParts {
+            tbs_certificate,
+            signature,
+            algorithm,
+            public_key,
+        })
+    }
+}
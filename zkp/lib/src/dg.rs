@@ -0,0 +1,336 @@
+use crate::mrz::{parse_td3_mrz, MrzError};
+use crate::{Date, PassportAttributes, SignatureAlgorithm};
+use serde::{Deserialize, Serialize};
+
+// Data groups as read from the chip over NFC (ICAO 9303 part 10): BER-TLV blobs, starting with
+// the data group's own tag.
+
+const DG1_TAG: u32 = 0x61;
+const MRZ_TAG: u32 = 0x5F1F;
+const DG15_TAG: u32 = 0x6F;
+const SOD_TAG: u32 = 0x77;
+pub(crate) const SEQUENCE_TAG: u32 = 0x30;
+const SET_TAG: u32 = 0x31;
+pub(crate) const INTEGER_TAG: u32 = 0x02;
+const OCTET_STRING_TAG: u32 = 0x04;
+const OID_TAG: u32 = 0x06;
+pub(crate) const BIT_STRING_TAG: u32 = 0x03;
+pub(crate) const EXPLICIT_0_TAG: u32 = 0xA0;
+
+const TD3_MRZ_LENGTH: usize = 88;
+
+// DER contents of the OIDs a DG15 key can be tagged with.
+const RSA_ENCRYPTION_OID: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01];
+const EC_PUBLIC_KEY_OID: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+const PRIME256V1_OID: &[u8] = &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+const BRAINPOOL_P256R1_OID: &[u8] = &[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07];
+const SHA256_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+const SHA384_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x02];
+const SHA512_OID: &[u8] = &[0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x03];
+const MESSAGE_DIGEST_OID: &[u8] = &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04];
+
+// Raw data groups from the chip, which let the guest tie the signed Document Security Object to
+// the MRZ fields being proved.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DataGroups {
+    // EF.DG1, as parsed by parse_dg1
+    pub dg1: Vec<u8>,
+    // EF.SOD: the CMS SignedData over the LDS security object, which lists each data group's hash
+    pub sod: Vec<u8>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DgError {
+    // The blob ends before a tag, length or value it announces, or uses an indefinite length
+    Malformed,
+    UnexpectedTag { expected: u32, found: u32 },
+    // DG1 holds a TD1 or TD2 MRZ rather than a passport's
+    NotTd3(usize),
+    UnsupportedKey,
+    // The SOD lists no hash for this data group
+    MissingDataGroup(u8),
+    Mrz(MrzError),
+}
+
+impl std::fmt::Display for DgError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DgError::Malformed => write!(f, "data group is truncated or malformed"),
+            DgError::UnexpectedTag { expected, found } => {
+                write!(f, "expected tag {:#X}, found {:#X}", expected, found)
+            }
+            DgError::NotTd3(length) => {
+                write!(f, "DG1 MRZ has {} characters, expected {} (TD3)", length, TD3_MRZ_LENGTH)
+            }
+            DgError::UnsupportedKey => write!(f, "DG15 key is not RSA, P-256 or brainpoolP256r1"),
+            DgError::MissingDataGroup(number) => write!(f, "SOD has no hash for DG{}", number),
+            DgError::Mrz(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for DgError {}
+
+impl From<MrzError> for DgError {
+    fn from(err: MrzError) -> Self {
+        DgError::Mrz(err)
+    }
+}
+
+// The two 44-character MRZ lines stored in DG1.
+pub fn dg1_mrz_lines(dg1: &[u8]) -> Result<(String, String), DgError> {
+    let (contents, _) = expect_tlv(dg1, DG1_TAG)?;
+    let (mrz, _) = expect_tlv(contents, MRZ_TAG)?;
+    if mrz.len() != TD3_MRZ_LENGTH {
+        return Err(DgError::NotTd3(mrz.len()));
+    }
+    let line = |bytes: &[u8]| bytes.iter().map(|&b| b as char).collect::<String>();
+    Ok((line(&mrz[..44]), line(&mrz[44..])))
+}
+
+// DG1 parsed exactly as the same MRZ typed in with --mrz, check digits included.
+pub fn parse_dg1(dg1: &[u8], current: &Date) -> Result<PassportAttributes, DgError> {
+    let (line1, line2) = dg1_mrz_lines(dg1)?;
+    Ok(parse_td3_mrz(&line1, &line2, current)?)
+}
+
+// The Active Authentication key from DG15's SubjectPublicKeyInfo, in the encoding
+// DocumentSignature::public_key takes: PKCS#1 DER for RSA, a SEC1 point for EC. Only named
+// curves are recognised; keys with explicit curve parameters are UnsupportedKey.
+pub fn parse_dg15(dg15: &[u8]) -> Result<(SignatureAlgorithm, Vec<u8>), DgError> {
+    let (contents, _) = expect_tlv(dg15, DG15_TAG)?;
+    parse_subject_public_key_info(contents)
+}
+
+// A DER SubjectPublicKeyInfo, as in DG15 and X.509 certificates, reduced to the key encoding
+// DocumentSignature::public_key takes.
+pub(crate) fn parse_subject_public_key_info(spki: &[u8]) -> Result<(SignatureAlgorithm, Vec<u8>), DgError> {
+    let (spki, _) = expect_tlv(spki, SEQUENCE_TAG)?;
+    let (algorithm, rest) = expect_tlv(spki, SEQUENCE_TAG)?;
+    let (key_bits, _) = expect_tlv(rest, BIT_STRING_TAG)?;
+    // The leading byte counts unused bits, which is always 0 for a key.
+    let key = match key_bits.split_first() {
+        Some((0, key)) => key.to_vec(),
+        _ => return Err(DgError::UnsupportedKey),
+    };
+
+    let (oid, params) = expect_tlv(algorithm, OID_TAG)?;
+    let algorithm = match oid {
+        RSA_ENCRYPTION_OID => SignatureAlgorithm::RsaPkcs1,
+        EC_PUBLIC_KEY_OID => match read_tlv(params) {
+            Ok((OID_TAG, PRIME256V1_OID, _)) => SignatureAlgorithm::EcdsaP256,
+            Ok((OID_TAG, BRAINPOOL_P256R1_OID, _)) => SignatureAlgorithm::EcdsaBrainpoolP256r1,
+            _ => return Err(DgError::UnsupportedKey),
+        },
+        _ => return Err(DgError::UnsupportedKey),
+    };
+    Ok((algorithm, key))
+}
+
+// The LDS security object in EF.SOD: ContentInfo -> SignedData -> encapContentInfo -> eContent.
+pub fn lds_security_object(sod: &[u8]) -> Result<&[u8], DgError> {
+    let (content_info, _) = expect_tlv(sod, SOD_TAG)?;
+    let (content_info, _) = expect_tlv(content_info, SEQUENCE_TAG)?;
+    let (_, content) = expect_tlv(content_info, OID_TAG)?;
+    let (signed_data, _) = expect_tlv(content, EXPLICIT_0_TAG)?;
+    let (signed_data, _) = expect_tlv(signed_data, SEQUENCE_TAG)?;
+    let (_, rest) = expect_tlv(signed_data, INTEGER_TAG)?;
+    let (_, rest) = expect_tlv(rest, SET_TAG)?;
+    let (encap_content_info, _) = expect_tlv(rest, SEQUENCE_TAG)?;
+    let (_, content) = expect_tlv(encap_content_info, OID_TAG)?;
+    let (content, _) = expect_tlv(content, EXPLICIT_0_TAG)?;
+    let (lds_security_object, _) = expect_tlv(content, OCTET_STRING_TAG)?;
+    Ok(lds_security_object)
+}
+
+// DG1's hash as recomputed from dg1 equals the one the SOD lists for data group 1, under the
+// SOD's own hash algorithm (SHA-256, SHA-384 or SHA-512).
+pub fn verify_sod_hashes(sod: &[u8], dg1: &[u8]) -> bool {
+    lds_data_group_hash(sod, 1).is_ok_and(|(algorithm, stored)| {
+        hash_with(algorithm, dg1).is_some_and(|computed| computed == stored)
+    })
+}
+
+// signed_attributes (the SET the document signature covers) carries a messageDigest equal to
+// the SHA-256 of the SOD's LDS security object, so the signature vouches for its hash list.
+// SHA-256 matches the digest verify_passport_signature assumes. Read in canonical form, like the
+// signature.
+pub fn sod_matches_signed_attributes(sod: &[u8], signed_attributes: &[u8]) -> bool {
+    use sha2::{Digest, Sha256};
+
+    let Ok(lds_security_object) = lds_security_object(sod) else {
+        return false;
+    };
+    let Ok(signed_attributes) = canonical_signed_attributes(signed_attributes) else {
+        return false;
+    };
+    message_digest(&signed_attributes).is_ok_and(|digest| *digest == Sha256::digest(lds_security_object)[..])
+}
+
+// The bytes a CMS signature over signed attributes covers (RFC 5652, section 5.4): the SET OF
+// Attribute in DER. A SignerInfo stores the set under [0] IMPLICIT, and BER allows long-form
+// lengths and any order, so a reader that lifts the bytes straight out of the SOD may hand over
+// an encoding the signer never hashed. This retags [0] as SET, writes every length in its
+// shortest form and sorts each SET OF by encoding, at every level. DER input comes back unchanged.
+pub fn canonical_signed_attributes(signed_attributes: &[u8]) -> Result<Vec<u8>, DgError> {
+    let (tag, value, rest) = read_tlv(signed_attributes)?;
+    if !rest.is_empty() {
+        return Err(DgError::Malformed);
+    }
+    if tag != SET_TAG && tag != EXPLICIT_0_TAG {
+        return Err(DgError::UnexpectedTag { expected: SET_TAG, found: tag });
+    }
+    let mut out = Vec::with_capacity(signed_attributes.len());
+    write_tlv(&mut out, SET_TAG, &canonical_contents(value, true)?);
+    Ok(out)
+}
+
+// The TLVs in contents re-encoded, constructed ones recursively; sorted when they are the
+// elements of a SET OF.
+fn canonical_contents(mut contents: &[u8], sort: bool) -> Result<Vec<u8>, DgError> {
+    let mut elements = Vec::new();
+    while !contents.is_empty() {
+        let (tag, value, rest) = read_tlv(contents)?;
+        contents = rest;
+        let mut element = Vec::with_capacity(value.len() + 4);
+        if tag_bytes(tag)[0] & 0x20 != 0 {
+            write_tlv(&mut element, tag, &canonical_contents(value, tag == SET_TAG)?);
+        } else {
+            write_tlv(&mut element, tag, value);
+        }
+        elements.push(element);
+    }
+    if sort {
+        elements.sort();
+    }
+    Ok(elements.concat())
+}
+
+// read_tlv's tag back in its encoded bytes.
+fn tag_bytes(tag: u32) -> Vec<u8> {
+    let bytes = tag.to_be_bytes();
+    let first = bytes.iter().position(|&b| b != 0).unwrap_or(3);
+    bytes[first..].to_vec()
+}
+
+// A DER TLV: the tag, the length in its shortest form, then the value.
+fn write_tlv(out: &mut Vec<u8>, tag: u32, value: &[u8]) {
+    out.extend_from_slice(&tag_bytes(tag));
+    let length = value.len();
+    if length < 0x80 {
+        out.push(length as u8);
+    } else {
+        let bytes = length.to_be_bytes();
+        let first = bytes.iter().position(|&b| b != 0).unwrap_or(bytes.len() - 1);
+        out.push(0x80 | (bytes.len() - first) as u8);
+        out.extend_from_slice(&bytes[first..]);
+    }
+    out.extend_from_slice(value);
+}
+
+// DG1 holds exactly these passport fields. current resolves the two-digit birth year as in
+// parse_td3_mrz.
+pub fn dg1_matches_passport(dg1: &[u8], passport: &PassportAttributes, current: &Date) -> bool {
+    let Ok(read) = parse_dg1(dg1, current) else {
+        return false;
+    };
+    read.document_number.as_str() == passport.document_number.as_str()
+        && read.date_of_birth == passport.date_of_birth
+        && read.date_of_expiry == passport.date_of_expiry
+        && read.nationality.as_str() == passport.nationality.as_str()
+        && read.name == passport.name
+        && read.sex == passport.sex
+}
+
+// LDSSecurityObject ::= SEQUENCE { version, hashAlgorithm, SEQUENCE OF { dataGroupNumber,
+// dataGroupHashValue }, ... }
+fn lds_data_group_hash(sod: &[u8], number: u8) -> Result<(&[u8], &[u8]), DgError> {
+    let (lds, _) = expect_tlv(lds_security_object(sod)?, SEQUENCE_TAG)?;
+    let (_, rest) = expect_tlv(lds, INTEGER_TAG)?;
+    let (hash_algorithm, rest) = expect_tlv(rest, SEQUENCE_TAG)?;
+    let (algorithm, _) = expect_tlv(hash_algorithm, OID_TAG)?;
+    let (mut hashes, _) = expect_tlv(rest, SEQUENCE_TAG)?;
+    while !hashes.is_empty() {
+        let (entry, rest) = expect_tlv(hashes, SEQUENCE_TAG)?;
+        hashes = rest;
+        let (group, entry) = expect_tlv(entry, INTEGER_TAG)?;
+        let (hash, _) = expect_tlv(entry, OCTET_STRING_TAG)?;
+        if group == [number] {
+            return Ok((algorithm, hash));
+        }
+    }
+    Err(DgError::MissingDataGroup(number))
+}
+
+// Attributes ::= SET OF SEQUENCE { attrType OID, attrValues SET OF value }
+fn message_digest(signed_attributes: &[u8]) -> Result<&[u8], DgError> {
+    let (mut attributes, _) = expect_tlv(signed_attributes, SET_TAG)?;
+    while !attributes.is_empty() {
+        let (attribute, rest) = expect_tlv(attributes, SEQUENCE_TAG)?;
+        attributes = rest;
+        let (oid, values) = expect_tlv(attribute, OID_TAG)?;
+        if oid == MESSAGE_DIGEST_OID {
+            let (values, _) = expect_tlv(values, SET_TAG)?;
+            let (digest, _) = expect_tlv(values, OCTET_STRING_TAG)?;
+            return Ok(digest);
+        }
+    }
+    Err(DgError::Malformed)
+}
+
+fn hash_with(algorithm: &[u8], data: &[u8]) -> Option<Vec<u8>> {
+    use sha2::{Digest, Sha256, Sha384, Sha512};
+
+    match algorithm {
+        SHA256_OID => Some(Sha256::digest(data).to_vec()),
+        SHA384_OID => Some(Sha384::digest(data).to_vec()),
+        SHA512_OID => Some(Sha512::digest(data).to_vec()),
+        _ => None,
+    }
+}
+
+pub(crate) fn expect_tlv(data: &[u8], expected: u32) -> Result<(&[u8], &[u8]), DgError> {
+    let (found, value, rest) = read_tlv(data)?;
+    if found != expected {
+        return Err(DgError::UnexpectedTag { expected, found });
+    }
+    Ok((value, rest))
+}
+
+// One BER-TLV: a tag of one or more bytes (0x1F in the low bits means more follow, each with the
+// high bit set but the last), a short or long-form definite length, then the value.
+pub(crate) fn read_tlv(data: &[u8]) -> Result<(u32, &[u8], &[u8]), DgError> {
+    let (&first, mut rest) = data.split_first().ok_or(DgError::Malformed)?;
+    let mut tag = first as u32;
+    if first & 0x1F == 0x1F {
+        loop {
+            let (&byte, next) = rest.split_first().ok_or(DgError::Malformed)?;
+            if tag > 0x00FF_FFFF {
+                return Err(DgError::Malformed);
+            }
+            tag = (tag << 8) | byte as u32;
+            rest = next;
+            if byte & 0x80 == 0 {
+                break;
+            }
+        }
+    }
+
+    let (&length_byte, mut rest) = rest.split_first().ok_or(DgError::Malformed)?;
+    let length = if length_byte < 0x80 {
+        length_byte as usize
+    } else {
+        let count = (length_byte & 0x7F) as usize;
+        if count == 0 || count > 3 || rest.len() < count {
+            return Err(DgError::Malformed);
+        }
+        let (bytes, next) = rest.split_at(count);
+        rest = next;
+        bytes.iter().fold(0, |length, &b| (length << 8) | b as usize)
+    };
+    if rest.len() < length {
+        return Err(DgError::Malformed);
+    }
+    let (value, rest) = rest.split_at(length);
+    Ok((tag, value, rest))
+}
@@ -0,0 +1,45 @@
+// Minimum legal drinking age by destination (ICAO code, '<' filler stripped). Where the age
+// differs by drink or region the highest nationwide minimum is used. Countries with a general
+// ban, or not listed, have no drinking age. Changing this table changes `table_hash()`, which
+// verifiers pin, so entries are only ever edited deliberately.
+pub const DRINKING_AGES: &[(&str, u16)] = &[
+    ("AUS", 18),
+    ("BRA", 18),
+    ("CHN", 18),
+    ("D", 18),
+    ("ESP", 18),
+    ("FRA", 18),
+    ("GBR", 18),
+    ("IDN", 21),
+    ("ITA", 18),
+    ("JPN", 20),
+    ("KOR", 19),
+    ("MYS", 21),
+    ("NLD", 18),
+    ("NZL", 18),
+    ("PHL", 18),
+    ("SGP", 18),
+    ("THA", 20),
+    ("USA", 21),
+    ("VNM", 18),
+];
+
+pub fn drinking_age(country: &str) -> Option<u16> {
+    DRINKING_AGES
+        .iter()
+        .find(|(code, _)| *code == country)
+        .map(|(_, age)| *age)
+}
+
+// SHA-256 over each entry as code bytes, a '<' separator and the little-endian age.
+pub fn table_hash() -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    for (code, age) in DRINKING_AGES {
+        hasher.update(code.as_bytes());
+        hasher.update(b"<");
+        hasher.update(age.to_le_bytes());
+    }
+    hasher.finalize().into()
+}
@@ -0,0 +1,374 @@
+// What the passport program commits, computed from its input. The guest is a thin wrapper that
+// reads a GuestInput, calls evaluate_passport and commits the ABI-encoded result, so the host can
+// run the same function to predict a proof's public values without proving.
+//
+// Panics where the guest would abort the proof: an expired passport outside expired mode (and the
+// reverse), oversized lists, or identity fields the hash can't use.
+use crate::*;
+use alloy_sol_types::private::U256;
+
+pub fn evaluate_passport(mut input: GuestInput) -> PassportVerificationOutput {
+    assert!(input.other_passports.len() <= MAX_OTHER_PASSPORTS, "too many passports");
+    // The zero address is never anyone's wallet, and binding it would be a burn binding anyone
+    // could claim. It is committed as an undisclosed wallet, so a verifier sees no binding at all.
+    input.disclosures = Disclosures {
+        wallet: input.disclosures.wallet && input.wallet_address != [0u8; 20],
+        ..input.disclosures
+    };
+
+    // Every passport is evaluated in full, so the cycle count doesn't say which one matched.
+    let other_passports = std::mem::take(&mut input.other_passports);
+    let mut outputs: Vec<PassportVerificationOutput> = std::iter::once(input.clone())
+        .chain(other_passports.into_iter().map(|other| other.in_place_of(&input)))
+        .map(evaluate_checks)
+        .collect();
+    let identity_hashes: Vec<[u8; 32]> = outputs.iter().map(|output| output.identity_hash.0).collect();
+    let (_, set_commitment) = identity_set_commitment(&identity_hashes);
+    let passport_count = outputs.len() as u8;
+    let satisfying = outputs.iter().position(|output| meets_requirements(output, &input));
+    let mut output = outputs.swap_remove(satisfying.unwrap_or(0));
+
+    // Renewal and amnesty services need the opposite guarantee: the document has expired.
+    if input.expired_mode {
+        assert!(output.expiry_check.is_expired, "passport is not expired");
+    } else {
+        assert!(!output.expiry_check.is_expired, "passport is expired");
+    }
+    output.identity_commitment.passport_set = PassportSet {
+        any_satisfies: satisfying.is_some(),
+        passport_count,
+        set_commitment: set_commitment.into(),
+    };
+    withhold(output, &input.disclosures)
+}
+
+// Whether a passport meets every requirement the proof discloses, which is what any_satisfies
+// reports. Checks that weren't asked for pass on their own, e.g. a zero min_age or no max_age.
+fn meets_requirements(output: &PassportVerificationOutput, input: &GuestInput) -> bool {
+    let nationality_required = !input.target_nationality.is_empty()
+        || !input.allowed_nationalities.is_empty()
+        || input.target_nationality_commitment.is_some();
+    let age = output.min_age_check.is_over_min_age
+        && output.max_age_bound.is_over_min_age_months
+        && output.max_age_bound.is_under_max_age;
+    let nationality = (!nationality_required || output.is_nationality_match) && output.nationality_policy.is_not_blocked;
+    let validity = input.min_validity_days == 0 || output.expiry_check.has_min_validity;
+    output.expiry_check.is_expired == input.expired_mode
+        && (!input.disclosures.age || age)
+        && (!input.disclosures.nationality || nationality)
+        && (!input.disclosures.expiry || validity)
+        && (input.target_sex.is_empty() || output.is_sex_match)
+}
+
+// Every check for one passport, before the expiry mode is enforced or anything is withheld.
+fn evaluate_checks(input: GuestInput) -> PassportVerificationOutput {
+    let GuestInput {
+        passport,
+        wallet_address,
+        verifier_address,
+        current_timestamp,
+        min_age,
+        target_nationality,
+        target_sex,
+        mrz_check_digits,
+        grace_days,
+        document_signature,
+        drinking_destination,
+        max_age,
+        external_nullifier,
+        commitment_hash,
+        issuing_country,
+        alliance,
+        verifier_id,
+        identity_fields,
+        disclosures,
+        expired_mode,
+        allowed_nationalities,
+        blocked_nationalities,
+        data_groups,
+        csca_roots,
+        issuer_certificates,
+        reference_timestamp,
+        previous_wallet,
+        commitment_bytes,
+        min_age_months,
+        allowlist_root,
+        allowlist_path,
+        target_nationality_commitment: hidden_target,
+        linked_wallets,
+        min_validity_days,
+        valid_until_timestamp,
+        time_oracle,
+        stateless_by_issuer,
+        audit,
+        reveal_matched_nationality,
+        other_passports: _,
+    } = input;
+    assert!(
+        allowed_nationalities.len() <= groupings::MAX_NATIONALITY_SET_SIZE,
+        "too many acceptable nationalities"
+    );
+    assert!(
+        blocked_nationalities.len() <= groupings::MAX_NATIONALITY_SET_SIZE,
+        "too many blocked nationalities"
+    );
+    assert!(csca_roots.len() <= csca::MAX_CSCA_ROOTS, "too many CSCA roots");
+    assert!(linked_wallets.len() <= MAX_LINKED_WALLETS, "too many linked wallets");
+    assert!(
+        linked_wallets
+            .iter()
+            .all(|wallet| (1..=MAX_WALLET_ADDRESS_BYTES).contains(&wallet.address.len())),
+        "linked wallet address must be 1 to 64 bytes"
+    );
+
+    // A signed time leaves the prover no say over "now"; verifiers check whose key signed it.
+    let time_oracle = match time_oracle {
+        Some(signature) => {
+            assert!(
+                oracle::verify_timestamp(&signature, current_timestamp),
+                "time oracle signature does not cover current_timestamp"
+            );
+            oracle::oracle_key_hash(&signature.public_key)
+        }
+        None => [0u8; 32],
+    };
+
+    let current_date = timestamp_to_date(current_timestamp);
+    let is_expired = is_passport_expired(&passport.date_of_expiry, &current_date);
+
+    // Ages are taken as of the reference date; expiry and freshness stay tied to current_date.
+    let reference_date = timestamp_to_date(reference_timestamp);
+    let is_over_min_age = is_over_age(&passport.date_of_birth, &reference_date, min_age);
+    let is_over_min_age_months = is_over_age_months(&passport.date_of_birth, &reference_date, min_age_months);
+    let is_under_max_age = max_age
+        .is_none_or(|max_age| is_under_age(&passport.date_of_birth, &reference_date, max_age));
+    let age_bracket_flags = age_bracket_flags(&passport.date_of_birth, &reference_date);
+    let within_grace = is_within_age_grace(&passport.date_of_birth, &reference_date, min_age, grace_days);
+    let policy = VerificationPolicy {
+        min_age,
+        allowed_nationalities: match (allowed_nationalities.is_empty(), target_nationality.is_empty()) {
+            (false, _) => allowed_nationalities.clone(),
+            (true, false) => vec![target_nationality.clone()],
+            (true, true) => Vec::new(),
+        },
+        require_unexpired: !expired_mode,
+    };
+    let nationality = iso3166::nationality_for_checks(
+        passport.nationality.as_str(),
+        issuing_country.as_ref().map(Nationality::as_str),
+        stateless_by_issuer,
+    );
+    // With a list of acceptable nationalities only its commitment is revealed, not the list or
+    // which entry matched, unless the verifier asked for the match. A hidden target reveals only
+    // its salted commitment.
+    let (is_nationality_match, target_nationality) = if let Some((commitment, salt)) = hidden_target {
+        (target_nationality_commitment(nationality, &salt) == commitment, String::new())
+    } else if allowed_nationalities.is_empty() {
+        (iso3166::same_nationality(nationality, &target_nationality), target_nationality)
+    } else {
+        let is_match = groupings::contains_nationality(&allowed_nationalities, nationality);
+        let matched = if is_match && reveal_matched_nationality { nationality.to_string() } else { String::new() };
+        (is_match, matched)
+    };
+    // Compliance gating: reject listed countries without learning the holder's nationality.
+    let is_not_blocked = !groupings::contains_nationality(&blocked_nationalities, nationality);
+    let is_sex_match = passport.sex == target_sex;
+    assert!((1..=32).contains(&commitment_bytes), "commitment bytes must be between 1 and 32");
+    let identity_hash = derive_identity_hash_with(&passport, commitment_hash, identity_fields)
+        .expect("identity fields must be non-empty, and the default set for Poseidon");
+    let identity_hash = truncate_commitment(identity_hash, commitment_bytes);
+    let nullifier = derive_nullifier(&passport, &external_nullifier);
+    let audience_binding = match verifier_id {
+        Some(verifier_id) => AudienceBinding {
+            audience: verifier_id.into(),
+            audience_commitment: derive_audience_commitment(&passport, &verifier_id).into(),
+            valid_until_timestamp: U256::from(valid_until_timestamp),
+            time_oracle: time_oracle.into(),
+        },
+        None => AudienceBinding {
+            audience: [0u8; 32].into(),
+            audience_commitment: [0u8; 32].into(),
+            valid_until_timestamp: U256::from(valid_until_timestamp),
+            time_oracle: time_oracle.into(),
+        },
+    };
+    let signature_status = signature_status(document_signature.as_ref());
+    // Without DG1 and the SOD the signature covers attributes that say nothing about these fields.
+    let is_data_group_consistent = document_signature
+        .as_ref()
+        .zip(data_groups.as_ref())
+        .is_some_and(|(signature, data_groups)| {
+            is_data_group_consistent(&passport, signature, data_groups, &current_date)
+        });
+    let is_trusted_issuer = document_signature
+        .as_ref()
+        .zip(issuer_certificates.as_ref())
+        .is_some_and(|(signature, certificates)| csca::is_trusted_issuer(signature, certificates, &csca_roots));
+    let can_drink = can_drink_in(&passport.date_of_birth, &reference_date, &drinking_destination);
+    let validity_months_remaining = validity_months_bucket(&passport.date_of_expiry, &current_date);
+    let has_min_validity = has_min_validity(&passport.date_of_expiry, &current_date, min_validity_days);
+    let issuer_in_alliance = issuing_country
+        .is_some_and(|country| groupings::is_member(&alliance, country.as_str()));
+    let is_allowlisted = allowlist_path
+        .as_ref()
+        .is_some_and(|path| merkle::is_member(passport.document_number.as_str(), path, &allowlist_root));
+    // Sorted so the committed list, like the binding, doesn't depend on the input order.
+    let linked_wallets = LinkedWallets {
+        binding_commitment: if linked_wallets.is_empty() {
+            [0u8; 32].into()
+        } else {
+            derive_wallet_binding(&passport, &linked_wallets).into()
+        },
+        wallets: sorted_wallets(&linked_wallets)
+            .into_iter()
+            .map(|wallet| LinkedWallet {
+                chain_id: wallet.chain_id,
+                wallet: wallet.address.into(),
+            })
+            .collect(),
+    };
+    let mrz_checksums_valid = mrz_check_digits
+        .is_some_and(|digits| mrz_checksums_valid(&passport, &digits));
+
+    PassportVerificationOutput {
+        identity_hash: identity_hash.into(),
+        wallet_address: wallet_address.into(),
+        verifier_address: verifier_address.into(),
+        min_age_check: MinAgeCheck {
+            is_over_min_age,
+            min_age: U256::from(min_age),
+            reference_timestamp: U256::from(reference_timestamp),
+        },
+        is_nationality_match,
+        target_nationality,
+        is_sex_match,
+        target_sex,
+        current_timestamp: U256::from(current_timestamp),
+        age_grace: AgeGrace {
+            within_grace,
+            grace_days: U256::from(grace_days),
+        },
+        document_integrity: DocumentIntegrity {
+            mrz_checksums_valid,
+            is_valid_signature: signature_status == SignatureStatus::Valid,
+            is_data_group_consistent,
+            is_trusted_issuer,
+            issuer_root_commitment: csca::root_set_commitment(&csca_roots).into(),
+            signature_status: signature_status as u8,
+        },
+        drinking_check: DrinkingCheck {
+            can_drink,
+            drinking_destination,
+            drinking_age_table_hash: drinking_age::table_hash().into(),
+        },
+        expiry_check: ExpiryCheck {
+            validity_months_remaining,
+            is_expired,
+            has_min_validity,
+            min_validity_days,
+        },
+        max_age_bound: MaxAgeBound {
+            is_under_max_age,
+            max_age: U256::from(max_age.unwrap_or(0)),
+            age_bracket_flags,
+            is_over_min_age_months,
+            min_age_months,
+        },
+        nullifier_binding: NullifierBinding {
+            external_nullifier: external_nullifier.into(),
+            nullifier: nullifier.into(),
+        },
+        issuer_alliance: IssuerAlliance {
+            issuer_in_alliance,
+            grouping_id: groupings::grouping_id(&alliance).into(),
+        },
+        audience_binding,
+        identity_commitment: IdentityCommitment {
+            commitment_hash: commitment_hash as u8,
+            commitment_field_mask: U256::from(identity_fields.bits()).into(),
+            commitment_version: COMMITMENT_VERSION,
+            commitment_bytes,
+            full_record_commitment: if audit { full_record_commitment(&passport) } else { [0u8; 32] }.into(),
+            // Filled in by evaluate_passport, which sees every passport
+            passport_set: PassportSet {
+                any_satisfies: false,
+                passport_count: 0,
+                set_commitment: [0u8; 32].into(),
+            },
+        },
+        disclosed_flags: disclosures.flags(),
+        nationality_policy: NationalityPolicy {
+            allowed: NationalitySet::from_codes(&allowed_nationalities),
+            is_not_blocked,
+            blocked: NationalitySet::from_codes(&blocked_nationalities),
+            target_commitment: hidden_target.map_or([0u8; 32], |(commitment, _)| commitment).into(),
+            stateless_by_issuer,
+        },
+        wallet_rebind: WalletRebind {
+            is_rebind: previous_wallet.is_some(),
+            previous_wallet: previous_wallet.unwrap_or([0u8; 20]).into(),
+        },
+        policy_commitment: policy.commitment().into(),
+        document_membership: DocumentMembership {
+            is_member: is_allowlisted,
+            merkle_root: allowlist_root.into(),
+        },
+        linked_wallets,
+    }
+}
+
+// Withheld predicates are still evaluated, so every proof costs the same, then zeroed.
+fn withhold(mut output: PassportVerificationOutput, disclosures: &Disclosures) -> PassportVerificationOutput {
+    if !disclosures.age {
+        output.min_age_check = MinAgeCheck {
+            is_over_min_age: false,
+            min_age: U256::ZERO,
+            reference_timestamp: U256::ZERO,
+        };
+        output.age_grace = AgeGrace {
+            within_grace: false,
+            grace_days: U256::ZERO,
+        };
+        output.max_age_bound = MaxAgeBound {
+            is_under_max_age: false,
+            max_age: U256::ZERO,
+            age_bracket_flags: 0,
+            is_over_min_age_months: false,
+            min_age_months: 0,
+        };
+    }
+    if !disclosures.nationality {
+        output.is_nationality_match = false;
+        output.target_nationality = String::new();
+        output.nationality_policy = NationalityPolicy {
+            allowed: NationalitySet::from_codes(&[]),
+            is_not_blocked: false,
+            blocked: NationalitySet::from_codes(&[]),
+            target_commitment: [0u8; 32].into(),
+            stateless_by_issuer: false,
+        };
+    }
+    if !disclosures.expiry {
+        // is_expired is the proof's mode, which evaluate_passport asserts, not a fact about the
+        // holder, so it stays for verifiers that must refuse expired-mode proofs.
+        output.expiry_check = ExpiryCheck {
+            validity_months_remaining: 0,
+            is_expired: output.expiry_check.is_expired,
+            has_min_validity: false,
+            min_validity_days: 0,
+        };
+    }
+    if !disclosures.wallet {
+        output.wallet_address = [0u8; 20].into();
+        output.wallet_rebind = WalletRebind {
+            is_rebind: false,
+            previous_wallet: [0u8; 20].into(),
+        };
+        output.linked_wallets = LinkedWallets {
+            binding_commitment: [0u8; 32].into(),
+            wallets: Vec::new(),
+        };
+    }
+
+    output
+}
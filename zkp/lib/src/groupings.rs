@@ -0,0 +1,67 @@
+// Country groupings a verifier can require the issuing state to belong to (ICAO codes, '<'
+// filler stripped, sorted). Each grouping's `grouping_id()` is committed with the result, so
+// editing a member list changes the id verifiers pin.
+pub const GROUPINGS: &[(&str, &[&str])] = &[
+    ("G7", &["CAN", "D", "FRA", "GBR", "ITA", "JPN", "USA"]),
+    // US Visa Waiver Program. GBR covers British citizens only, not other British nationality codes.
+    (
+        "VWP",
+        &[
+            "AND", "AUS", "AUT", "BEL", "BRN", "CHE", "CHL", "CZE", "D", "DNK", "ESP", "EST", "FIN", "FRA", "GBR",
+            "GRC", "HRV", "HUN", "IRL", "ISL", "ISR", "ITA", "JPN", "KOR", "LIE", "LTU", "LUX", "LVA", "MCO", "MLT",
+            "NLD", "NOR", "NZL", "POL", "PRT", "QAT", "ROU", "SGP", "SMR", "SVK", "SVN", "SWE", "TWN",
+        ],
+    ),
+];
+
+pub fn grouping(name: &str) -> Option<&'static [&'static str]> {
+    GROUPINGS
+        .iter()
+        .find(|(grouping, _)| *grouping == name)
+        .map(|(_, members)| *members)
+}
+
+pub fn is_member(name: &str, country: &str) -> bool {
+    grouping(name).is_some_and(|members| members.contains(&country))
+}
+
+// SHA-256 over the grouping name followed by each member, every entry '<'-terminated. An unknown
+// name hashes with no members, so its id still differs from every real grouping.
+pub fn grouping_id(name: &str) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(name.as_bytes());
+    hasher.update(b"<");
+    for member in grouping(name).unwrap_or_default() {
+        hasher.update(member.as_bytes());
+        hasher.update(b"<");
+    }
+    hasher.finalize().into()
+}
+
+// Largest acceptable-nationality list the guest takes. The list is checked entry by entry, so its
+// length drives the cycle count; there are fewer than 256 ICAO nationality codes.
+pub const MAX_NATIONALITY_SET_SIZE: usize = 256;
+
+// Compares against every entry rather than stopping at the first match, so neither the answer
+// nor the cycle count depends on where in the list the nationality appears.
+pub fn contains_nationality(codes: &[String], nationality: &str) -> bool {
+    codes.iter().fold(false, |found, code| found | (code == nationality))
+}
+
+// SHA-256 over a verifier-supplied list of acceptable nationalities, sorted and deduplicated so
+// the order it was written in doesn't change the commitment, each code '<'-terminated.
+pub fn nationality_set_commitment(codes: &[String]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut codes: Vec<&str> = codes.iter().map(String::as_str).collect();
+    codes.sort_unstable();
+    codes.dedup();
+    let mut hasher = Sha256::new();
+    for code in codes {
+        hasher.update(code.as_bytes());
+        hasher.update(b"<");
+    }
+    hasher.finalize().into()
+}
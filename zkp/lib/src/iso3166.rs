@@ -0,0 +1,320 @@
+// Nationality codes as passports carry them: ISO 3166-1 alpha-3, except where ICAO 9303 departs
+// from it (Germany is "D", plus the British, UN and stateless codes that have no ISO entry).
+// Requirements written as an alpha-2 code or an English country name are mapped onto these, so
+// "MY", "Malaysia" and "MYS" all mean the code printed in a Malaysian passport.
+
+// (ICAO code, ISO alpha-2 or "" for ICAO-only codes, English short name), sorted by code.
+pub const COUNTRIES: &[(&str, &str, &str)] = &[
+    ("ABW", "AW", "Aruba"),
+    ("AFG", "AF", "Afghanistan"),
+    ("AGO", "AO", "Angola"),
+    ("AIA", "AI", "Anguilla"),
+    ("ALA", "AX", "Aland Islands"),
+    ("ALB", "AL", "Albania"),
+    ("AND", "AD", "Andorra"),
+    ("ARE", "AE", "United Arab Emirates"),
+    ("ARG", "AR", "Argentina"),
+    ("ARM", "AM", "Armenia"),
+    ("ASM", "AS", "American Samoa"),
+    ("ATA", "AQ", "Antarctica"),
+    ("ATF", "TF", "French Southern Territories"),
+    ("ATG", "AG", "Antigua and Barbuda"),
+    ("AUS", "AU", "Australia"),
+    ("AUT", "AT", "Austria"),
+    ("AZE", "AZ", "Azerbaijan"),
+    ("BDI", "BI", "Burundi"),
+    ("BEL", "BE", "Belgium"),
+    ("BEN", "BJ", "Benin"),
+    ("BES", "BQ", "Bonaire, Sint Eustatius and Saba"),
+    ("BFA", "BF", "Burkina Faso"),
+    ("BGD", "BD", "Bangladesh"),
+    ("BGR", "BG", "Bulgaria"),
+    ("BHR", "BH", "Bahrain"),
+    ("BHS", "BS", "Bahamas"),
+    ("BIH", "BA", "Bosnia and Herzegovina"),
+    ("BLM", "BL", "Saint Barthelemy"),
+    ("BLR", "BY", "Belarus"),
+    ("BLZ", "BZ", "Belize"),
+    ("BMU", "BM", "Bermuda"),
+    ("BOL", "BO", "Bolivia"),
+    ("BRA", "BR", "Brazil"),
+    ("BRB", "BB", "Barbados"),
+    ("BRN", "BN", "Brunei"),
+    ("BTN", "BT", "Bhutan"),
+    ("BVT", "BV", "Bouvet Island"),
+    ("BWA", "BW", "Botswana"),
+    ("CAF", "CF", "Central African Republic"),
+    ("CAN", "CA", "Canada"),
+    ("CCK", "CC", "Cocos (Keeling) Islands"),
+    ("CHE", "CH", "Switzerland"),
+    ("CHL", "CL", "Chile"),
+    ("CHN", "CN", "China"),
+    ("CIV", "CI", "Cote d'Ivoire"),
+    ("CMR", "CM", "Cameroon"),
+    ("COD", "CD", "Democratic Republic of the Congo"),
+    ("COG", "CG", "Congo"),
+    ("COK", "CK", "Cook Islands"),
+    ("COL", "CO", "Colombia"),
+    ("COM", "KM", "Comoros"),
+    ("CPV", "CV", "Cabo Verde"),
+    ("CRI", "CR", "Costa Rica"),
+    ("CUB", "CU", "Cuba"),
+    ("CUW", "CW", "Curacao"),
+    ("CXR", "CX", "Christmas Island"),
+    ("CYM", "KY", "Cayman Islands"),
+    ("CYP", "CY", "Cyprus"),
+    ("CZE", "CZ", "Czechia"),
+    ("D", "DE", "Germany"),
+    ("DJI", "DJ", "Djibouti"),
+    ("DMA", "DM", "Dominica"),
+    ("DNK", "DK", "Denmark"),
+    ("DOM", "DO", "Dominican Republic"),
+    ("DZA", "DZ", "Algeria"),
+    ("ECU", "EC", "Ecuador"),
+    ("EGY", "EG", "Egypt"),
+    ("ERI", "ER", "Eritrea"),
+    ("ESH", "EH", "Western Sahara"),
+    ("ESP", "ES", "Spain"),
+    ("EST", "EE", "Estonia"),
+    ("ETH", "ET", "Ethiopia"),
+    ("EUE", "", "European Union"),
+    ("FIN", "FI", "Finland"),
+    ("FJI", "FJ", "Fiji"),
+    ("FLK", "FK", "Falkland Islands"),
+    ("FRA", "FR", "France"),
+    ("FRO", "FO", "Faroe Islands"),
+    ("FSM", "FM", "Micronesia"),
+    ("GAB", "GA", "Gabon"),
+    ("GBD", "", "British Overseas Territories Citizen"),
+    ("GBN", "", "British National (Overseas)"),
+    ("GBO", "", "British Overseas Citizen"),
+    ("GBP", "", "British Protected Person"),
+    ("GBR", "GB", "United Kingdom"),
+    ("GBS", "", "British Subject"),
+    ("GEO", "GE", "Georgia"),
+    ("GGY", "GG", "Guernsey"),
+    ("GHA", "GH", "Ghana"),
+    ("GIB", "GI", "Gibraltar"),
+    ("GIN", "GN", "Guinea"),
+    ("GLP", "GP", "Guadeloupe"),
+    ("GMB", "GM", "Gambia"),
+    ("GNB", "GW", "Guinea-Bissau"),
+    ("GNQ", "GQ", "Equatorial Guinea"),
+    ("GRC", "GR", "Greece"),
+    ("GRD", "GD", "Grenada"),
+    ("GRL", "GL", "Greenland"),
+    ("GTM", "GT", "Guatemala"),
+    ("GUF", "GF", "French Guiana"),
+    ("GUM", "GU", "Guam"),
+    ("GUY", "GY", "Guyana"),
+    ("HKG", "HK", "Hong Kong"),
+    ("HMD", "HM", "Heard Island and McDonald Islands"),
+    ("HND", "HN", "Honduras"),
+    ("HRV", "HR", "Croatia"),
+    ("HTI", "HT", "Haiti"),
+    ("HUN", "HU", "Hungary"),
+    ("IDN", "ID", "Indonesia"),
+    ("IMN", "IM", "Isle of Man"),
+    ("IND", "IN", "India"),
+    ("IOT", "IO", "British Indian Ocean Territory"),
+    ("IRL", "IE", "Ireland"),
+    ("IRN", "IR", "Iran"),
+    ("IRQ", "IQ", "Iraq"),
+    ("ISL", "IS", "Iceland"),
+    ("ISR", "IL", "Israel"),
+    ("ITA", "IT", "Italy"),
+    ("JAM", "JM", "Jamaica"),
+    ("JEY", "JE", "Jersey"),
+    ("JOR", "JO", "Jordan"),
+    ("JPN", "JP", "Japan"),
+    ("KAZ", "KZ", "Kazakhstan"),
+    ("KEN", "KE", "Kenya"),
+    ("KGZ", "KG", "Kyrgyzstan"),
+    ("KHM", "KH", "Cambodia"),
+    ("KIR", "KI", "Kiribati"),
+    ("KNA", "KN", "Saint Kitts and Nevis"),
+    ("KOR", "KR", "South Korea"),
+    ("KWT", "KW", "Kuwait"),
+    ("LAO", "LA", "Laos"),
+    ("LBN", "LB", "Lebanon"),
+    ("LBR", "LR", "Liberia"),
+    ("LBY", "LY", "Libya"),
+    ("LCA", "LC", "Saint Lucia"),
+    ("LIE", "LI", "Liechtenstein"),
+    ("LKA", "LK", "Sri Lanka"),
+    ("LSO", "LS", "Lesotho"),
+    ("LTU", "LT", "Lithuania"),
+    ("LUX", "LU", "Luxembourg"),
+    ("LVA", "LV", "Latvia"),
+    ("MAC", "MO", "Macao"),
+    ("MAF", "MF", "Saint Martin"),
+    ("MAR", "MA", "Morocco"),
+    ("MCO", "MC", "Monaco"),
+    ("MDA", "MD", "Moldova"),
+    ("MDG", "MG", "Madagascar"),
+    ("MDV", "MV", "Maldives"),
+    ("MEX", "MX", "Mexico"),
+    ("MHL", "MH", "Marshall Islands"),
+    ("MKD", "MK", "North Macedonia"),
+    ("MLI", "ML", "Mali"),
+    ("MLT", "MT", "Malta"),
+    ("MMR", "MM", "Myanmar"),
+    ("MNE", "ME", "Montenegro"),
+    ("MNG", "MN", "Mongolia"),
+    ("MNP", "MP", "Northern Mariana Islands"),
+    ("MOZ", "MZ", "Mozambique"),
+    ("MRT", "MR", "Mauritania"),
+    ("MSR", "MS", "Montserrat"),
+    ("MTQ", "MQ", "Martinique"),
+    ("MUS", "MU", "Mauritius"),
+    ("MWI", "MW", "Malawi"),
+    ("MYS", "MY", "Malaysia"),
+    ("MYT", "YT", "Mayotte"),
+    ("NAM", "NA", "Namibia"),
+    ("NCL", "NC", "New Caledonia"),
+    ("NER", "NE", "Niger"),
+    ("NFK", "NF", "Norfolk Island"),
+    ("NGA", "NG", "Nigeria"),
+    ("NIC", "NI", "Nicaragua"),
+    ("NIU", "NU", "Niue"),
+    ("NLD", "NL", "Netherlands"),
+    ("NOR", "NO", "Norway"),
+    ("NPL", "NP", "Nepal"),
+    ("NRU", "NR", "Nauru"),
+    ("NZL", "NZ", "New Zealand"),
+    ("OMN", "OM", "Oman"),
+    ("PAK", "PK", "Pakistan"),
+    ("PAN", "PA", "Panama"),
+    ("PCN", "PN", "Pitcairn"),
+    ("PER", "PE", "Peru"),
+    ("PHL", "PH", "Philippines"),
+    ("PLW", "PW", "Palau"),
+    ("PNG", "PG", "Papua New Guinea"),
+    ("POL", "PL", "Poland"),
+    ("PRI", "PR", "Puerto Rico"),
+    ("PRK", "KP", "North Korea"),
+    ("PRT", "PT", "Portugal"),
+    ("PRY", "PY", "Paraguay"),
+    ("PSE", "PS", "Palestine"),
+    ("PYF", "PF", "French Polynesia"),
+    ("QAT", "QA", "Qatar"),
+    ("REU", "RE", "Reunion"),
+    ("RKS", "XK", "Kosovo"),
+    ("ROU", "RO", "Romania"),
+    ("RUS", "RU", "Russia"),
+    ("RWA", "RW", "Rwanda"),
+    ("SAU", "SA", "Saudi Arabia"),
+    ("SDN", "SD", "Sudan"),
+    ("SEN", "SN", "Senegal"),
+    ("SGP", "SG", "Singapore"),
+    ("SGS", "GS", "South Georgia and the South Sandwich Islands"),
+    ("SHN", "SH", "Saint Helena"),
+    ("SJM", "SJ", "Svalbard and Jan Mayen"),
+    ("SLB", "SB", "Solomon Islands"),
+    ("SLE", "SL", "Sierra Leone"),
+    ("SLV", "SV", "El Salvador"),
+    ("SMR", "SM", "San Marino"),
+    ("SOM", "SO", "Somalia"),
+    ("SPM", "PM", "Saint Pierre and Miquelon"),
+    ("SRB", "RS", "Serbia"),
+    ("SSD", "SS", "South Sudan"),
+    ("STP", "ST", "Sao Tome and Principe"),
+    ("SUR", "SR", "Suriname"),
+    ("SVK", "SK", "Slovakia"),
+    ("SVN", "SI", "Slovenia"),
+    ("SWE", "SE", "Sweden"),
+    ("SWZ", "SZ", "Eswatini"),
+    ("SXM", "SX", "Sint Maarten"),
+    ("SYC", "SC", "Seychelles"),
+    ("SYR", "SY", "Syria"),
+    ("TCA", "TC", "Turks and Caicos Islands"),
+    ("TCD", "TD", "Chad"),
+    ("TGO", "TG", "Togo"),
+    ("THA", "TH", "Thailand"),
+    ("TJK", "TJ", "Tajikistan"),
+    ("TKL", "TK", "Tokelau"),
+    ("TKM", "TM", "Turkmenistan"),
+    ("TLS", "TL", "Timor-Leste"),
+    ("TON", "TO", "Tonga"),
+    ("TTO", "TT", "Trinidad and Tobago"),
+    ("TUN", "TN", "Tunisia"),
+    ("TUR", "TR", "Turkey"),
+    ("TUV", "TV", "Tuvalu"),
+    ("TWN", "TW", "Taiwan"),
+    ("TZA", "TZ", "Tanzania"),
+    ("UGA", "UG", "Uganda"),
+    ("UKR", "UA", "Ukraine"),
+    ("UMI", "UM", "United States Minor Outlying Islands"),
+    ("UNA", "", "United Nations Specialized Agency"),
+    ("UNK", "", "United Nations Interim Administration in Kosovo"),
+    ("UNO", "", "United Nations Organization"),
+    ("URY", "UY", "Uruguay"),
+    ("USA", "US", "United States"),
+    // The fictional issuing state of ICAO 9303's specimen documents
+    ("UTO", "", "Utopia"),
+    ("UZB", "UZ", "Uzbekistan"),
+    ("VAT", "VA", "Holy See"),
+    ("VCT", "VC", "Saint Vincent and the Grenadines"),
+    ("VEN", "VE", "Venezuela"),
+    ("VGB", "VG", "British Virgin Islands"),
+    ("VIR", "VI", "United States Virgin Islands"),
+    ("VNM", "VN", "Vietnam"),
+    ("VUT", "VU", "Vanuatu"),
+    ("WLF", "WF", "Wallis and Futuna"),
+    ("WSM", "WS", "Samoa"),
+    ("XOM", "", "Sovereign Military Order of Malta"),
+    ("XXA", "", "Stateless person"),
+    ("XXB", "", "Refugee (1951 Convention)"),
+    ("XXC", "", "Refugee (other)"),
+    ("XXX", "", "Unspecified nationality"),
+    ("YEM", "YE", "Yemen"),
+    ("ZAF", "ZA", "South Africa"),
+    ("ZMB", "ZM", "Zambia"),
+    ("ZWE", "ZW", "Zimbabwe"),
+];
+
+// The ICAO code for a nationality given as that code (with or without '<' filler), an ISO
+// alpha-2 code, or an English short name, ignoring case and surrounding spaces. None for
+// anything else, so a typo surfaces instead of never matching.
+pub fn normalize_nationality(input: &str) -> Option<&'static str> {
+    let input = input.trim().trim_end_matches('<');
+    COUNTRIES
+        .iter()
+        .find(|(code, alpha2, name)| {
+            code.eq_ignore_ascii_case(input)
+                || (!alpha2.is_empty() && alpha2.eq_ignore_ascii_case(input))
+                || name.eq_ignore_ascii_case(input)
+        })
+        .map(|(code, _, _)| *code)
+}
+
+// Both sides name the same nationality. Inputs that aren't recognised compare as written, so a
+// code missing from COUNTRIES still matches itself.
+pub fn same_nationality(a: &str, b: &str) -> bool {
+    match (normalize_nationality(a), normalize_nationality(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => a == b,
+    }
+}
+
+// ICAO 9303's codes for holders without a nationality: stateless persons (XXA) and refugees
+// under the 1951 Convention (XXB) or otherwise (XXC). XXX, an unspecified nationality, is not
+// among them. Policy: these codes are ordinary entries everywhere else, so a target or
+// allow-list only accepts a stateless holder when it names the code, and a blocklist only rejects
+// one when it names the code. With stateless_by_issuer the nationality checks use the issuing
+// state of the travel document instead; see nationality_for_checks.
+pub const STATELESS_CODES: [&str; 3] = ["XXA", "XXB", "XXC"];
+
+pub fn is_stateless(nationality: &str) -> bool {
+    normalize_nationality(nationality).is_some_and(|code| STATELESS_CODES.contains(&code))
+}
+
+// The nationality the target, allow-list and blocklist are checked against: the holder's own,
+// or for a stateless holder under stateless_by_issuer the state that issued the document. A
+// stateless holder without a known issuing state keeps their own code.
+pub fn nationality_for_checks<'a>(nationality: &'a str, issuing_country: Option<&'a str>, stateless_by_issuer: bool) -> &'a str {
+    match issuing_country {
+        Some(issuer) if stateless_by_issuer && is_stateless(nationality) => issuer,
+        _ => nationality,
+    }
+}
@@ -1,35 +1,896 @@
+pub mod aggregate;
+pub mod csca;
+pub mod dg;
+pub mod drinking_age;
+pub mod evaluate;
+pub mod groupings;
+pub mod iso3166;
+pub mod merkle;
+pub mod mrz;
+pub mod oracle;
+
 use alloy_sol_types::sol;
 use serde::{Deserialize, Serialize};
 
-#[derive(Serialize, Deserialize, Clone, Debug)]
+// JSON and other human-readable formats write a Date as "YYYY-MM-DD". Binary formats such as the
+// bincode behind SP1Stdin keep the plain year, month, day layout, so the guest's input is unchanged.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Date {
     pub year: u16,
     pub month: u8,
     pub day: u8,
 }
 
+#[derive(Serialize, Deserialize)]
+struct DateFields {
+    year: u16,
+    month: u8,
+    day: u8,
+}
+
+impl Serialize for Date {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&format_args!("{:04}-{:02}-{:02}", self.year, self.month, self.day))
+        } else {
+            DateFields {
+                year: self.year,
+                month: self.month,
+                day: self.day,
+            }
+            .serialize(serializer)
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Date {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        use serde::de::Error;
+
+        if !deserializer.is_human_readable() {
+            let DateFields { year, month, day } = DateFields::deserialize(deserializer)?;
+            return Ok(Date { year, month, day });
+        }
+        let s = String::deserialize(deserializer)?;
+        let invalid = || D::Error::custom(format!("invalid date {:?}: expected YYYY-MM-DD", s));
+        let mut parts = s.splitn(3, '-');
+        let mut next = || parts.next().filter(|p| !p.is_empty() && p.bytes().all(|c| c.is_ascii_digit()));
+        let year = next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let month = next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        let day = next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+        Date::new(year, month, day).map_err(|e| D::Error::custom(format!("invalid date {:?}: {}", s, e)))
+    }
+}
+
+impl Date {
+    // The fields stay public, but this is the checked way to build a Date.
+    pub fn new(year: u16, month: u8, day: u8) -> Result<Self, DateError> {
+        if !(1..=12).contains(&month) {
+            return Err(DateError::InvalidMonth(month));
+        }
+        if day == 0 || day > days_in_month(year, month) {
+            return Err(DateError::InvalidDay { year, month, day });
+        }
+        Ok(Date { year, month, day })
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DateError {
+    InvalidMonth(u8),
+    InvalidDay { year: u16, month: u8, day: u8 },
+}
+
+impl std::fmt::Display for DateError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DateError::InvalidMonth(month) => write!(f, "invalid month {}: expected 1-12", month),
+            DateError::InvalidDay { year, month, day } => {
+                write!(f, "invalid day {} for {:04}-{:02}", day, year, month)
+            }
+        }
+    }
+}
+
+impl std::error::Error for DateError {}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AttributeError {
+    InvalidNationality(String),
+    InvalidDocumentNumber(String),
+}
+
+impl std::fmt::Display for AttributeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            AttributeError::InvalidNationality(s) => {
+                write!(f, "invalid nationality {:?}: expected a 1-3 letter ICAO code", s)
+            }
+            AttributeError::InvalidDocumentNumber(s) => {
+                write!(f, "invalid document number {:?}: expected 1-20 letters or digits", s)
+            }
+        }
+    }
+}
+
+impl std::error::Error for AttributeError {}
+
+// ICAO nationality code as read from the MRZ with '<' filler stripped (e.g. "MYS", or "D" for Germany).
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Nationality(String);
+
+impl Nationality {
+    pub fn new(code: impl Into<String>) -> Result<Self, AttributeError> {
+        let code = code.into().trim().to_ascii_uppercase();
+        if code.is_empty() || code.len() > 3 || !code.bytes().all(|c| c.is_ascii_uppercase()) {
+            return Err(AttributeError::InvalidNationality(code));
+        }
+        Ok(Nationality(code))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+// Passport number with '<' filler stripped. TD3 numbers over 9 characters continue in the optional data field.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct DocumentNumber(String);
+
+impl DocumentNumber {
+    pub fn new(number: impl Into<String>) -> Result<Self, AttributeError> {
+        let number = number.into().trim().to_ascii_uppercase();
+        if number.is_empty() || number.len() > 20 || !number.bytes().all(|c| c.is_ascii_alphanumeric()) {
+            return Err(AttributeError::InvalidDocumentNumber(number));
+        }
+        Ok(DocumentNumber(number))
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+macro_rules! impl_string_newtype {
+    ($name:ident) => {
+        impl TryFrom<String> for $name {
+            type Error = AttributeError;
+
+            fn try_from(value: String) -> Result<Self, Self::Error> {
+                $name::new(value)
+            }
+        }
+
+        impl From<$name> for String {
+            fn from(value: $name) -> String {
+                value.0
+            }
+        }
+
+        impl std::fmt::Display for $name {
+            fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+                f.write_str(&self.0)
+            }
+        }
+    };
+}
+
+impl_string_newtype!(Nationality);
+impl_string_newtype!(DocumentNumber);
+
+// JSON form: camelCase keys, dates as "YYYY-MM-DD", e.g.
+//   {"documentNumber": "L898902C3", "dateOfBirth": "1974-08-12", "dateOfExpiry": "2030-04-15",
+//    "nationality": "UTO", "name": "ANNA MARIA ERIKSSON", "sex": "F"}
 #[derive(Serialize, Deserialize, Clone, Debug)]
+#[serde(rename_all = "camelCase")]
 pub struct PassportAttributes {
-    pub document_number: String,
+    pub document_number: DocumentNumber,
     pub date_of_birth: Date,
     pub date_of_expiry: Date,
-    pub nationality: String,
+    pub nationality: Nationality,
     pub name: String,
     pub sex: String,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FieldError {
+    Missing(&'static str),
+    InvalidDate(&'static str),
+    Invalid(AttributeError),
+    // Every field parsed, but together they fail PassportAttributes::check_consistency
+    Inconsistent(ValidationError),
+}
+
+impl std::fmt::Display for FieldError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FieldError::Missing(field) => write!(f, "missing {}", field),
+            FieldError::InvalidDate(field) => write!(f, "invalid {}: month or day out of range", field),
+            FieldError::Invalid(err) => err.fmt(f),
+            FieldError::Inconsistent(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for FieldError {}
+
+// Collects passport fields and validates them all at once, reporting every problem rather than
+// stopping at the first.
+#[derive(Clone, Debug, Default)]
+pub struct PassportAttributesBuilder {
+    document_number: Option<String>,
+    date_of_birth: Option<Date>,
+    date_of_expiry: Option<Date>,
+    nationality: Option<String>,
+    name: Option<String>,
+    sex: Option<String>,
+}
+
+impl PassportAttributesBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn document_number(mut self, number: impl Into<String>) -> Self {
+        self.document_number = Some(number.into());
+        self
+    }
+
+    pub fn birth_date(mut self, date: Date) -> Self {
+        self.date_of_birth = Some(date);
+        self
+    }
+
+    pub fn expiry_date(mut self, date: Date) -> Self {
+        self.date_of_expiry = Some(date);
+        self
+    }
+
+    pub fn nationality(mut self, code: impl Into<String>) -> Self {
+        self.nationality = Some(code.into());
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.name = Some(name.into());
+        self
+    }
+
+    pub fn sex(mut self, sex: impl Into<String>) -> Self {
+        self.sex = Some(sex.into());
+        self
+    }
+
+    pub fn build(self) -> Result<PassportAttributes, Vec<FieldError>> {
+        let mut errors = Vec::new();
+
+        let document_number = match self.document_number {
+            Some(number) => DocumentNumber::new(number).map_err(|e| errors.push(FieldError::Invalid(e))).ok(),
+            None => {
+                errors.push(FieldError::Missing("document number"));
+                None
+            }
+        };
+        let nationality = match self.nationality {
+            Some(code) => Nationality::new(code).map_err(|e| errors.push(FieldError::Invalid(e))).ok(),
+            None => {
+                errors.push(FieldError::Missing("nationality"));
+                None
+            }
+        };
+        let mut check_date = |date: Option<Date>, field: &'static str| match date {
+            Some(date) if Date::new(date.year, date.month, date.day).is_ok() => Some(date),
+            Some(_) => {
+                errors.push(FieldError::InvalidDate(field));
+                None
+            }
+            None => {
+                errors.push(FieldError::Missing(field));
+                None
+            }
+        };
+        let date_of_birth = check_date(self.date_of_birth, "date of birth");
+        let date_of_expiry = check_date(self.date_of_expiry, "date of expiry");
+        if self.name.is_none() {
+            errors.push(FieldError::Missing("name"));
+        }
+        if self.sex.is_none() {
+            errors.push(FieldError::Missing("sex"));
+        }
+
+        match (document_number, date_of_birth, date_of_expiry, nationality, self.name, self.sex) {
+            (Some(document_number), Some(date_of_birth), Some(date_of_expiry), Some(nationality), Some(name), Some(sex))
+                if errors.is_empty() =>
+            {
+                let passport = PassportAttributes {
+                    document_number,
+                    date_of_birth,
+                    date_of_expiry,
+                    nationality,
+                    name,
+                    sex,
+                };
+                passport.check_consistency().map_err(|e| vec![FieldError::Inconsistent(e)])?;
+                Ok(passport)
+            }
+            _ => Err(errors),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    InvalidDate(&'static str, DateError),
+    ExpiryNotAfterBirth,
+    BirthAfterReference,
+    EmptyName,
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ValidationError::InvalidDate(field, err) => write!(f, "{}: {}", field, err),
+            ValidationError::ExpiryNotAfterBirth => f.write_str("date of expiry is not after the date of birth"),
+            ValidationError::BirthAfterReference => f.write_str("date of birth is in the future"),
+            ValidationError::EmptyName => f.write_str("name is empty"),
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl PassportAttributes {
+    // Catches data no proof would be meaningful for, before paying for one: dates that aren't on
+    // the calendar (the fields are public, so Date::new may have been bypassed), an expiry on or
+    // before the birth date, a birth after `reference`, or an empty name. The document number and
+    // nationality can't be empty, since their types reject that.
+    pub fn validate(&self, reference: &Date) -> Result<(), ValidationError> {
+        self.check_consistency()?;
+        if days_since_epoch(&self.date_of_birth) > days_since_epoch(reference) {
+            return Err(ValidationError::BirthAfterReference);
+        }
+        Ok(())
+    }
+
+    // The checks of validate that don't need a reference date, which PassportAttributesBuilder
+    // runs on every passport it builds.
+    pub fn check_consistency(&self) -> Result<(), ValidationError> {
+        for (field, date) in [("date of birth", &self.date_of_birth), ("date of expiry", &self.date_of_expiry)] {
+            Date::new(date.year, date.month, date.day).map_err(|e| ValidationError::InvalidDate(field, e))?;
+        }
+        if days_since_epoch(&self.date_of_expiry) <= days_since_epoch(&self.date_of_birth) {
+            return Err(ValidationError::ExpiryNotAfterBirth);
+        }
+        if self.name.trim().is_empty() {
+            return Err(ValidationError::EmptyName);
+        }
+        Ok(())
+    }
+}
+
+// Check digits as printed in the passport's MRZ, used to cross-check the structured fields.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct MrzCheckDigits {
+    pub document_number: u8,
+    pub date_of_birth: u8,
+    pub date_of_expiry: u8,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SignatureAlgorithm {
+    #[default]
+    RsaPkcs1,
+    EcdsaP256,
+    EcdsaBrainpoolP256r1,
+    // Any scheme without a name above, e.g. RSASSA-PSS or a curve other than P-256. The passport
+    // can still be proved, but its signature is committed as unverified.
+    #[serde(other)]
+    Unsupported,
+}
+
+// The algorithms verify_passport_signature can check, so a caller can tell before proving whether
+// is_valid_signature has a chance of being true.
+pub const SUPPORTED_SIGNATURE_ALGORITHMS: [SignatureAlgorithm; 2] =
+    [SignatureAlgorithm::RsaPkcs1, SignatureAlgorithm::EcdsaP256];
+
+impl SignatureAlgorithm {
+    pub fn is_supported(self) -> bool {
+        SUPPORTED_SIGNATURE_ALGORITHMS.contains(&self)
+    }
+}
+
+// What became of the document signature, committed as DocumentIntegrity::signature_status. It
+// separates a passport whose scheme the guest can't check from one whose signature failed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub enum SignatureStatus {
+    NotSupplied = 0,
+    Valid = 1,
+    UnsupportedAlgorithm = 2,
+    Invalid = 3,
+}
+
+pub fn signature_status(document_signature: Option<&DocumentSignature>) -> SignatureStatus {
+    match document_signature.map(verify_passport_signature_detailed) {
+        None => SignatureStatus::NotSupplied,
+        Some(Ok(())) => SignatureStatus::Valid,
+        Some(Err(PassportVerifyError::UnsupportedAlgorithm(_))) => SignatureStatus::UnsupportedAlgorithm,
+        Some(Err(_)) => SignatureStatus::Invalid,
+    }
+}
+
+// Hash behind identity_hash. Poseidon (circomlib parameters over BN254) is cheap to recompute
+// in Circom circuits and in Solidity via poseidon-solidity's PoseidonT3/PoseidonT5. Keccak-256
+// hashes the same preimage as SHA-256, so a contract can recompute it with keccak256() instead of
+// the SHA-256 precompile. Each hash gives the same passport a different identity_hash, so a
+// registry has to be keyed on one of them.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum CommitmentHash {
+    #[default]
+    Sha256,
+    Poseidon,
+    Keccak256,
+}
+
+// Passport fields fed to the SHA-256 identity hash, as a bit mask. Including the document
+// number makes the identity per-document, and adding the expiry date as well separates a
+// renewal that reuses the number. The default leaves both out so the identity survives passport
+// renewal. Changing the default changes every identity hash already stored on-chain.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CommitmentFields(u8);
+
+impl CommitmentFields {
+    pub const DOCUMENT_NUMBER: Self = CommitmentFields(1);
+    pub const DATE_OF_BIRTH: Self = CommitmentFields(2);
+    pub const NATIONALITY: Self = CommitmentFields(4);
+    pub const NAMES: Self = CommitmentFields(8);
+    pub const DATE_OF_EXPIRY: Self = CommitmentFields(16);
+
+    pub const fn bits(self) -> u8 {
+        self.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    pub const fn contains(self, other: Self) -> bool {
+        self.0 & other.0 == other.0
+    }
+}
+
+impl Default for CommitmentFields {
+    fn default() -> Self {
+        CommitmentFields::NAMES | CommitmentFields::NATIONALITY | CommitmentFields::DATE_OF_BIRTH
+    }
+}
+
+impl std::ops::BitOr for CommitmentFields {
+    type Output = Self;
+
+    fn bitor(self, rhs: Self) -> Self {
+        CommitmentFields(self.0 | rhs.0)
+    }
+}
+
+// Predicates the holder chooses to reveal in one proof. Withheld predicates are committed as
+// zero, false or empty, and disclosed_flags tells the verifier which outputs are meaningful.
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Disclosures {
+    // is_over_min_age, the max-age bound and the age grace window
+    pub age: bool,
+    pub nationality: bool,
+    // validity_months_remaining
+    pub expiry: bool,
+    pub wallet: bool,
+}
+
+impl Disclosures {
+    pub const AGE: u8 = 1;
+    pub const NATIONALITY: u8 = 2;
+    pub const EXPIRY: u8 = 4;
+    pub const WALLET: u8 = 8;
+
+    pub const fn flags(self) -> u8 {
+        (self.age as u8 * Self::AGE)
+            | (self.nationality as u8 * Self::NATIONALITY)
+            | (self.expiry as u8 * Self::EXPIRY)
+            | (self.wallet as u8 * Self::WALLET)
+    }
+}
+
+impl Default for Disclosures {
+    fn default() -> Self {
+        Disclosures {
+            age: true,
+            nationality: true,
+            expiry: true,
+            wallet: true,
+        }
+    }
+}
+
+// Why a document signature was rejected.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PassportVerifyError {
+    UnsupportedAlgorithm(SignatureAlgorithm),
+    MalformedPublicKey,
+    MalformedSignature,
+    // No signed attributes or no signature, e.g. a document signature left at its defaults. Checked
+    // before anything is hashed, so a hash of nothing can never verify
+    MissingSignedData,
+    // Well-formed, but not a signature by this key over SHA-256 of the signed attributes
+    SignatureMismatch,
+}
+
+impl std::fmt::Display for PassportVerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PassportVerifyError::UnsupportedAlgorithm(SignatureAlgorithm::Unsupported) => {
+                write!(f, "the signature algorithm is not one the guest can verify")
+            }
+            PassportVerifyError::UnsupportedAlgorithm(algorithm) => {
+                write!(f, "{:?} signatures can't be verified yet", algorithm)
+            }
+            PassportVerifyError::MalformedPublicKey => {
+                write!(f, "public key is not PKCS#1 DER (RSA) or a SEC1 point (EC)")
+            }
+            PassportVerifyError::MalformedSignature => write!(f, "ECDSA signature is not DER SEQUENCE {{ r, s }}"),
+            PassportVerifyError::MissingSignedData => write!(f, "the signed attributes or the signature are empty"),
+            PassportVerifyError::SignatureMismatch => {
+                write!(f, "signature does not match the signed attributes under this key")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PassportVerifyError {}
+
+// Read from the chip: the document signer's public key and its signature over the CMS
+// SignedAttributes from the Document Security Object. RSA keys are PKCS#1 DER, EC keys SEC1 points.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct DocumentSignature {
+    pub algorithm: SignatureAlgorithm,
+    pub public_key: Vec<u8>,
+    pub signed_attributes: Vec<u8>,
+    pub signature: Vec<u8>,
+}
+
+pub const MAX_LINKED_WALLETS: usize = 8;
+pub const MAX_WALLET_ADDRESS_BYTES: usize = 64;
+
+// A wallet on any chain, linked to the identity next to wallet_address. The address is raw
+// bytes (20 for EVM chains, 32 for e.g. Solana), so chains with other address formats fit too.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub struct WalletEntry {
+    pub chain_id: u64,
+    pub address: Vec<u8>,
+}
+
+// Everything the passport program reads, written by the host as one value so the two sides agree
+// by type rather than by the order of separate reads. Fields keep the order they were added in.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct GuestInput {
+    pub passport: PassportAttributes,
+    pub wallet_address: [u8; 20],
+    pub verifier_address: [u8; 20],
+    // Unix time the proof is evaluated at, committed as current_timestamp
+    pub current_timestamp: u64,
+    pub min_age: u16,
+    // Empty when allowed_nationalities is used instead
+    pub target_nationality: String,
+    pub target_sex: String,
+    pub mrz_check_digits: Option<MrzCheckDigits>,
+    pub grace_days: u16,
+    pub document_signature: Option<DocumentSignature>,
+    pub drinking_destination: String,
+    pub max_age: Option<u16>,
+    pub external_nullifier: [u8; 32],
+    pub commitment_hash: CommitmentHash,
+    pub issuing_country: Option<Nationality>,
+    pub alliance: String,
+    pub verifier_id: Option<[u8; 32]>,
+    pub identity_fields: CommitmentFields,
+    pub disclosures: Disclosures,
+    // Prove the passport has expired rather than that it is valid
+    pub expired_mode: bool,
+    pub allowed_nationalities: Vec<String>,
+    pub blocked_nationalities: Vec<String>,
+    pub data_groups: Option<dg::DataGroups>,
+    pub csca_roots: Vec<[u8; 32]>,
+    pub issuer_certificates: Option<csca::IssuerCertificates>,
+    // Unix time the age checks are evaluated at; current_timestamp unless a reference date is set
+    pub reference_timestamp: u64,
+    // The wallet identity_hash is bound to now, for a proof that moves the binding to wallet_address
+    pub previous_wallet: Option<[u8; 20]>,
+    // Leading bytes of identity_hash kept, 1 to 32; see truncate_commitment
+    pub commitment_bytes: u8,
+    // Minimum age in completed months; 0 when only min_age is checked
+    pub min_age_months: u32,
+    // merkle::MerkleTree root of a document number allowlist, zero when none is used, and the
+    // passport's path in it
+    pub allowlist_root: [u8; 32],
+    pub allowlist_path: Option<merkle::MerklePath>,
+    // A hidden target nationality: its target_nationality_commitment and the salt behind it.
+    // target_nationality is empty when this is set.
+    pub target_nationality_commitment: Option<([u8; 32], [u8; 32])>,
+    // Further wallets to tie to the identity, in any order; see derive_wallet_binding
+    pub linked_wallets: Vec<WalletEntry>,
+    // Days the passport must stay valid after current_timestamp; 0 for none
+    pub min_validity_days: u16,
+    // Unix seconds after which the proof should be refused, committed as given; 0 for none
+    pub valid_until_timestamp: u64,
+    // A time oracle's signature over current_timestamp; the prover's own clock when None
+    pub time_oracle: Option<oracle::OracleSignature>,
+    // Check a stateless holder's nationality (iso3166::STATELESS_CODES) as issuing_country
+    pub stateless_by_issuer: bool,
+    // Commit full_record_commitment of the whole passport
+    pub audit: bool,
+    // In allow-list mode, commit the matched code as target_nationality instead of leaving it empty
+    pub reveal_matched_nationality: bool,
+    // The holder's further passports. The guest proves over the first of passport and these that
+    // meets the requirements, and commits only that one did; see evaluate_passport.
+    pub other_passports: Vec<OtherPassport>,
+}
+
+// A dual national holds a passport per nationality; more than this is an input mistake.
+pub const MAX_OTHER_PASSPORTS: usize = 3;
+
+// The fields of GuestInput that belong to one document, for each of GuestInput::other_passports.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct OtherPassport {
+    pub passport: PassportAttributes,
+    pub mrz_check_digits: Option<MrzCheckDigits>,
+    pub document_signature: Option<DocumentSignature>,
+    pub issuing_country: Option<Nationality>,
+    pub data_groups: Option<dg::DataGroups>,
+    pub issuer_certificates: Option<csca::IssuerCertificates>,
+    pub allowlist_path: Option<merkle::MerklePath>,
+}
+
+impl OtherPassport {
+    // input with this document in place of its passport, and no further passports.
+    pub fn in_place_of(self, input: &GuestInput) -> GuestInput {
+        GuestInput {
+            passport: self.passport,
+            mrz_check_digits: self.mrz_check_digits,
+            document_signature: self.document_signature,
+            issuing_country: self.issuing_country,
+            data_groups: self.data_groups,
+            issuer_certificates: self.issuer_certificates,
+            allowlist_path: self.allowlist_path,
+            other_passports: Vec::new(),
+            ..input.clone()
+        }
+    }
+}
+
 sol! {
+    // alloy caps structs at 24 fields, so later checks are grouped into nested structs. Structs of
+    // static fields are ABI-encoded inline, so the layout matches the flattened fields.
+    struct IssuerAlliance {
+        bool issuer_in_alliance;
+        bytes32 grouping_id;
+    }
+
+    struct NullifierBinding {
+        bytes32 external_nullifier;
+        bytes32 nullifier;
+    }
+
+    // Who the proof is for and until when. audience and audience_commitment are zero when no
+    // verifier id was supplied.
+    struct AudienceBinding {
+        bytes32 audience;
+        bytes32 audience_commitment;
+        // Unix seconds after which a verifier should refuse the proof; 0 when unbounded
+        uint256 valid_until_timestamp;
+        // oracle::oracle_key_hash of the time oracle that signed current_timestamp; zero when the
+        // prover chose the time
+        bytes32 time_oracle;
+    }
+
+    // A verifier-supplied nationality list; both zero when none was given.
+    struct NationalitySet {
+        // groupings::nationality_set_commitment of the list
+        bytes32 commitment;
+        // Entries in the list as supplied, duplicates included
+        uint16 size;
+    }
+
+    struct NationalityPolicy {
+        // Acceptable nationalities, replacing target_nationality when given
+        NationalitySet allowed;
+        // The nationality is not in the blocklist; true when there is none
+        bool is_not_blocked;
+        NationalitySet blocked;
+        // target_nationality_commitment of a hidden target, replacing target_nationality; zero
+        // when the target is plaintext or absent
+        bytes32 target_commitment;
+        // A stateless holder was checked as a national of the issuing state
+        // (iso3166::nationality_for_checks); records the option, not whether the holder is stateless
+        bool stateless_by_issuer;
+    }
+
+    struct MinAgeCheck {
+        bool is_over_min_age;
+        uint256 min_age;
+        // Unix time every age check (min, max, grace, drinking) was evaluated at. Equal to
+        // current_timestamp unless the prover pinned another reference date, e.g. the day of a
+        // past transaction.
+        uint256 reference_timestamp;
+    }
+
+    struct AgeGrace {
+        bool within_grace;
+        uint256 grace_days;
+    }
+
+    struct ExpiryCheck {
+        uint8 validity_months_remaining;
+        // Set in expired mode, which proves the document is past its expiry date. Committed even
+        // when expiry isn't disclosed.
+        bool is_expired;
+        // The expiry date is at least min_validity_days after current_timestamp; 0 days when no
+        // minimum was requested
+        bool has_min_validity;
+        uint16 min_validity_days;
+    }
+
+    struct DrinkingCheck {
+        bool can_drink;
+        string drinking_destination;
+        bytes32 drinking_age_table_hash;
+    }
+
+    // The document number is a leaf of the allowlist tree with this root; both zero when no
+    // allowlist was used. See merkle.rs.
+    struct DocumentMembership {
+        bool is_member;
+        bytes32 merkle_root;
+    }
+
+    struct MaxAgeBound {
+        bool is_under_max_age;
+        // 0 when no upper bound was requested
+        uint256 max_age;
+        // age_bracket_flags: bit i set when the holder is at least AGE_BRACKETS[i]
+        // (13, 16, 18, 21, 65)
+        uint8 age_bracket_flags;
+        // A month-granular minimum age, e.g. 198 for 16 years and 6 months; 0 when none was
+        // requested. Checked alongside min_age, which stays in years.
+        bool is_over_min_age_months;
+        uint32 min_age_months;
+    }
+
+    struct DocumentIntegrity {
+        // The supplied MRZ check digits match the proved fields
+        bool mrz_checksums_valid;
+        // The document signer's signature over the SOD's signed attributes verifies
+        bool is_valid_signature;
+        // DG1 matches the proved fields and its hash is the one the signed SOD lists
+        bool is_data_group_consistent;
+        // The document signer's certificate chains to a CSCA in the verifier's root list
+        bool is_trusted_issuer;
+        // csca::root_set_commitment of that list; zero when none was given
+        bytes32 issuer_root_commitment;
+        // SignatureStatus: 0 = none supplied, 1 = valid, 2 = algorithm not supported,
+        // 3 = invalid
+        uint8 signature_status;
+    }
+
+    // Asks a registry to move identity_hash's binding from previous_wallet to wallet_address,
+    // e.g. after the previous wallet's key was lost
+    struct WalletRebind {
+        bool is_rebind;
+        // Zero unless is_rebind
+        address previous_wallet;
+    }
+
+    struct LinkedWallet {
+        uint64 chain_id;
+        bytes wallet;
+    }
+
+    // Wallets tied to the identity besides wallet_address; empty with a zero commitment when none
+    // were linked.
+    struct LinkedWallets {
+        // derive_wallet_binding of the identity and the wallets
+        bytes32 binding_commitment;
+        // Sorted by chain id, then address
+        LinkedWallet[] wallets;
+    }
+
+    // The passports a proof was evaluated over. The committed checks are those of the first one
+    // that meets every disclosed requirement, or of the first passport when none does.
+    struct PassportSet {
+        // One of the passports met the requirements
+        bool any_satisfies;
+        // Passports supplied, 1 for a single-passport proof
+        uint8 passport_count;
+        // identity_set_commitment of every passport's identity_hash
+        bytes32 set_commitment;
+    }
+
+    struct IdentityCommitment {
+        // CommitmentHash used for identity_hash: 0 = SHA-256, 1 = Poseidon, 2 = Keccak-256
+        uint8 commitment_hash;
+        // CommitmentFields bits, right-aligned: 1 = document number, 2 = date of birth,
+        // 4 = nationality, 8 = names, 16 = date of expiry
+        bytes32 commitment_field_mask;
+        // COMMITMENT_VERSION the identity hash was derived under
+        uint8 commitment_version;
+        // Leading bytes of identity_hash kept; the rest are zero
+        uint8 commitment_bytes;
+        // full_record_commitment of every passport field, for audit trails; zero unless the
+        // proof was made with --audit
+        bytes32 full_record_commitment;
+        PassportSet passport_set;
+    }
+
     struct PassportVerificationOutput {
         bytes32 identity_hash;
         address wallet_address;
         address verifier_address;
-        bool is_over_min_age;
-        uint256 min_age;
+        MinAgeCheck min_age_check;
         bool is_nationality_match;
         string target_nationality;
         bool is_sex_match;
         string target_sex;
         uint256 current_timestamp;
+        AgeGrace age_grace;
+        DocumentIntegrity document_integrity;
+        DrinkingCheck drinking_check;
+        ExpiryCheck expiry_check;
+        MaxAgeBound max_age_bound;
+        NullifierBinding nullifier_binding;
+        IssuerAlliance issuer_alliance;
+        AudienceBinding audience_binding;
+        IdentityCommitment identity_commitment;
+        // Disclosures bits: 1 = age, 2 = nationality, 4 = expiry, 8 = wallet
+        uint8 disclosed_flags;
+        NationalityPolicy nationality_policy;
+        WalletRebind wallet_rebind;
+        // VerificationPolicy::commitment of the minimum age, nationalities and expiry mode checked
+        bytes32 policy_commitment;
+        DocumentMembership document_membership;
+        LinkedWallets linked_wallets;
+    }
+
+    // Public output of the aggregation program, which folds many compressed passport proofs into
+    // one. Only proofs that disclose their age check at min_age are accepted.
+    struct AggregateAgeOutput {
+        // vk.hash_u32() of the passport program every inner proof was checked against, each word
+        // big-endian
+        bytes32 passport_vkey;
+        uint16 min_age;
+        // Inner proofs verified, duplicates of the same identity included
+        uint32 proof_count;
+        // Distinct identity hashes whose proof passed the age check
+        uint32 over_min_age_count;
+        // identity_set_commitment of those identity hashes
+        bytes32 identity_set_commitment;
+        // Earliest current_timestamp among the inner proofs, so the stalest evaluation date
+        uint256 oldest_timestamp;
+    }
+}
+
+impl NationalitySet {
+    pub fn from_codes(codes: &[String]) -> Self {
+        if codes.is_empty() {
+            return NationalitySet {
+                commitment: [0u8; 32].into(),
+                size: 0,
+            };
+        }
+        NationalitySet {
+            commitment: groupings::nationality_set_commitment(codes).into(),
+            size: codes.len() as u16,
+        }
     }
 }
 
@@ -37,38 +898,1811 @@ pub fn is_passport_valid(expiry: &Date, current: &Date) -> bool {
     (expiry.year, expiry.month, expiry.day) >= (current.year, current.month, current.day)
 }
 
-pub fn calculate_age(birth: &Date, current: &Date) -> u16 {
-    let mut age = current.year - birth.year;
+// A passport is still valid on its expiry day, so it only counts as expired from the day after.
+pub fn is_passport_expired(expiry: &Date, current: &Date) -> bool {
+    !is_passport_valid(expiry, current)
+}
+
+// Validity is committed in whole months so one proof answers any "N months remaining" rule
+// without revealing the expiry date. Anything beyond the cap is reported as cap + 1.
+pub const VALIDITY_MONTHS_CAP: u8 = 24;
+
+// Whole months from current until expiry; 0 once expired or with less than a month left.
+pub fn validity_months_bucket(expiry: &Date, current: &Date) -> u8 {
+    if !is_passport_valid(expiry, current) {
+        return 0;
+    }
+    let mut months = (expiry.year as i32 - current.year as i32) * 12 + expiry.month as i32 - current.month as i32;
+    if expiry.day < current.day {
+        months -= 1;
+    }
+    months.clamp(0, VALIDITY_MONTHS_CAP as i32 + 1) as u8
+}
+
+// True if expiry is at least min_validity_days after current, e.g. 183 for a visa rule asking for
+// six months' validity. Counted in calendar days through days_since_epoch, so leap days count.
+pub fn has_min_validity(expiry: &Date, current: &Date, min_validity_days: u16) -> bool {
+    days_since_epoch(expiry) - days_since_epoch(current) >= i64::from(min_validity_days)
+}
+
+// A committed timestamp more than skew_secs past the verifier's clock is either a prover clock
+// bug or an attempt to dodge the expiry check, so host-side consumers reject it.
+pub fn is_timestamp_plausible(ts: u64, now: u64, skew_secs: u64) -> bool {
+    ts <= now.saturating_add(skew_secs)
+}
+
+// None when current is before birth (a corrupt timestamp or bad input), which callers treat
+// as failing any age check instead of underflowing and aborting the guest.
+pub fn calculate_age(birth: &Date, current: &Date) -> Option<u16> {
+    let age = current.year.checked_sub(birth.year)?;
     if current.month < birth.month || (current.month == birth.month && current.day < birth.day) {
-        age -= 1;
+        return age.checked_sub(1);
+    }
+    Some(age)
+}
+
+pub fn is_over_age(birth: &Date, current: &Date, min_age: u16) -> bool {
+    calculate_age(birth, current).is_some_and(|age| age >= min_age)
+}
+
+// Completed months, for thresholds like 16 years and 6 months (198). A month completes on the
+// birth day-of-month, or on the 1st of the next month when it has fewer days, as calculate_age
+// does for 29 February.
+pub fn calculate_age_months(birth: &Date, current: &Date) -> Option<u32> {
+    let months = (u32::from(current.year) * 12 + u32::from(current.month))
+        .checked_sub(u32::from(birth.year) * 12 + u32::from(birth.month))?;
+    if current.day < birth.day {
+        return months.checked_sub(1);
+    }
+    Some(months)
+}
+
+pub fn is_over_age_months(birth: &Date, current: &Date, min_age_months: u32) -> bool {
+    calculate_age_months(birth, current).is_some_and(|months| months >= min_age_months)
+}
+
+// Inclusive, so a holder aged exactly max_age is still within the range.
+pub fn is_under_age(birth: &Date, current: &Date, max_age: u16) -> bool {
+    calculate_age(birth, current).is_some_and(|age| age <= max_age)
+}
+
+// The fixed ladder behind age_bracket_flags. Part of what a committed flag means, so changing it
+// changes every verifier's reading of old proofs.
+pub const AGE_BRACKETS: [u16; 5] = [13, 16, 18, 21, 65];
+
+// Bit i is set when the holder is at least AGE_BRACKETS[i] on `current`, so a verifier learns the
+// coarse bracket (e.g. 0b00111 = 18 to 20) rather than a single threshold or the birth date.
+pub fn age_bracket_flags(birth: &Date, current: &Date) -> u8 {
+    AGE_BRACKETS
+        .iter()
+        .enumerate()
+        .fold(0, |flags, (i, &age)| flags | ((is_over_age(birth, current, age) as u8) << i))
+}
+
+pub fn is_leap_year(year: u16) -> bool {
+    (year.is_multiple_of(4) && !year.is_multiple_of(100)) || year.is_multiple_of(400)
+}
+
+// 0 for a month outside 1-12.
+pub fn days_in_month(year: u16, month: u8) -> u8 {
+    match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if is_leap_year(year) => 29,
+        2 => 28,
+        _ => 0,
+    }
+}
+
+// Days since 1970-01-01, using Howard Hinnant's days_from_civil.
+pub fn days_since_epoch(date: &Date) -> i64 {
+    let year = date.year as i64 - if date.month <= 2 { 1 } else { 0 };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = date.month as i64;
+    let day_of_year = (153 * (month + if month > 2 { -3 } else { 9 }) + 2) / 5 + date.day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146097 + day_of_era - 719468
+}
+
+// True if the holder is already min_age or turns it within grace_days of current.
+// Someone born on 29 February turns a year older on 1 March in common years, matching calculate_age.
+pub fn is_within_age_grace(birth: &Date, current: &Date, min_age: u16, grace_days: u16) -> bool {
+    let year = birth.year.saturating_add(min_age);
+    let threshold = if birth.month == 2 && birth.day == 29 && !is_leap_year(year) {
+        Date { year, month: 3, day: 1 }
+    } else {
+        Date { year, month: birth.month, day: birth.day }
+    };
+    days_since_epoch(&threshold) - days_since_epoch(current) <= grace_days as i64
+}
+
+// False for destinations without a drinking age, including an empty destination.
+pub fn can_drink_in(birth: &Date, current: &Date, destination: &str) -> bool {
+    drinking_age::drinking_age(destination).is_some_and(|min_age| is_over_age(birth, current, min_age))
+}
+
+// ICAO 9303 check digit: weights 7-3-1, digits as-is, A-Z as 10-35, '<' (and anything else) as 0.
+pub fn mrz_check_digit(field: &[u8]) -> u8 {
+    const WEIGHTS: [u32; 3] = [7, 3, 1];
+    let total: u32 = field
+        .iter()
+        .enumerate()
+        .map(|(i, &c)| {
+            let value = match c {
+                b'0'..=b'9' => (c - b'0') as u32,
+                b'A'..=b'Z' => (c - b'A') as u32 + 10,
+                b'a'..=b'z' => (c - b'a') as u32 + 10,
+                _ => 0,
+            };
+            value * WEIGHTS[i % 3]
+        })
+        .sum();
+    (total % 10) as u8
+}
+
+// Dates appear in the MRZ as YYMMDD.
+pub fn mrz_date(date: &Date) -> [u8; 6] {
+    let yy = (date.year % 100) as u8;
+    [
+        b'0' + yy / 10,
+        b'0' + yy % 10,
+        b'0' + date.month / 10,
+        b'0' + date.month % 10,
+        b'0' + date.day / 10,
+        b'0' + date.day % 10,
+    ]
+}
+
+// Rebuilds the TD3 document number and date fields from the structured attributes and checks
+// them against the MRZ check digits. Document numbers over 9 characters spill into the optional
+// data field in the MRZ and aren't supported.
+pub fn mrz_checksums_valid(passport: &PassportAttributes, digits: &MrzCheckDigits) -> bool {
+    let document_number = passport.document_number.as_str().as_bytes();
+    if document_number.len() > 9 {
+        return false;
     }
-    age
+    let mut document_field = [b'<'; 9];
+    document_field[..document_number.len()].copy_from_slice(document_number);
+
+    mrz_check_digit(&document_field) == digits.document_number
+        && mrz_check_digit(&mrz_date(&passport.date_of_birth)) == digits.date_of_birth
+        && mrz_check_digit(&mrz_date(&passport.date_of_expiry)) == digits.date_of_expiry
+}
+
+// Checks the signature over SHA-256(signed_attributes), taking CMS signed attributes in the DER
+// form the signer hashed (dg::canonical_signed_attributes). Bytes that aren't a SET are hashed as
+// given. Malformed keys or signatures return false rather than panicking, since a panic in the
+// guest aborts the whole proof.
+pub fn verify_passport_signature(document_signature: &DocumentSignature) -> bool {
+    verify_passport_signature_detailed(document_signature).is_ok()
+}
+
+// verify_passport_signature with the reason for a rejection, for host tooling. The guest commits
+// only whether it passed.
+pub fn verify_passport_signature_detailed(document_signature: &DocumentSignature) -> Result<(), PassportVerifyError> {
+    use sha2::{Digest, Sha256};
+
+    if document_signature.signed_attributes.is_empty() || document_signature.signature.is_empty() {
+        return Err(PassportVerifyError::MissingSignedData);
+    }
+    let signed_attributes = &document_signature.signed_attributes;
+    let digest = match dg::canonical_signed_attributes(signed_attributes) {
+        Ok(canonical) => Sha256::digest(canonical),
+        Err(_) => Sha256::digest(signed_attributes),
+    };
+    match document_signature.algorithm {
+        SignatureAlgorithm::RsaPkcs1 => verify_rsa_pkcs1(document_signature, &digest),
+        SignatureAlgorithm::EcdsaP256 => verify_ecdsa_p256(document_signature, &digest),
+        // No brainpool implementation that builds for the zkVM is vendored yet.
+        algorithm @ (SignatureAlgorithm::EcdsaBrainpoolP256r1 | SignatureAlgorithm::Unsupported) => {
+            Err(PassportVerifyError::UnsupportedAlgorithm(algorithm))
+        }
+    }
+}
+
+// The chip data binds the proved fields to the signature: DG1 holds exactly these fields, the SOD
+// lists DG1's hash, and the signed attributes commit to that SOD. Only meaningful together with
+// verify_passport_signature over the same signed attributes.
+pub fn is_data_group_consistent(
+    passport: &PassportAttributes,
+    document_signature: &DocumentSignature,
+    data_groups: &dg::DataGroups,
+    current: &Date,
+) -> bool {
+    dg::dg1_matches_passport(&data_groups.dg1, passport, current)
+        && dg::verify_sod_hashes(&data_groups.sod, &data_groups.dg1)
+        && dg::sod_matches_signed_attributes(&data_groups.sod, &document_signature.signed_attributes)
+}
+
+fn verify_rsa_pkcs1(document_signature: &DocumentSignature, digest: &[u8]) -> Result<(), PassportVerifyError> {
+    use rsa::pkcs1::DecodeRsaPublicKey;
+    use rsa::{Pkcs1v15Sign, RsaPublicKey};
+
+    let public_key =
+        RsaPublicKey::from_pkcs1_der(&document_signature.public_key).map_err(|_| PassportVerifyError::MalformedPublicKey)?;
+    public_key
+        .verify(Pkcs1v15Sign::new::<sha2::Sha256>(), digest, &document_signature.signature)
+        .map_err(|_| PassportVerifyError::SignatureMismatch)
+}
+
+// Passports carry ECDSA signatures as DER SEQUENCE { r, s }.
+fn verify_ecdsa_p256(document_signature: &DocumentSignature, digest: &[u8]) -> Result<(), PassportVerifyError> {
+    use p256::ecdsa::signature::hazmat::PrehashVerifier;
+    use p256::ecdsa::{Signature, VerifyingKey};
+
+    let public_key =
+        VerifyingKey::from_sec1_bytes(&document_signature.public_key).map_err(|_| PassportVerifyError::MalformedPublicKey)?;
+    let signature = Signature::from_der(&document_signature.signature).map_err(|_| PassportVerifyError::MalformedSignature)?;
+    public_key
+        .verify_prehash(digest, &signature)
+        .map_err(|_| PassportVerifyError::SignatureMismatch)
+}
+
+// The bytes fed to the identity hash, exposed so other hash functions can be compared on them.
+pub fn identity_hash_preimage(passport: &PassportAttributes) -> Vec<u8> {
+    identity_hash_preimage_with(passport, CommitmentFields::default())
+}
+
+// Version byte in every identity hash input. Bump it with any change to the fields or their
+// encoding, so old and new hashes can never collide and a contract can route on the committed
+// version.
+pub const COMMITMENT_VERSION: u8 = 3;
+
+// Tags at the start of each SHA-256 commitment family, so an identity hash, a nullifier and an
+// audience commitment can't be the same digest even over the same bytes. None is a prefix of
+// another, so a tag followed by input can't be read as a different tag.
+pub const IDENTITY_HASH_DOMAIN: &[u8] = b"PASSPORT_IDENTITY";
+pub const NULLIFIER_DOMAIN: &[u8] = b"PASSPORT_NULLIFIER";
+pub const AUDIENCE_DOMAIN: &[u8] = b"PASSPORT_AUDIENCE";
+pub const POLICY_DOMAIN: &[u8] = b"PASSPORT_POLICY";
+pub const TARGET_NATIONALITY_DOMAIN: &[u8] = b"PASSPORT_TARGET_NATIONALITY";
+pub const WALLET_BINDING_DOMAIN: &[u8] = b"PASSPORT_WALLET_BINDING";
+pub const FULL_RECORD_DOMAIN: &[u8] = b"PASSPORT_FULL_RECORD";
+
+// The four bytes every commitment hashes a date as: the year as a little-endian u16, then the
+// month and the day as one byte each. 1974-08-12 is b6 07 08 0c.
+pub fn encode_commitment_date(date: &Date) -> [u8; 4] {
+    let [year_low, year_high] = date.year.to_le_bytes();
+    [year_low, year_high, date.month, date.day]
+}
+
+// The exact bytes the SHA-256 and Keccak-256 identity hashes cover, for reimplementing them in
+// another language. In order, with nothing between them:
+//   IDENTITY_HASH_DOMAIN ("PASSPORT_IDENTITY", 17 ASCII bytes)
+//   version byte (COMMITMENT_VERSION)
+//   names: 4-byte big-endian byte length, then the UTF-8 name
+//   nationality: 4-byte big-endian length, then the 1 to 3 ASCII letters ("D" for Germany)
+//   date of birth: encode_commitment_date, 4 bytes
+//   document number: 4-byte big-endian length, then its ASCII characters
+//   date of expiry: encode_commitment_date, 4 bytes
+// Fields left out of `fields` are skipped; the rest keep this order. Later fields go last so the
+// default field set keeps its layout. The length prefixes keep every field apart whichever are
+// selected, since the dates can't be relied on to sit between two text fields.
+pub fn identity_hash_preimage_with(passport: &PassportAttributes, fields: CommitmentFields) -> Vec<u8> {
+    versioned_identity_hash_preimage(COMMITMENT_VERSION, passport, fields)
+}
+
+fn versioned_identity_hash_preimage(version: u8, passport: &PassportAttributes, fields: CommitmentFields) -> Vec<u8> {
+    // Text fields carry their length, as in full_record_commitment, so a name ending in "GR"
+    // with nationality "D" can't read as the same bytes as one with nationality "GRD".
+    fn push_text(preimage: &mut Vec<u8>, text: &str) {
+        preimage.extend_from_slice(&(text.len() as u32).to_be_bytes());
+        preimage.extend_from_slice(text.as_bytes());
+    }
+
+    let mut preimage = IDENTITY_HASH_DOMAIN.to_vec();
+    preimage.push(version);
+    if fields.contains(CommitmentFields::NAMES) {
+        push_text(&mut preimage, &passport.name);
+    }
+    if fields.contains(CommitmentFields::NATIONALITY) {
+        push_text(&mut preimage, passport.nationality.as_str());
+    }
+    if fields.contains(CommitmentFields::DATE_OF_BIRTH) {
+        preimage.extend_from_slice(&encode_commitment_date(&passport.date_of_birth));
+    }
+    if fields.contains(CommitmentFields::DOCUMENT_NUMBER) {
+        push_text(&mut preimage, passport.document_number.as_str());
+    }
+    if fields.contains(CommitmentFields::DATE_OF_EXPIRY) {
+        preimage.extend_from_slice(&encode_commitment_date(&passport.date_of_expiry));
+    }
+    preimage
 }
 
 // Hashes name + nationality + date of birth so the identity survives passport renewal.
 pub fn derive_identity_hash(passport: &PassportAttributes) -> [u8; 32] {
     use sha2::{Sha256, Digest};
 
+    let result = Sha256::digest(identity_hash_preimage(passport));
+    let mut commitment = [0u8; 32];
+    commitment.copy_from_slice(&result);
+    commitment
+}
+
+// None for an empty field set, or a non-default one with Poseidon, whose packing is fixed.
+pub fn derive_identity_hash_with(
+    passport: &PassportAttributes,
+    hash: CommitmentHash,
+    fields: CommitmentFields,
+) -> Option<[u8; 32]> {
+    use sha2::{Sha256, Digest};
+
+    if fields.is_empty() {
+        return None;
+    }
+    match hash {
+        CommitmentHash::Sha256 => Some(Sha256::digest(identity_hash_preimage_with(passport, fields)).into()),
+        CommitmentHash::Poseidon if fields == CommitmentFields::default() => {
+            Some(derive_identity_hash_poseidon(passport))
+        }
+        CommitmentHash::Poseidon => None,
+        CommitmentHash::Keccak256 => {
+            Some(alloy_sol_types::private::keccak256(identity_hash_preimage_with(passport, fields)).0)
+        }
+    }
+}
+
+// Keeps the first `bytes` bytes of an identity hash and zeroes the rest, so a contract can store it
+// in a narrower slot (bytes16, or a uint128 packed next to other state). Fewer bytes trade
+// collision resistance for gas: two of n identities collide with probability about
+// n^2 / 2^(8 * bytes + 1), so below 16 bytes a collision is within reach of a large registry.
+pub fn truncate_commitment(hash: [u8; 32], bytes: u8) -> [u8; 32] {
+    let mut truncated = [0u8; 32];
+    let kept = usize::from(bytes).min(32);
+    truncated[..kept].copy_from_slice(&hash[..kept]);
+    truncated
+}
+
+// SHA-256 over a set of identity hashes, sorted and deduplicated so neither the order proofs were
+// aggregated in nor a repeated proof changes it. Returns the number of distinct hashes with it.
+pub fn identity_set_commitment(identity_hashes: &[[u8; 32]]) -> (u32, [u8; 32]) {
+    use sha2::{Digest, Sha256};
+
+    let mut hashes = identity_hashes.to_vec();
+    hashes.sort_unstable();
+    hashes.dedup();
     let mut hasher = Sha256::new();
-    hasher.update(passport.name.as_bytes());
-    hasher.update(passport.nationality.as_bytes());
-    hasher.update(&passport.date_of_birth.year.to_le_bytes());
-    hasher.update(&[passport.date_of_birth.month]);
-    hasher.update(&[passport.date_of_birth.day]);
+    for hash in &hashes {
+        hasher.update(hash);
+    }
+    (hashes.len() as u32, hasher.finalize().into())
+}
+
+// Field element packing, all over the BN254 scalar field:
+//   name_acc = 0, then for each 31-byte chunk of the UTF-8 name: name_acc = Poseidon(name_acc, chunk)
+//     where a chunk is read big-endian after right-padding the last one with zero bytes
+//   identity = Poseidon(COMMITMENT_VERSION, name_acc, name byte length, nationality bytes big-endian,
+//                       year * 10000 + month * 100 + day of birth)
+// The result is the 32-byte big-endian encoding of identity.
+pub fn derive_identity_hash_poseidon(passport: &PassportAttributes) -> [u8; 32] {
+    use ark_bn254::Fr;
+    use ark_ff::{BigInteger, PrimeField};
+    use light_poseidon::{Poseidon, PoseidonHasher};
+
+    fn poseidon(inputs: &[Fr]) -> Fr {
+        Poseidon::<Fr>::new_circom(inputs.len())
+            .and_then(|mut hasher| hasher.hash(inputs))
+            .expect("circom Poseidon supports this arity")
+    }
+
+    let name = passport.name.as_bytes();
+    let mut name_acc = Fr::from(0u64);
+    for chunk in name.chunks(31) {
+        let mut padded = [0u8; 31];
+        padded[..chunk.len()].copy_from_slice(chunk);
+        name_acc = poseidon(&[name_acc, Fr::from_be_bytes_mod_order(&padded)]);
+    }
+
+    let birth = &passport.date_of_birth;
+    let identity = poseidon(&[
+        Fr::from(COMMITMENT_VERSION as u64),
+        name_acc,
+        Fr::from(name.len() as u64),
+        Fr::from_be_bytes_mod_order(passport.nationality.as_str().as_bytes()),
+        Fr::from(birth.year as u64 * 10000 + birth.month as u64 * 100 + birth.day as u64),
+    ]);
 
-    let result = hasher.finalize();
     let mut commitment = [0u8; 32];
-    commitment.copy_from_slice(&result);
+    commitment.copy_from_slice(&identity.into_bigint().to_bytes_be());
     commitment
 }
 
+// Scoped to an application-chosen external nullifier (an app ID or epoch), so a contract can
+// reject a replayed action while the same person stays unlinkable across scopes.
+pub fn derive_nullifier(passport: &PassportAttributes, external_nullifier: &[u8; 32]) -> [u8; 32] {
+    use sha2::{Sha256, Digest};
+
+    let mut hasher = Sha256::new();
+    hasher.update(NULLIFIER_DOMAIN);
+    hasher.update(derive_identity_hash(passport));
+    hasher.update(external_nullifier);
+    hasher.finalize().into()
+}
+
+// Binds the identity to one verifier so a proof shown to verifier A can't be replayed at B.
+// AUDIENCE_DOMAIN keeps it distinct from a nullifier scoped to the same 32 bytes.
+pub fn derive_audience_commitment(passport: &PassportAttributes, verifier_id: &[u8; 32]) -> [u8; 32] {
+    use sha2::{Sha256, Digest};
+
+    let mut hasher = Sha256::new();
+    hasher.update(AUDIENCE_DOMAIN);
+    hasher.update(derive_identity_hash(passport));
+    hasher.update(verifier_id);
+    hasher.finalize().into()
+}
+
+// Hides which country a nationality check tested: SHA-256 over TARGET_NATIONALITY_DOMAIN, the
+// verifier's 32-byte salt and the ICAO code. There are only a few hundred codes, so without a
+// secret salt anyone could hash them all and read the target back.
+pub fn target_nationality_commitment(nationality: &str, salt: &[u8; 32]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let code = iso3166::normalize_nationality(nationality).unwrap_or(nationality);
+    let mut hasher = Sha256::new();
+    hasher.update(TARGET_NATIONALITY_DOMAIN);
+    hasher.update(salt);
+    hasher.update(code.as_bytes());
+    hasher.finalize().into()
+}
+
+// Ties every linked wallet to the identity at once: SHA-256 over WALLET_BINDING_DOMAIN, the
+// identity hash, then each wallet in sorted_wallets order as its chain id (8 bytes, big-endian),
+// address length (1 byte) and address. Sorting first means the order they were given in doesn't
+// matter.
+pub fn derive_wallet_binding(passport: &PassportAttributes, wallets: &[WalletEntry]) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(WALLET_BINDING_DOMAIN);
+    hasher.update(derive_identity_hash(passport));
+    for wallet in sorted_wallets(wallets) {
+        hasher.update(wallet.chain_id.to_be_bytes());
+        hasher.update([wallet.address.len() as u8]);
+        hasher.update(&wallet.address);
+    }
+    hasher.finalize().into()
+}
+
+// Audit hash of the whole record, for verifiers that must retain one: SHA-256 over
+// FULL_RECORD_DOMAIN, then document number, name, nationality and sex, each as a 4-byte
+// big-endian length and the bytes, then the dates of birth and expiry as encode_commitment_date
+// lays them out. Unlike the identity hash it changes with any field, so it
+// only confirms data an auditor already holds.
+pub fn full_record_commitment(passport: &PassportAttributes) -> [u8; 32] {
+    use sha2::{Digest, Sha256};
+
+    let mut hasher = Sha256::new();
+    hasher.update(FULL_RECORD_DOMAIN);
+    for field in [
+        passport.document_number.as_str(),
+        passport.name.as_str(),
+        passport.nationality.as_str(),
+        passport.sex.as_str(),
+    ] {
+        hasher.update((field.len() as u32).to_be_bytes());
+        hasher.update(field.as_bytes());
+    }
+    for date in [&passport.date_of_birth, &passport.date_of_expiry] {
+        hasher.update(encode_commitment_date(date));
+    }
+    hasher.finalize().into()
+}
+
+// Wallets by chain id, then address, with repeats dropped.
+pub fn sorted_wallets(wallets: &[WalletEntry]) -> Vec<WalletEntry> {
+    let mut sorted = wallets.to_vec();
+    sorted.sort();
+    sorted.dedup();
+    sorted
+}
+
+// The rules a proof was checked against, independent of the holder. The guest derives it from
+// its inputs and commits policy_commitment, so a contract can pin an approved policy by hash
+// instead of re-checking each threshold. JSON form, as evm --policy reads it:
+//   {"minAge": 18, "allowedNationalities": ["MYS", "SGP"], "requireUnexpired": true}
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationPolicy {
+    pub min_age: u16,
+    // Empty accepts any nationality
+    #[serde(default)]
+    pub allowed_nationalities: Vec<String>,
+    // False is expired mode's policy: the proof shows the document has expired
+    #[serde(default = "require_unexpired_default")]
+    pub require_unexpired: bool,
+}
+
+fn require_unexpired_default() -> bool {
+    true
+}
+
+impl VerificationPolicy {
+    // SHA-256 over POLICY_DOMAIN, min_age as 2 bytes big-endian, require_unexpired as one byte,
+    // then the groupings::nationality_set_commitment of allowed_nationalities (32 zero bytes
+    // when empty), so the order the list was written in doesn't matter.
+    pub fn commitment(&self) -> [u8; 32] {
+        use sha2::{Digest, Sha256};
+
+        let mut hasher = Sha256::new();
+        hasher.update(POLICY_DOMAIN);
+        hasher.update(self.min_age.to_be_bytes());
+        hasher.update([self.require_unexpired as u8]);
+        hasher.update(NationalitySet::from_codes(&self.allowed_nationalities).commitment);
+        hasher.finalize().into()
+    }
+}
+
+// Pedersen commitment C = m*B + r*B_blinding over ristretto255, compressed to 32 bytes.
+// m is the identity hash reduced mod the group order and r the caller's blinding factor.
+// The generators match bulletproofs' PedersenGens::default(): B is the ristretto basepoint and
+// B_blinding = hash_from_bytes::<Sha3_512>(B.compress()).
+#[cfg(feature = "pedersen")]
+pub fn create_passport_commitment_pedersen(passport: &PassportAttributes, blinding: &[u8; 32]) -> [u8; 32] {
+    use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+    use curve25519_dalek::ristretto::RistrettoPoint;
+    use curve25519_dalek::scalar::Scalar;
+
+    let blinding_generator = RistrettoPoint::hash_from_bytes::<sha3::Sha3_512>(
+        RISTRETTO_BASEPOINT_POINT.compress().as_bytes(),
+    );
+    let message = Scalar::from_bytes_mod_order(derive_identity_hash(passport));
+    let blinding = Scalar::from_bytes_mod_order(*blinding);
+
+    (message * RISTRETTO_BASEPOINT_POINT + blinding * blinding_generator)
+        .compress()
+        .to_bytes()
+}
+
+// EIP-55 form of an address: a hex letter is uppercase when the matching nibble of
+// keccak256(lowercase hex) is 8 or more.
+pub fn to_eip55(address: &[u8; 20]) -> String {
+    let lower: String = address.iter().map(|b| format!("{:02x}", b)).collect();
+    let hash = alloy_sol_types::private::keccak256(lower.as_bytes());
+    let digits: String = lower
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            let nibble = if i % 2 == 0 { hash[i / 2] >> 4 } else { hash[i / 2] & 0x0f };
+            if nibble >= 8 {
+                c.to_ascii_uppercase()
+            } else {
+                c
+            }
+        })
+        .collect();
+    format!("0x{}", digits)
+}
+
+// True for a 40-digit hex address, with or without 0x, whose casing is either uniform (no
+// checksum) or matches EIP-55.
+pub fn validate_eip55(address: &str) -> bool {
+    let digits = address.trim().trim_start_matches("0x");
+    if digits.len() != 40 || !digits.bytes().all(|c| c.is_ascii_hexdigit()) {
+        return false;
+    }
+    let has_lower = digits.bytes().any(|c| c.is_ascii_lowercase());
+    let has_upper = digits.bytes().any(|c| c.is_ascii_uppercase());
+    if !(has_lower && has_upper) {
+        return true;
+    }
+    let mut bytes = [0u8; 20];
+    for (i, byte) in bytes.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&digits[2 * i..2 * i + 2], 16).unwrap();
+    }
+    to_eip55(&bytes)[2..] == *digits
+}
+
+// CREATE2 address (EIP-1014): the last 20 bytes of
+// keccak256(0xff ++ factory ++ salt ++ keccak256(init_code)).
+pub fn create2_address(factory: &[u8; 20], salt: &[u8; 32], init_code_hash: &[u8; 32]) -> [u8; 20] {
+    let mut preimage = Vec::with_capacity(85);
+    preimage.push(0xff);
+    preimage.extend_from_slice(factory);
+    preimage.extend_from_slice(salt);
+    preimage.extend_from_slice(init_code_hash);
+    let hash = alloy_sol_types::private::keccak256(&preimage);
+    hash[12..].try_into().unwrap()
+}
+
+// The CREATE2 salt a wallet factory deploys an identity's wallet with:
+// keccak256(identity_commitment ++ salt). The caller's salt tells several wallets of one identity
+// apart; zero for the first.
+pub fn counterfactual_salt(identity_commitment: &[u8; 32], salt: &[u8; 32]) -> [u8; 32] {
+    let mut preimage = [0u8; 64];
+    preimage[..32].copy_from_slice(identity_commitment);
+    preimage[32..].copy_from_slice(salt);
+    alloy_sol_types::private::keccak256(preimage).0
+}
+
+// Address of the smart wallet factory would deploy for an identity, known before deployment, so
+// funds or attestations can be sent to it counterfactually. init_code_hash is keccak256 of the
+// wallet's creation code with its constructor arguments, as the factory deploys it.
+pub fn derive_counterfactual_address(
+    identity_commitment: &[u8; 32],
+    factory: &[u8; 20],
+    salt: &[u8; 32],
+    init_code_hash: &[u8; 32],
+) -> [u8; 20] {
+    create2_address(factory, &counterfactual_salt(identity_commitment, salt), init_code_hash)
+}
+
+// Last day a Date can hold. timestamp_to_date clamps to it rather than wrapping the year.
+pub const MAX_DATE: Date = Date { year: u16::MAX, month: 12, day: 31 };
+
+// UTC date of a Unix timestamp, using Howard Hinnant's civil_from_days. A timestamp past the end
+// of year 65535 gives MAX_DATE, so an absurd clock reads as far in the future (every passport
+// expired) instead of wrapping round to an early year that could pass an expiry check.
 pub fn timestamp_to_date(timestamp: u64) -> Date {
-    use chrono::{DateTime, Datelike};
-    let dt = DateTime::from_timestamp(timestamp as i64, 0)
-        .expect("invalid timestamp");
+    let days = (timestamp / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let mp = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    if year > i64::from(u16::MAX) {
+        return MAX_DATE;
+    }
     Date {
-        year: dt.year() as u16,
-        month: dt.month() as u8,
-        day: dt.day() as u8,
+        year: year as u16,
+        month: month as u8,
+        day: day as u8,
+    }
+}
+
+// The commitment tests are golden vectors. A failure there means every identity hash, nullifier
+// and audience commitment already issued would change, so a value should only be updated
+// together with a deliberate, versioned change to the preimage.
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_passport() -> PassportAttributes {
+        PassportAttributes {
+            document_number: DocumentNumber::new("L898902C3").unwrap(),
+            date_of_birth: Date { year: 1974, month: 8, day: 12 },
+            date_of_expiry: Date { year: 2030, month: 4, day: 15 },
+            nationality: Nationality::new("UTO").unwrap(),
+            name: "ANNA MARIA ERIKSSON".to_string(),
+            sex: "F".to_string(),
+        }
+    }
+
+    fn hex(bytes: &[u8]) -> String {
+        bytes.iter().map(|b| format!("{:02x}", b)).collect()
+    }
+
+    #[test]
+    fn identity_hash_golden_vector() {
+        assert_eq!(
+            hex(&derive_identity_hash(&sample_passport())),
+            "3a3d967080aad8e0f19621bca8d0ea83ab15070d34ede1b22de84d4a38eb9588"
+        );
+    }
+
+    #[test]
+    fn identity_hash_with_document_number_golden_vector() {
+        let fields = CommitmentFields::default() | CommitmentFields::DOCUMENT_NUMBER;
+        let hash = derive_identity_hash_with(&sample_passport(), CommitmentHash::Sha256, fields).unwrap();
+        assert_eq!(hex(&hash), "ed7fadfdc141bc00fdcefc6d46942166f60d62da66dd290af547cbaef303953d");
+    }
+
+    // Byte for byte what another implementation has to hash, with every field selected.
+    #[test]
+    fn identity_hash_preimage_golden_vector() {
+        assert_eq!(encode_commitment_date(&Date { year: 1974, month: 8, day: 12 }), [0xb6, 0x07, 0x08, 0x0c]);
+        let all = CommitmentFields::default() | CommitmentFields::DOCUMENT_NUMBER | CommitmentFields::DATE_OF_EXPIRY;
+        assert_eq!(
+            hex(&identity_hash_preimage_with(&sample_passport(), all)),
+            concat!(
+                "50415353504f52545f4944454e54495459",       // PASSPORT_IDENTITY
+                "03",                                       // COMMITMENT_VERSION
+                "00000013",                                 // name length, 19
+                "414e4e41204d41524941204552494b53534f4e",   // ANNA MARIA ERIKSSON
+                "00000003",                                 // nationality length
+                "55544f",                                   // UTO
+                "b607080c",                                 // 1974-08-12
+                "00000009",                                 // document number length
+                "4c3839383930324333",                       // L898902C3
+                "ee07040f",                                 // 2030-04-15
+            )
+        );
+        let default = identity_hash_preimage(&sample_passport());
+        assert_eq!(hex(&default), "50415353504f52545f4944454e544954590300000013414e4e41204d41524941204552494b53534f4e0000000355544fb607080c");
+        // A one-letter nationality is still prefixed with a 4-byte length.
+        let german = PassportAttributes { nationality: Nationality::new("D").unwrap(), ..sample_passport() };
+        assert_eq!(
+            hex(&identity_hash_preimage(&german)),
+            concat!(
+                "50415353504f52545f4944454e54495459",       // PASSPORT_IDENTITY
+                "03",                                       // COMMITMENT_VERSION
+                "00000013",                                 // name length, 19
+                "414e4e41204d41524941204552494b53534f4e",   // ANNA MARIA ERIKSSON
+                "00000001",                                 // nationality length
+                "44",                                       // D
+                "b607080c",                                 // 1974-08-12
+            )
+        );
+    }
+
+    // Which fields the default hashes is part of every stored identity hash: changing it must be a
+    // new commitment version, not an edit to this test.
+    #[test]
+    fn default_commitment_fields_are_locked() {
+        assert_eq!(CommitmentFields::default().bits(), 0b01110);
+        assert!(!CommitmentFields::default().contains(CommitmentFields::DOCUMENT_NUMBER));
+        assert!(!CommitmentFields::default().contains(CommitmentFields::DATE_OF_EXPIRY));
+    }
+
+    #[test]
+    fn identity_hash_with_expiry_golden_vector() {
+        let fields = CommitmentFields::default() | CommitmentFields::DOCUMENT_NUMBER | CommitmentFields::DATE_OF_EXPIRY;
+        let hash = derive_identity_hash_with(&sample_passport(), CommitmentHash::Sha256, fields).unwrap();
+        assert_eq!(hex(&hash), "02d9ec8e8e9c81368f61763c5ee55598758a0c2c2586c615aba0970dd25f36aa");
+    }
+
+    #[test]
+    fn min_validity_counts_calendar_days() {
+        let current = Date::new(2024, 1, 1).unwrap();
+        // 2024 is a leap year, so 2025-01-01 is 366 days away.
+        let expiry = Date::new(2025, 1, 1).unwrap();
+        assert!(has_min_validity(&expiry, &current, 366));
+        assert!(!has_min_validity(&expiry, &current, 367));
+        assert!(has_min_validity(&current, &current, 0));
+        assert!(!has_min_validity(&current, &current, 1));
+        assert!(!has_min_validity(&Date::new(2023, 12, 31).unwrap(), &current, 0));
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn expiry_separates_renewal_with_same_number() {
+        let passport = sample_passport();
+        let renewed = PassportAttributes {
+            date_of_expiry: Date { year: 2040, month: 4, day: 15 },
+            ..passport.clone()
+        };
+        let per_document = CommitmentFields::default() | CommitmentFields::DOCUMENT_NUMBER;
+        let per_issue = per_document | CommitmentFields::DATE_OF_EXPIRY;
+        let hash = |p: &PassportAttributes, fields| derive_identity_hash_with(p, CommitmentHash::Sha256, fields);
+        assert_eq!(hash(&passport, per_document), hash(&renewed, per_document));
+        assert_ne!(hash(&passport, per_issue), hash(&renewed, per_issue));
+    }
+
+    // A 16-byte hash leaves ~2^64 work for a birthday collision; shorter ones are only for small
+    // registries, which is why evm warns about them.
+    #[test]
+    fn truncated_commitment_keeps_the_leading_bytes() {
+        let hash = derive_identity_hash(&sample_passport());
+        let truncated = truncate_commitment(hash, 16);
+        assert_eq!(hex(&truncated), "3a3d967080aad8e0f19621bca8d0ea8300000000000000000000000000000000");
+        assert_eq!(truncate_commitment(hash, 32), hash);
+    }
+
+    #[test]
+    fn identity_hash_poseidon_golden_vector() {
+        assert_eq!(
+            hex(&derive_identity_hash_poseidon(&sample_passport())),
+            "177bb7e59e0f6874317315297a6da17e4d041adf4fe7961f798cb86cfe57b54f"
+        );
+    }
+
+    #[test]
+    fn nullifier_golden_vector() {
+        assert_eq!(
+            hex(&derive_nullifier(&sample_passport(), &[0x11; 32])),
+            "46e9d5f47eaff2f0f2a3714146e6569c2e6f88ad6949691e9e3079b36ff0213f"
+        );
+    }
+
+    #[test]
+    fn audience_commitment_golden_vector() {
+        assert_eq!(
+            hex(&derive_audience_commitment(&sample_passport(), &[0x22; 32])),
+            "4c3a9185db30e292c10f7bbc8939696a3acb3f36ddb884093f8d9e65842b1f47"
+        );
+    }
+
+    #[test]
+    fn date_new_checks_leap_years() {
+        assert!(Date::new(2024, 2, 29).is_ok());
+        assert!(Date::new(2000, 2, 29).is_ok());
+        assert_eq!(
+            Date::new(2025, 2, 29).unwrap_err(),
+            DateError::InvalidDay { year: 2025, month: 2, day: 29 }
+        );
+        assert!(Date::new(1900, 2, 29).is_err());
+    }
+
+    #[test]
+    fn date_new_rejects_impossible_dates() {
+        assert_eq!(Date::new(2025, 0, 1).unwrap_err(), DateError::InvalidMonth(0));
+        assert_eq!(Date::new(2025, 13, 40).unwrap_err(), DateError::InvalidMonth(13));
+        assert!(Date::new(2025, 4, 31).is_err());
+        assert!(Date::new(2025, 1, 0).is_err());
+        assert!(Date::new(2025, 12, 31).is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_expiry_before_birth() {
+        let today = Date { year: 2026, month: 1, day: 1 };
+        assert_eq!(sample_passport().validate(&today), Ok(()));
+        let passport = PassportAttributes {
+            date_of_expiry: Date { year: 1970, month: 1, day: 1 },
+            ..sample_passport()
+        };
+        assert_eq!(passport.validate(&today), Err(ValidationError::ExpiryNotAfterBirth));
+    }
+
+    #[test]
+    fn validate_rejects_a_future_birth() {
+        let passport = PassportAttributes {
+            date_of_birth: Date { year: 2027, month: 3, day: 1 },
+            ..sample_passport()
+        };
+        assert_eq!(
+            passport.validate(&Date { year: 2026, month: 1, day: 1 }),
+            Err(ValidationError::BirthAfterReference)
+        );
+        let passport = PassportAttributes {
+            date_of_birth: Date { year: 1990, month: 2, day: 30 },
+            ..sample_passport()
+        };
+        assert!(matches!(
+            passport.validate(&Date { year: 2026, month: 1, day: 1 }),
+            Err(ValidationError::InvalidDate("date of birth", _))
+        ));
+    }
+
+    #[test]
+    fn age_brackets_map_ages_to_flags() {
+        let today = Date { year: 2026, month: 6, day: 1 };
+        let born_years_ago = |years: u16| Date { year: 2026 - years, month: 6, day: 1 };
+        for (age, flags) in [
+            (10, 0b00000),
+            (13, 0b00001),
+            (17, 0b00011),
+            (18, 0b00111),
+            (20, 0b00111),
+            (21, 0b01111),
+            (64, 0b01111),
+            (65, 0b11111),
+        ] {
+            assert_eq!(age_bracket_flags(&born_years_ago(age), &today), flags, "age {}", age);
+        }
+        // A day short of 18 is still in the 16 bracket.
+        assert_eq!(age_bracket_flags(&Date { year: 2008, month: 6, day: 2 }, &today), 0b00011);
+    }
+
+    #[test]
+    fn age_months_count_completed_months() {
+        let birth = Date { year: 2009, month: 5, day: 15 };
+        // 16 years and 6 months on 2025-11-15, not the day before.
+        assert_eq!(calculate_age_months(&birth, &Date { year: 2025, month: 11, day: 14 }), Some(197));
+        assert_eq!(calculate_age_months(&birth, &Date { year: 2025, month: 11, day: 15 }), Some(198));
+        assert!(is_over_age_months(&birth, &Date { year: 2025, month: 11, day: 16 }, 198));
+        assert!(!is_over_age_months(&birth, &Date { year: 2025, month: 11, day: 14 }, 198));
+        // Born on the 31st: the month ending in February completes on 1 March.
+        let birth = Date { year: 2024, month: 1, day: 31 };
+        assert_eq!(calculate_age_months(&birth, &Date { year: 2024, month: 2, day: 29 }), Some(0));
+        assert_eq!(calculate_age_months(&birth, &Date { year: 2024, month: 3, day: 1 }), Some(1));
+        assert_eq!(calculate_age_months(&birth, &Date { year: 2024, month: 1, day: 30 }), None);
+    }
+
+    #[test]
+    fn passport_expires_the_day_after_its_expiry_date() {
+        let expiry = Date::new(2026, 6, 15).unwrap();
+        assert!(!is_passport_expired(&expiry, &Date::new(2026, 6, 14).unwrap()));
+        assert!(!is_passport_expired(&expiry, &Date::new(2026, 6, 15).unwrap()));
+        assert!(is_passport_expired(&expiry, &Date::new(2026, 6, 16).unwrap()));
+        assert!(is_passport_expired(&expiry, &Date::new(2027, 1, 1).unwrap()));
+        assert!(is_passport_valid(&expiry, &Date::new(2026, 6, 15).unwrap()));
+    }
+
+    #[test]
+    fn validity_buckets_count_whole_months_up_to_the_cap() {
+        let today = Date { year: 2026, month: 6, day: 15 };
+        for ((year, month, day), bucket) in [
+            // Expired yesterday, then valid on the expiry day itself but with no month left.
+            ((2026, 6, 14), 0),
+            ((2025, 1, 1), 0),
+            ((2026, 6, 15), 0),
+            ((2026, 7, 14), 0),
+            ((2026, 7, 15), 1),
+            ((2026, 12, 14), 5),
+            ((2026, 12, 15), 6),
+            ((2028, 6, 15), VALIDITY_MONTHS_CAP),
+            ((2028, 7, 14), VALIDITY_MONTHS_CAP),
+            // Beyond the cap everything reads as cap + 1.
+            ((2028, 7, 15), VALIDITY_MONTHS_CAP + 1),
+            ((2035, 1, 1), VALIDITY_MONTHS_CAP + 1),
+        ] {
+            let expiry = Date { year, month, day };
+            assert_eq!(validity_months_bucket(&expiry, &today), bucket, "expiry {:?}", expiry);
+        }
+    }
+
+    #[test]
+    fn max_age_includes_the_holder_aged_exactly_max_age() {
+        let birth = Date { year: 2000, month: 6, day: 15 };
+        assert!(is_under_age(&birth, &Date { year: 2065, month: 6, day: 14 }, 65));
+        // Exactly 65 on the birthday, and still 65 the day before turning 66.
+        assert!(is_under_age(&birth, &Date { year: 2065, month: 6, day: 15 }, 65));
+        assert!(is_under_age(&birth, &Date { year: 2066, month: 6, day: 14 }, 65));
+        assert!(!is_under_age(&birth, &Date { year: 2066, month: 6, day: 15 }, 65));
+        // No age before birth, so never under a maximum either.
+        assert!(!is_under_age(&birth, &Date { year: 1999, month: 1, day: 1 }, 65));
+    }
+
+    #[test]
+    fn timestamps_convert_to_utc_dates() {
+        for (timestamp, (year, month, day)) in [
+            (0, (1970, 1, 1)),
+            (86399, (1970, 1, 1)),
+            (951782400, (2000, 2, 29)),
+            (951868800, (2000, 3, 1)),
+            (2147483647, (2038, 1, 19)),
+            (2147483648, (2038, 1, 19)),
+            (4102444800, (2100, 1, 1)),
+            (253402300799, (9999, 12, 31)),
+        ] {
+            assert_eq!(timestamp_to_date(timestamp), Date { year, month, day }, "timestamp {}", timestamp);
+        }
+        // The last second of year 65535 still converts; anything later clamps instead of wrapping.
+        let last_day = days_since_epoch(&MAX_DATE) as u64 * 86400;
+        assert_eq!(timestamp_to_date(last_day + 86399), MAX_DATE);
+        assert_eq!(timestamp_to_date(last_day + 86400), MAX_DATE);
+        assert_eq!(timestamp_to_date(u64::MAX), MAX_DATE);
+    }
+
+    #[test]
+    fn age_is_none_before_birth_and_counts_whole_years() {
+        let birth = Date { year: 2000, month: 5, day: 20 };
+        // Current date before the birth year, then earlier in the birth year itself.
+        assert_eq!(calculate_age(&birth, &Date { year: 1999, month: 12, day: 31 }), None);
+        assert_eq!(calculate_age(&birth, &Date { year: 2000, month: 3, day: 1 }), None);
+        assert!(!is_over_age(&birth, &Date { year: 1999, month: 12, day: 31 }, 0));
+        // Same year as a later birthday, with an earlier month: a year short.
+        assert_eq!(calculate_age(&birth, &Date { year: 2025, month: 4, day: 30 }), Some(24));
+        assert_eq!(calculate_age(&birth, &Date { year: 2025, month: 5, day: 20 }), Some(25));
+        // Born on 29 February: a year older on 1 March in common years, on the 29th in leap years.
+        let leap = Date { year: 2004, month: 2, day: 29 };
+        assert_eq!(calculate_age(&leap, &Date { year: 2022, month: 2, day: 28 }), Some(17));
+        assert_eq!(calculate_age(&leap, &Date { year: 2022, month: 3, day: 1 }), Some(18));
+        assert_eq!(calculate_age(&leap, &Date { year: 2024, month: 2, day: 29 }), Some(20));
+    }
+
+    #[test]
+    fn drinking_age_follows_the_destination_table() {
+        let today = Date { year: 2026, month: 6, day: 1 };
+        let aged = |years: u16| Date { year: 2026 - years, month: 6, day: 1 };
+        for (destination, age, can_drink) in [
+            ("USA", 20, false),
+            ("USA", 21, true),
+            ("JPN", 19, false),
+            ("JPN", 20, true),
+            ("FRA", 17, false),
+            ("FRA", 18, true),
+        ] {
+            assert_eq!(can_drink_in(&aged(age), &today, destination), can_drink, "{} at {}", destination, age);
+        }
+        assert_eq!(drinking_age::drinking_age("USA"), Some(21));
+        assert_eq!(drinking_age::drinking_age("JPN"), Some(20));
+        assert_eq!(drinking_age::drinking_age("GBR"), Some(18));
+        // Unlisted destinations have no drinking age, so no one can drink there.
+        assert_eq!(drinking_age::drinking_age("SAU"), None);
+        assert!(!can_drink_in(&aged(40), &today, "SAU"));
+        assert!(!can_drink_in(&aged(40), &today, ""));
+    }
+
+    #[test]
+    fn age_grace_admits_only_holders_close_to_the_threshold() {
+        let birth = Date { year: 2000, month: 6, day: 15 };
+        // Clearly over, and exactly 18 on the day.
+        assert!(is_within_age_grace(&birth, &Date { year: 2030, month: 1, day: 1 }, 18, 30));
+        assert!(is_within_age_grace(&birth, &Date { year: 2018, month: 6, day: 15 }, 18, 30));
+        // 14 days short, then exactly 30 days short: inside the window. 31 days short is not.
+        assert!(is_within_age_grace(&birth, &Date { year: 2018, month: 6, day: 1 }, 18, 30));
+        assert!(is_within_age_grace(&birth, &Date { year: 2018, month: 5, day: 16 }, 18, 30));
+        assert!(!is_within_age_grace(&birth, &Date { year: 2018, month: 5, day: 15 }, 18, 30));
+        // Well under.
+        assert!(!is_within_age_grace(&birth, &Date { year: 2010, month: 6, day: 15 }, 18, 30));
+        // A 29 February birthday reaches 18 on 1 March of a common year.
+        let leap = Date { year: 2004, month: 2, day: 29 };
+        assert!(is_within_age_grace(&leap, &Date { year: 2022, month: 2, day: 28 }, 18, 1));
+        assert!(!is_within_age_grace(&leap, &Date { year: 2022, month: 2, day: 27 }, 18, 1));
+    }
+
+    #[test]
+    fn merkle_paths_prove_allowlist_membership() {
+        let documents: Vec<String> = ["C1", "A1", "B1", "E1", "D1"].iter().map(|d| d.to_string()).collect();
+        let tree = merkle::MerkleTree::new(&documents);
+        let root = tree.root();
+        for document in &documents {
+            let path = tree.path(document).unwrap();
+            // Five leaves pad to eight, so three levels.
+            assert_eq!(path.siblings.len(), 3);
+            assert!(merkle::is_member(document, &path, &root), "{}", document);
+        }
+        // Order of the input list doesn't change the root.
+        let mut reversed = documents.clone();
+        reversed.reverse();
+        assert_eq!(merkle::MerkleTree::new(&reversed).root(), root);
+
+        assert!(tree.path("F1").is_none());
+        let path = tree.path("A1").unwrap();
+        assert!(!merkle::is_member("F1", &path, &root));
+        assert!(!merkle::is_member("A1", &merkle::MerklePath { index: 1, ..path.clone() }, &root));
+        assert_eq!(merkle::MerkleTree::new(&[]).root(), [0u8; 32]);
+        assert!(!merkle::is_member("A1", &path, &[0u8; 32]));
+    }
+
+    #[test]
+    fn blocklist_matches_its_first_and_last_entries() {
+        let blocklist: Vec<String> = ["PRK", "IRN", "SYR", "RUS"].iter().map(|c| c.to_string()).collect();
+        let mut reversed = blocklist.clone();
+        reversed.reverse();
+        for list in [&blocklist, &reversed] {
+            assert!(groupings::contains_nationality(list, "PRK"), "{:?}", list);
+            assert!(groupings::contains_nationality(list, "RUS"), "{:?}", list);
+            assert!(groupings::contains_nationality(list, "SYR"), "{:?}", list);
+            assert!(!groupings::contains_nationality(list, "MYS"), "{:?}", list);
+        }
+        assert!(!groupings::contains_nationality(&[], "PRK"));
+        // The committed list doesn't depend on its order either.
+        assert_eq!(NationalitySet::from_codes(&blocklist).commitment, NationalitySet::from_codes(&reversed).commitment);
+    }
+
+    #[test]
+    fn merkle_paths_are_accepted_up_to_max_depth() {
+        let path = |depth: usize| merkle::MerklePath { siblings: vec![[7u8; 32]; depth], index: 5 };
+        let at_limit = path(merkle::MAX_MERKLE_DEPTH);
+        let root = at_limit.root(merkle::leaf_hash("C1"));
+        assert!(merkle::is_member("C1", &at_limit, &root));
+        // One level deeper is refused even though it hashes to its own root.
+        let over_limit = path(merkle::MAX_MERKLE_DEPTH + 1);
+        let root = over_limit.root(merkle::leaf_hash("C1"));
+        assert!(!merkle::is_member("C1", &over_limit, &root));
+    }
+
+    #[test]
+    fn full_record_commitment_changes_with_every_field() {
+        let passport = sample_passport();
+        let commitment = full_record_commitment(&passport);
+        let edits: [fn(&mut PassportAttributes); 6] = [
+            |p| p.document_number = DocumentNumber::new("L898902C4").unwrap(),
+            |p| p.date_of_birth.day = 13,
+            |p| p.date_of_expiry.year = 2031,
+            |p| p.nationality = Nationality::new("D").unwrap(),
+            |p| p.name = "ANNA ERIKSSON".to_string(),
+            |p| p.sex = "M".to_string(),
+        ];
+        for edit in edits {
+            let mut other = passport.clone();
+            edit(&mut other);
+            assert_ne!(full_record_commitment(&other), commitment);
+        }
+        // Length prefixes keep a character from moving between adjacent fields unnoticed.
+        let mut shifted = passport.clone();
+        shifted.name = "ANNA MARIA ERIKSSONU".to_string();
+        shifted.nationality = Nationality::new("TO").unwrap();
+        assert_ne!(full_record_commitment(&shifted), commitment);
+        assert_ne!(commitment, derive_identity_hash(&passport));
+    }
+
+    #[test]
+    fn keccak_identity_hash_matches_solidity_keccak256() {
+        // Known vector, so the primitive is the Ethereum Keccak-256 rather than NIST SHA3-256.
+        assert_eq!(
+            alloy_sol_types::private::keccak256(b"abc").0,
+            [
+                0x4e, 0x03, 0x65, 0x7a, 0xea, 0x45, 0xa9, 0x4f,
+                0xc7, 0xd4, 0x7b, 0xa8, 0x26, 0xc8, 0xd6, 0x67,
+                0xc0, 0xd1, 0xe6, 0xe3, 0x3a, 0x64, 0xa0, 0x36,
+                0xec, 0x44, 0xf5, 0x8f, 0xa1, 0x2d, 0x6c, 0x45,
+            ]
+        );
+        let passport = sample_passport();
+        let fields = CommitmentFields::default();
+        let preimage = identity_hash_preimage_with(&passport, fields);
+        let keccak = derive_identity_hash_with(&passport, CommitmentHash::Keccak256, fields).unwrap();
+        assert_eq!(keccak, alloy_sol_types::private::keccak256(&preimage).0);
+        assert_ne!(Some(keccak), derive_identity_hash_with(&passport, CommitmentHash::Sha256, fields));
+    }
+
+    #[test]
+    fn builder_reports_missing_and_inconsistent_fields() {
+        let builder = PassportAttributesBuilder::new()
+            .document_number("A12345678")
+            .birth_date(Date { year: 1990, month: 1, day: 1 })
+            .nationality("MYS")
+            .name("TAN AH KOW")
+            .sex("M");
+        assert_eq!(builder.clone().build().unwrap_err(), vec![FieldError::Missing("date of expiry")]);
+        assert_eq!(
+            builder.clone().expiry_date(Date { year: 2031, month: 2, day: 30 }).build().unwrap_err(),
+            vec![FieldError::InvalidDate("date of expiry")]
+        );
+        assert_eq!(
+            builder.clone().expiry_date(Date { year: 1989, month: 1, day: 1 }).build().unwrap_err(),
+            vec![FieldError::Inconsistent(ValidationError::ExpiryNotAfterBirth)]
+        );
+        assert!(builder.expiry_date(Date { year: 2031, month: 1, day: 1 }).build().is_ok());
+    }
+
+    #[test]
+    fn target_nationality_commitment_depends_on_code_and_salt() {
+        let salt = [7u8; 32];
+        let commitment = target_nationality_commitment("MYS", &salt);
+        // Normalized first, so the alpha-2 code or the name commit the same way.
+        assert_eq!(target_nationality_commitment("MY", &salt), commitment);
+        assert_eq!(target_nationality_commitment("Malaysia", &salt), commitment);
+        assert_ne!(target_nationality_commitment("SGP", &salt), commitment);
+        assert_ne!(target_nationality_commitment("MYS", &[8u8; 32]), commitment);
+    }
+
+    #[test]
+    fn wallet_binding_ignores_wallet_order() {
+        let evm = WalletEntry { chain_id: 1, address: vec![0xaa; 20] };
+        let solana = WalletEntry { chain_id: 501, address: vec![0xbb; 32] };
+        let base = WalletEntry { chain_id: 8453, address: vec![0xaa; 20] };
+        let passport = sample_passport();
+        let binding = derive_wallet_binding(&passport, &[evm.clone(), solana.clone(), base.clone()]);
+        assert_eq!(derive_wallet_binding(&passport, &[base.clone(), evm.clone(), solana.clone()]), binding);
+        assert_eq!(derive_wallet_binding(&passport, &[solana.clone(), base.clone(), evm.clone(), evm.clone()]), binding);
+        assert_eq!(sorted_wallets(&[solana.clone(), base.clone(), evm.clone()]), vec![evm.clone(), solana.clone(), base]);
+        assert_ne!(derive_wallet_binding(&passport, &[evm.clone(), solana.clone()]), binding);
+        let mut other = passport.clone();
+        other.name = "LIM AH KOW".to_string();
+        assert_ne!(derive_wallet_binding(&other, &[evm.clone(), solana.clone()]), derive_wallet_binding(&passport, &[evm, solana]));
+    }
+
+    #[test]
+    fn nationalities_normalize_to_icao_codes() {
+        for input in ["MYS", "my", " Malaysia ", "MYS<"] {
+            assert_eq!(iso3166::normalize_nationality(input), Some("MYS"));
+        }
+        assert_eq!(iso3166::normalize_nationality("DE"), Some("D"));
+        assert_eq!(iso3166::normalize_nationality("D<<"), Some("D"));
+        assert_eq!(iso3166::normalize_nationality("MYSS"), None);
+        assert_eq!(iso3166::normalize_nationality(""), None);
+        assert!(iso3166::same_nationality("GBR", "United Kingdom"));
+        assert!(!iso3166::same_nationality("GBR", "GBN"));
+        assert!(iso3166::COUNTRIES.windows(2).all(|pair| pair[0].0 < pair[1].0));
+    }
+
+    #[test]
+    fn stateless_codes_fall_back_to_the_issuer_only_when_asked() {
+        for code in ["XXA", "XXB", "XXC", "xxa", "XXB<", "Stateless person"] {
+            assert!(iso3166::is_stateless(code), "{}", code);
+            assert_eq!(iso3166::nationality_for_checks(code, Some("D"), true), "D");
+            assert_eq!(iso3166::nationality_for_checks(code, Some("D"), false), code);
+            assert_eq!(iso3166::nationality_for_checks(code, None, true), code);
+        }
+        for code in ["XXX", "GBR", "D", ""] {
+            assert!(!iso3166::is_stateless(code), "{}", code);
+            assert_eq!(iso3166::nationality_for_checks(code, Some("FRA"), true), code);
+        }
+        // Without the option a stateless code only matches lists that name it.
+        assert!(!iso3166::same_nationality("XXA", "D"));
+        assert!(!iso3166::same_nationality("XXA", "XXB"));
+        assert!(groupings::contains_nationality(&["XXB".to_string()], "XXB"));
+    }
+
+    // Vectors from EIP-1014.
+    #[test]
+    fn create2_address_matches_eip1014() {
+        let init_code_hash = |code: &[u8]| alloy_sol_types::private::keccak256(code).0;
+        let address = |hex: &str| -> [u8; 20] {
+            let mut bytes = [0u8; 20];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&hex[2 * i..2 * i + 2], 16).unwrap();
+            }
+            bytes
+        };
+        assert_eq!(
+            to_eip55(&create2_address(&[0u8; 20], &[0u8; 32], &init_code_hash(&[0x00]))),
+            "0x4D1A2e2bB4F88F0250f26Ffff098B0b30B26BF38"
+        );
+        let deployer = address("00000000000000000000000000000000deadbeef");
+        let mut salt = [0u8; 32];
+        salt[28..].copy_from_slice(&[0xca, 0xfe, 0xba, 0xbe]);
+        assert_eq!(
+            to_eip55(&create2_address(&deployer, &salt, &init_code_hash(&[0xde, 0xad, 0xbe, 0xef]))),
+            "0x60f3f640a8508fC6a86d45DF051962668E1e8AC7"
+        );
+
+        // A counterfactual wallet is CREATE2 over the identity-derived salt, and differs per identity and salt.
+        let commitment = [0x11; 32];
+        let factory = address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed");
+        let wallet = derive_counterfactual_address(&commitment, &factory, &[0u8; 32], &[0x22; 32]);
+        assert_eq!(wallet, create2_address(&factory, &counterfactual_salt(&commitment, &[0u8; 32]), &[0x22; 32]));
+        assert_ne!(wallet, derive_counterfactual_address(&[0x12; 32], &factory, &[0u8; 32], &[0x22; 32]));
+        assert_ne!(wallet, derive_counterfactual_address(&commitment, &factory, &[1u8; 32], &[0x22; 32]));
+    }
+
+    // Vectors from EIP-55.
+    #[test]
+    fn eip55_round_trips() {
+        for checksummed in [
+            "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed",
+            "0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359",
+            "0xdbF03B407c01E7cD3CBea99509d93f8DDDC8C6FB",
+            "0xD1220A0cf47c7B9Be7A2E6BA89F429762e7b9aDb",
+        ] {
+            let mut bytes = [0u8; 20];
+            for (i, byte) in bytes.iter_mut().enumerate() {
+                *byte = u8::from_str_radix(&checksummed[2 + 2 * i..4 + 2 * i], 16).unwrap();
+            }
+            assert_eq!(to_eip55(&bytes), checksummed);
+            assert!(validate_eip55(checksummed));
+            assert!(validate_eip55(&checksummed.to_lowercase()));
+        }
+    }
+
+    #[test]
+    fn eip55_rejects_bad_checksum() {
+        assert!(!validate_eip55("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"));
+        assert!(validate_eip55("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"));
+        assert!(!validate_eip55("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea"));
+    }
+
+    #[test]
+    fn passport_json_round_trips() {
+        let json = serde_json::to_string(&sample_passport()).unwrap();
+        assert_eq!(
+            json,
+            r#"{"documentNumber":"L898902C3","dateOfBirth":"1974-08-12","dateOfExpiry":"2030-04-15","nationality":"UTO","name":"ANNA MARIA ERIKSSON","sex":"F"}"#
+        );
+        let parsed: PassportAttributes = serde_json::from_str(&json).unwrap();
+        assert_eq!(format!("{:?}", parsed), format!("{:?}", sample_passport()));
+    }
+
+    // SP1Stdin is bincode, so the guest must keep seeing the plain field layout.
+    #[test]
+    fn date_binary_layout_is_unchanged() {
+        let date = Date::new(1974, 8, 12).unwrap();
+        assert_eq!(bincode::serialize(&date).unwrap(), [0xb6, 0x07, 8, 12]);
+        let parsed: Date = bincode::deserialize(&[0xb6, 0x07, 8, 12]).unwrap();
+        assert_eq!((parsed.year, parsed.month, parsed.day), (1974, 8, 12));
+    }
+
+    #[test]
+    fn date_json_rejects_impossible_dates() {
+        assert!(serde_json::from_str::<Date>(r#""2025-02-29""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""2025-13-01""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""20250101""#).is_err());
+        assert!(serde_json::from_str::<Date>(r#""2024-02-29""#).is_ok());
+    }
+
+    #[test]
+    fn commitment_version_separates_hashes() {
+        use sha2::Digest;
+
+        let passport = sample_passport();
+        let fields = CommitmentFields::default();
+        let v1 = versioned_identity_hash_preimage(COMMITMENT_VERSION, &passport, fields);
+        let v2 = versioned_identity_hash_preimage(COMMITMENT_VERSION + 1, &passport, fields);
+        let version_at = IDENTITY_HASH_DOMAIN.len();
+        assert_eq!(v1[..version_at], *IDENTITY_HASH_DOMAIN);
+        assert_eq!(v1[version_at], COMMITMENT_VERSION);
+        assert_eq!(v1[version_at + 1..], v2[version_at + 1..]);
+        assert_ne!(sha2::Sha256::digest(&v1), sha2::Sha256::digest(&v2));
+    }
+
+    // Pairs whose text fields ran together into the same bytes before they carried a length: a
+    // one-letter nationality (Germany is "D") and a name with the document number right after it.
+    #[test]
+    fn identity_hash_text_fields_do_not_run_together() {
+        let with = |name: &str, nationality: &str, document_number: &str| PassportAttributes {
+            name: name.to_string(),
+            nationality: Nationality::new(nationality).unwrap(),
+            document_number: DocumentNumber::new(document_number).unwrap(),
+            ..sample_passport()
+        };
+        let names_and_number = CommitmentFields::NAMES | CommitmentFields::DOCUMENT_NUMBER;
+        for (a, b, fields) in [
+            (with("ANNA ERIKSSON GR", "D", "L898902C3"), with("ANNA ERIKSSON ", "GRD", "L898902C3"), CommitmentFields::default()),
+            (with("ANNA", "UTO", "L898902C3"), with("ANNAL", "UTO", "898902C3"), names_and_number),
+        ] {
+            assert_ne!(identity_hash_preimage_with(&a, fields), identity_hash_preimage_with(&b, fields));
+            for hash in [CommitmentHash::Sha256, CommitmentHash::Keccak256] {
+                assert_ne!(derive_identity_hash_with(&a, hash, fields), derive_identity_hash_with(&b, hash, fields));
+            }
+        }
+    }
+
+    #[test]
+    fn nullifier_is_deterministic_and_scoped() {
+        let passport = sample_passport();
+        let scope = [0x11; 32];
+        assert_eq!(derive_nullifier(&passport, &scope), derive_nullifier(&passport, &scope));
+        assert_ne!(derive_nullifier(&passport, &scope), derive_nullifier(&passport, &[0x12; 32]));
+        // Same 32 bytes as a nullifier scope and as a verifier id must not collide.
+        assert_ne!(derive_nullifier(&passport, &scope), derive_audience_commitment(&passport, &scope));
+    }
+
+    // The default identity hash leaves out the document number so it survives renewal, but it
+    // does include the name: only a field set without NAMES drops it.
+    #[test]
+    fn default_identity_hash_survives_renewal() {
+        let passport = sample_passport();
+        let renewed = PassportAttributes {
+            document_number: DocumentNumber::new("X12345678").unwrap(),
+            date_of_expiry: Date { year: 2040, month: 4, day: 15 },
+            ..passport.clone()
+        };
+        assert_eq!(derive_identity_hash(&passport), derive_identity_hash(&renewed));
+        assert_eq!(derive_identity_hash_poseidon(&passport), derive_identity_hash_poseidon(&renewed));
+
+        let renamed = PassportAttributes {
+            name: "ANNA ERIKSSON".to_string(),
+            ..passport.clone()
+        };
+        assert_ne!(derive_identity_hash(&passport), derive_identity_hash(&renamed));
+        let without_names = CommitmentFields::NATIONALITY | CommitmentFields::DATE_OF_BIRTH;
+        assert_eq!(
+            derive_identity_hash_with(&passport, CommitmentHash::Sha256, without_names),
+            derive_identity_hash_with(&renamed, CommitmentHash::Sha256, without_names)
+        );
+    }
+
+    #[test]
+    fn identity_set_commitment_ignores_order_and_repeats() {
+        let (a, b) = ([0x01; 32], [0x02; 32]);
+        let (count, commitment) = identity_set_commitment(&[b, a, b]);
+        assert_eq!(count, 2);
+        assert_eq!(identity_set_commitment(&[a, b]), (2, commitment));
+        assert_ne!(identity_set_commitment(&[a]).1, commitment);
+    }
+
+    #[test]
+    fn policy_commitment_ignores_nationality_order() {
+        let policy = VerificationPolicy {
+            min_age: 18,
+            allowed_nationalities: vec!["MYS".to_string(), "SGP".to_string()],
+            require_unexpired: true,
+        };
+        let reordered = VerificationPolicy {
+            allowed_nationalities: vec!["SGP".to_string(), "MYS".to_string()],
+            ..policy.clone()
+        };
+        assert_eq!(policy.commitment(), reordered.commitment());
+        for changed in [
+            VerificationPolicy { min_age: 21, ..policy.clone() },
+            VerificationPolicy { require_unexpired: false, ..policy.clone() },
+            VerificationPolicy { allowed_nationalities: Vec::new(), ..policy.clone() },
+        ] {
+            assert_ne!(changed.commitment(), policy.commitment());
+        }
+    }
+
+    #[test]
+    fn commitment_domains_are_prefix_free() {
+        let domains = [
+            IDENTITY_HASH_DOMAIN,
+            NULLIFIER_DOMAIN,
+            AUDIENCE_DOMAIN,
+            POLICY_DOMAIN,
+            TARGET_NATIONALITY_DOMAIN,
+            WALLET_BINDING_DOMAIN,
+            FULL_RECORD_DOMAIN,
+        ];
+        for (i, a) in domains.iter().enumerate() {
+            for (j, b) in domains.iter().enumerate() {
+                assert!(i == j || !b.starts_with(a), "{:?} is a prefix of {:?}", a, b);
+            }
+        }
+    }
+
+    // A scope equal to the identity hash itself, and the same 32 bytes as nullifier scope and
+    // verifier id, still land in three different namespaces.
+    #[test]
+    fn commitment_families_do_not_collide() {
+        let passport = sample_passport();
+        let identity = derive_identity_hash(&passport);
+        let nullifier = derive_nullifier(&passport, &identity);
+        let audience = derive_audience_commitment(&passport, &identity);
+        assert_ne!(identity, nullifier);
+        assert_ne!(identity, audience);
+        assert_ne!(nullifier, audience);
+    }
+
+    // BER-TLV with a single- or two-byte tag and the shortest definite length.
+    fn tlv(tag: &[u8], value: &[u8]) -> Vec<u8> {
+        let mut out = tag.to_vec();
+        match value.len() {
+            len @ 0..=0x7F => out.push(len as u8),
+            len @ 0x80..=0xFF => out.extend_from_slice(&[0x81, len as u8]),
+            len => out.extend_from_slice(&[0x82, (len >> 8) as u8, len as u8]),
+        }
+        out.extend_from_slice(value);
+        out
+    }
+
+    #[test]
+    fn typed_names_are_transliterated_like_the_mrz() {
+        // German
+        assert_eq!(mrz::transliterate_to_mrz("Jürgen Müller-Groß"), "JUERGEN MUELLER GROSS");
+        assert_eq!(mrz::transliterate_to_mrz("Käthe Schröder"), "KAETHE SCHROEDER");
+        // Scandinavian
+        assert_eq!(mrz::transliterate_to_mrz("Åse Bjørk Ærø"), "AASE BJOERK AEROE");
+        // French
+        assert_eq!(mrz::transliterate_to_mrz("François  Lefèvre d'Œuvray"), "FRANCOIS LEFEVRE DOEUVRAY");
+        // Already in MRZ form
+        assert_eq!(mrz::transliterate_to_mrz("ANNA MARIA ERIKSSON"), "ANNA MARIA ERIKSSON");
+    }
+
+    #[test]
+    fn oracle_signature_covers_only_the_signed_time() {
+        let signed = oracle::sign_timestamp(&[0x44; 32], 1_761_955_200).unwrap();
+        assert!(oracle::verify_timestamp(&signed, 1_761_955_200));
+        // A prover moving the date forward by a second, or years, no longer has a signature.
+        assert!(!oracle::verify_timestamp(&signed, 1_761_955_201));
+        assert!(!oracle::verify_timestamp(&oracle::OracleSignature { signature: vec![0x30, 0x00], ..signed.clone() }, 1_761_955_200));
+        assert!(!oracle::verify_timestamp(&oracle::OracleSignature { public_key: vec![0x04; 10], ..signed.clone() }, 1_761_955_200));
+
+        let other = oracle::sign_timestamp(&[0x55; 32], 1_761_955_200).unwrap();
+        assert_ne!(oracle::oracle_key_hash(&signed.public_key), oracle::oracle_key_hash(&other.public_key));
+        let compressed = p256::ecdsa::VerifyingKey::from_sec1_bytes(&signed.public_key).unwrap().to_encoded_point(true);
+        assert_eq!(oracle::oracle_key_hash(compressed.as_bytes()), oracle::oracle_key_hash(&signed.public_key));
+        assert!(oracle::sign_timestamp(&[0; 32], 1_761_955_200).is_none());
+    }
+
+    #[test]
+    fn check_digit_results_name_each_failed_field() {
+        let line2 = "L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let results = mrz::td3_check_digit_results(line2).unwrap();
+        assert!(results.iter().all(|result| result.is_valid()));
+        assert!(mrz::td3_check_digits(line2).is_ok());
+
+        // A mistyped birth date breaks its own digit and the composite, nothing else.
+        let typo = line2.replacen("740812", "740813", 1);
+        let failed: Vec<&str> = mrz::td3_check_digit_results(&typo)
+            .unwrap()
+            .iter()
+            .filter(|result| !result.is_valid())
+            .map(|result| result.field)
+            .collect();
+        assert_eq!(failed, ["date of birth", "composite"]);
+        assert_eq!(mrz::td3_check_digits(&typo).unwrap_err(), mrz::MrzError::CheckDigit("date of birth"));
+    }
+
+    #[test]
+    fn mrz_unspecified_sex_reads_as_x() {
+        let line1 = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<";
+        let current = Date { year: 2026, month: 1, day: 1 };
+        // The sex isn't covered by any check digit, so the specimen stays valid with each value.
+        let with_sex = |sex: char| {
+            let line2 = format!("L898902C36UTO7408122{}1204159ZE184226B<<<<<10", sex);
+            mrz::parse_td3_mrz(line1, &line2, &current)
+        };
+        assert_eq!(with_sex('F').unwrap().sex, "F");
+        assert_eq!(with_sex('M').unwrap().sex, "M");
+        assert_eq!(with_sex('X').unwrap().sex, "X");
+        assert_eq!(with_sex('<').unwrap().sex, "X");
+        assert_eq!(with_sex('1').unwrap_err(), mrz::MrzError::InvalidSex('1'));
+    }
+
+    // Found by the parse_td3_mrz fuzz target: a birth year after an evaluation year below 100
+    // has no previous century, which used to overflow.
+    #[test]
+    fn mrz_birth_century_never_overflows() {
+        let line1 = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<";
+        let line2 = "L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        assert_eq!(
+            mrz::parse_td3_mrz(line1, line2, &Date { year: 50, month: 1, day: 1 }).unwrap_err(),
+            mrz::MrzError::InvalidDate("date of birth")
+        );
+        let passport = mrz::parse_td3_mrz(line1, line2, &Date { year: u16::MAX, month: 1, day: 1 }).unwrap();
+        assert_eq!(passport.date_of_birth.year, 65474);
+    }
+
+    // The ICAO 9303 specimen, wrapped as DG1 is stored on the chip.
+    #[test]
+    fn dg1_parses_specimen_mrz() {
+        let mrz = b"P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let dg1 = tlv(&[0x61], &tlv(&[0x5F, 0x1F], mrz));
+        assert_eq!(&dg1[..5], &[0x61, 0x5B, 0x5F, 0x1F, 0x58]);
+
+        let current = Date { year: 2026, month: 1, day: 1 };
+        let expected = PassportAttributes {
+            date_of_expiry: Date { year: 2012, month: 4, day: 15 },
+            ..sample_passport()
+        };
+        assert_eq!(
+            serde_json::to_value(dg::parse_dg1(&dg1, &current).unwrap()).unwrap(),
+            serde_json::to_value(expected).unwrap()
+        );
+        assert_eq!(dg::parse_dg1(&dg1[..40], &current).unwrap_err(), dg::DgError::Malformed);
+        let td1 = tlv(&[0x61], &tlv(&[0x5F, 0x1F], &[b'<'; 90]));
+        assert_eq!(dg::dg1_mrz_lines(&td1), Err(dg::DgError::NotTd3(90)));
+    }
+
+    #[test]
+    fn dg15_extracts_public_key() {
+        let spki = |algorithm: Vec<u8>, key: &[u8]| {
+            let mut bits = vec![0];
+            bits.extend_from_slice(key);
+            tlv(&[0x6F], &tlv(&[0x30], &[tlv(&[0x30], &algorithm), tlv(&[0x03], &bits)].concat()))
+        };
+        let ec_algorithm = |curve: &[u8]| {
+            [tlv(&[0x06], &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01]), tlv(&[0x06], curve)].concat()
+        };
+
+        let point = [0x04; 65];
+        let p256 = spki(ec_algorithm(&[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07]), &point);
+        assert_eq!(dg::parse_dg15(&p256), Ok((SignatureAlgorithm::EcdsaP256, point.to_vec())));
+        let brainpool = spki(ec_algorithm(&[0x2B, 0x24, 0x03, 0x03, 0x02, 0x08, 0x01, 0x01, 0x07]), &point);
+        assert_eq!(dg::parse_dg15(&brainpool).unwrap().0, SignatureAlgorithm::EcdsaBrainpoolP256r1);
+
+        // Long enough to need long-form lengths at every level.
+        let rsa_key = [0x30; 270];
+        let rsa_algorithm = [tlv(&[0x06], &[0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x01, 0x01]), vec![0x05, 0x00]].concat();
+        assert_eq!(dg::parse_dg15(&spki(rsa_algorithm, &rsa_key)), Ok((SignatureAlgorithm::RsaPkcs1, rsa_key.to_vec())));
+
+        let unknown = spki(ec_algorithm(&[0x2B, 0x81, 0x04, 0x00, 0x22]), &point);
+        assert_eq!(dg::parse_dg15(&unknown), Err(dg::DgError::UnsupportedKey));
+    }
+
+    // A minimal EF.SOD listing DG1 and DG2, with signed attributes whose messageDigest covers it.
+    #[test]
+    fn sod_binds_dg1_to_signed_attributes() {
+        use sha2::{Digest, Sha256};
+
+        let specimen = b"P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<L898902C36UTO7408122F1204159ZE184226B<<<<<10";
+        let passport = PassportAttributes {
+            date_of_expiry: Date { year: 2012, month: 4, day: 15 },
+            ..sample_passport()
+        };
+        let dg1 = tlv(&[0x61], &tlv(&[0x5F, 0x1F], specimen));
+        let sha256_oid = [0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01];
+        let lds_oid = [0x67, 0x81, 0x08, 0x01, 0x01, 0x01];
+        let hashes = [
+            tlv(&[0x30], &[tlv(&[0x02], &[1]), tlv(&[0x04], &Sha256::digest(&dg1))].concat()),
+            tlv(&[0x30], &[tlv(&[0x02], &[2]), tlv(&[0x04], &[0; 32])].concat()),
+        ];
+        let lds = tlv(
+            &[0x30],
+            &[tlv(&[0x02], &[0]), tlv(&[0x30], &tlv(&[0x06], &sha256_oid)), tlv(&[0x30], &hashes.concat())].concat(),
+        );
+        let encap_content_info = tlv(&[0x30], &[tlv(&[0x06], &lds_oid), tlv(&[0xA0], &tlv(&[0x04], &lds))].concat());
+        let signed_data = tlv(&[0x30], &[tlv(&[0x02], &[3]), tlv(&[0x31], &[]), encap_content_info].concat());
+        let signed_data_oid = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02];
+        let sod = tlv(&[0x77], &tlv(&[0x30], &[tlv(&[0x06], &signed_data_oid), tlv(&[0xA0], &signed_data)].concat()));
+        let message_digest_oid = [0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04];
+        let signed_attributes = tlv(
+            &[0x31],
+            &tlv(&[0x30], &[tlv(&[0x06], &message_digest_oid), tlv(&[0x31], &tlv(&[0x04], &Sha256::digest(&lds)))].concat()),
+        );
+
+        let current = Date { year: 2026, month: 1, day: 1 };
+        let document_signature = DocumentSignature {
+            algorithm: SignatureAlgorithm::EcdsaP256,
+            public_key: vec![],
+            signed_attributes,
+            signature: vec![],
+        };
+        let data_groups = dg::DataGroups { dg1: dg1.clone(), sod: sod.clone() };
+        assert!(dg::verify_sod_hashes(&sod, &dg1));
+        assert!(is_data_group_consistent(&passport, &document_signature, &data_groups, &current));
+
+        // The SOD only vouches for the DG1 it hashed, and DG1 only for its own fields.
+        let mut swapped = dg1.clone();
+        swapped[5] = b'D';
+        assert!(!dg::verify_sod_hashes(&sod, &swapped));
+        let renamed = PassportAttributes {
+            name: "ANNA ERIKSSON".to_string(),
+            ..passport.clone()
+        };
+        assert!(!is_data_group_consistent(&renamed, &document_signature, &data_groups, &current));
+        let unsigned = DocumentSignature {
+            signed_attributes: tlv(&[0x31], &[]),
+            ..document_signature
+        };
+        assert!(!is_data_group_consistent(&passport, &unsigned, &data_groups, &current));
+    }
+
+    // contentType = id-icao-mrtd-security-ldsSecurityObject and a messageDigest, as a passport's
+    // SignerInfo carries them, in DER order.
+    const SIGNED_ATTRIBUTES_DER: [u8; 74] = [
+        0x31, 0x48,
+        0x30, 0x15, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x03, 0x31, 0x08, 0x06, 0x06, 0x67, 0x81, 0x08, 0x01, 0x01, 0x01,
+        0x30, 0x2F, 0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x09, 0x04, 0x31, 0x22, 0x04, 0x20,
+        0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+        0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01, 0x01,
+    ];
+
+    #[test]
+    fn signed_attributes_are_hashed_in_der_form() {
+        use p256::ecdsa::signature::Signer;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let der = SIGNED_ATTRIBUTES_DER.to_vec();
+        assert_eq!(dg::canonical_signed_attributes(&der).unwrap(), der);
+
+        // The same attributes as a BER reader might return them: [0] IMPLICIT as stored in the
+        // SignerInfo, messageDigest first, and a long-form length.
+        let content_type = &der[2..25];
+        let message_digest = &der[25..];
+        let mut attributes = message_digest.to_vec();
+        attributes.extend_from_slice(&[0x30, 0x81, 0x15]);
+        attributes.extend_from_slice(&content_type[2..]);
+        let ber = tlv(&[0xA0], &attributes);
+        assert_ne!(ber, der);
+        assert_eq!(dg::canonical_signed_attributes(&ber).unwrap(), der);
+
+        let key = SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+        let signature: Signature = key.sign(&der);
+        let document_signature = DocumentSignature {
+            algorithm: SignatureAlgorithm::EcdsaP256,
+            public_key: key.verifying_key().to_encoded_point(false).as_bytes().to_vec(),
+            signed_attributes: ber,
+            signature: signature.to_der().as_bytes().to_vec(),
+        };
+        assert_eq!(verify_passport_signature_detailed(&document_signature), Ok(()));
+
+        assert_eq!(dg::canonical_signed_attributes(&der[..40]), Err(dg::DgError::Malformed));
+        assert!(dg::canonical_signed_attributes(&tlv(&[0x30], &der[2..])).is_err());
+    }
+
+    #[test]
+    fn signature_rejections_say_why() {
+        use p256::ecdsa::signature::Signer;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let key = SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+        let signed_attributes = b"signed attributes".to_vec();
+        let signature: Signature = key.sign(&signed_attributes);
+        let valid = DocumentSignature {
+            algorithm: SignatureAlgorithm::EcdsaP256,
+            public_key: key.verifying_key().to_encoded_point(false).as_bytes().to_vec(),
+            signed_attributes,
+            signature: signature.to_der().as_bytes().to_vec(),
+        };
+        assert_eq!(verify_passport_signature_detailed(&valid), Ok(()));
+        assert!(verify_passport_signature(&valid));
+
+        let reject = |signature: DocumentSignature| verify_passport_signature_detailed(&signature).unwrap_err();
+        assert_eq!(
+            reject(DocumentSignature { algorithm: SignatureAlgorithm::EcdsaBrainpoolP256r1, ..valid.clone() }),
+            PassportVerifyError::UnsupportedAlgorithm(SignatureAlgorithm::EcdsaBrainpoolP256r1)
+        );
+        assert_eq!(
+            reject(DocumentSignature { algorithm: SignatureAlgorithm::Unsupported, ..valid.clone() }),
+            PassportVerifyError::UnsupportedAlgorithm(SignatureAlgorithm::Unsupported)
+        );
+        assert_eq!(
+            reject(DocumentSignature { public_key: vec![0x04; 10], ..valid.clone() }),
+            PassportVerifyError::MalformedPublicKey
+        );
+        assert_eq!(
+            reject(DocumentSignature { algorithm: SignatureAlgorithm::RsaPkcs1, ..valid.clone() }),
+            PassportVerifyError::MalformedPublicKey
+        );
+        assert_eq!(
+            reject(DocumentSignature { signature: vec![0x30, 0x00], ..valid.clone() }),
+            PassportVerifyError::MalformedSignature
+        );
+        assert_eq!(
+            reject(DocumentSignature { signed_attributes: b"other attributes".to_vec(), ..valid }),
+            PassportVerifyError::SignatureMismatch
+        );
+    }
+
+    #[test]
+    fn empty_signed_data_never_verifies() {
+        use p256::ecdsa::signature::Signer;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let key = SigningKey::from_bytes(&[0x33; 32].into()).unwrap();
+        let signature: Signature = key.sign(b"");
+        for algorithm in [SignatureAlgorithm::RsaPkcs1, SignatureAlgorithm::EcdsaP256, SignatureAlgorithm::Unsupported] {
+            // A genuine signature over the empty string is still refused.
+            let empty_attributes = DocumentSignature {
+                algorithm,
+                public_key: key.verifying_key().to_encoded_point(false).as_bytes().to_vec(),
+                signed_attributes: Vec::new(),
+                signature: signature.to_der().as_bytes().to_vec(),
+            };
+            let empty_signature = DocumentSignature {
+                signed_attributes: b"signed attributes".to_vec(),
+                signature: Vec::new(),
+                ..empty_attributes.clone()
+            };
+            for document_signature in [empty_attributes, empty_signature] {
+                assert_eq!(verify_passport_signature_detailed(&document_signature), Err(PassportVerifyError::MissingSignedData));
+                assert!(!verify_passport_signature(&document_signature));
+                assert_eq!(signature_status(Some(&document_signature)), SignatureStatus::Invalid);
+            }
+        }
+    }
+
+    #[test]
+    fn unknown_signature_algorithms_are_committed_as_unsupported() {
+        let algorithm: SignatureAlgorithm = serde_json::from_str("\"rsassaPss\"").unwrap();
+        assert_eq!(algorithm, SignatureAlgorithm::Unsupported);
+        assert!(!algorithm.is_supported());
+        assert!(SignatureAlgorithm::EcdsaP256.is_supported());
+
+        let signature = DocumentSignature {
+            algorithm,
+            public_key: vec![0x04; 65],
+            signed_attributes: b"signed attributes".to_vec(),
+            signature: vec![0x30; 72],
+        };
+        assert_eq!(signature_status(Some(&signature)), SignatureStatus::UnsupportedAlgorithm);
+        assert_eq!(signature_status(None), SignatureStatus::NotSupplied);
+        let forged = DocumentSignature { algorithm: SignatureAlgorithm::EcdsaP256, ..signature };
+        assert_eq!(signature_status(Some(&forged)), SignatureStatus::Invalid);
+    }
+
+    #[test]
+    fn dsc_must_chain_to_a_trusted_csca() {
+        use p256::ecdsa::signature::Signer;
+        use p256::ecdsa::{Signature, SigningKey};
+
+        let csca = SigningKey::from_bytes(&[0x11; 32].into()).unwrap();
+        let dsc = SigningKey::from_bytes(&[0x22; 32].into()).unwrap();
+        let point = |key: &SigningKey| key.verifying_key().to_encoded_point(false).as_bytes().to_vec();
+        let ec_public_key_oid = [0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x02, 0x01];
+        let prime256v1_oid = [0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x03, 0x01, 0x07];
+        let spki = tlv(
+            &[0x30],
+            &[
+                tlv(&[0x30], &[tlv(&[0x06], &ec_public_key_oid), tlv(&[0x06], &prime256v1_oid)].concat()),
+                tlv(&[0x03], &[&[0][..], &point(&dsc)].concat()),
+            ]
+            .concat(),
+        );
+        let ecdsa_with_sha256 = tlv(&[0x30], &tlv(&[0x06], &[0x2A, 0x86, 0x48, 0xCE, 0x3D, 0x04, 0x03, 0x02]));
+        let tbs = tlv(
+            &[0x30],
+            &[
+                tlv(&[0xA0], &tlv(&[0x02], &[2])),
+                tlv(&[0x02], &[1]),
+                ecdsa_with_sha256.clone(),
+                tlv(&[0x30], &[]),
+                tlv(&[0x30], &[]),
+                tlv(&[0x30], &[]),
+                spki,
+            ]
+            .concat(),
+        );
+        let certificate_signature: Signature = csca.sign(&tbs);
+        let certificate_bits = [&[0][..], certificate_signature.to_der().as_bytes()].concat();
+        let certificate = tlv(&[0x30], &[tbs, ecdsa_with_sha256, tlv(&[0x03], &certificate_bits)].concat());
+
+        let document_signature = DocumentSignature {
+            algorithm: SignatureAlgorithm::EcdsaP256,
+            public_key: point(&dsc),
+            signed_attributes: vec![],
+            signature: vec![],
+        };
+        let certificates = csca::IssuerCertificates {
+            dsc: certificate,
+            csca_algorithm: SignatureAlgorithm::EcdsaP256,
+            csca_public_key: point(&csca),
+        };
+        let trusted = csca::csca_key_hash(&point(&csca));
+        let other = csca::csca_key_hash(&point(&dsc));
+        assert!(csca::is_trusted_issuer(&document_signature, &certificates, &[other, trusted]));
+        assert!(!csca::is_trusted_issuer(&document_signature, &certificates, &[other]));
+        assert_eq!(csca::root_set_commitment(&[other, trusted]), csca::root_set_commitment(&[trusted, other, trusted]));
+        assert_eq!(csca::root_set_commitment(&[]), [0u8; 32]);
+
+        // A self-signed DSC, or one certifying a different key, doesn't pass.
+        let self_signed = csca::IssuerCertificates {
+            csca_public_key: point(&dsc),
+            ..certificates.clone()
+        };
+        assert!(!csca::is_trusted_issuer(&document_signature, &self_signed, &[other, trusted]));
+        let other_key = DocumentSignature {
+            public_key: point(&csca),
+            ..document_signature
+        };
+        assert!(!csca::is_trusted_issuer(&other_key, &certificates, &[other, trusted]));
+    }
+}
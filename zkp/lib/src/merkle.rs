@@ -0,0 +1,99 @@
+// Membership of a document number in a verifier's allowlist, proved against the list's Merkle
+// root so the proof shows the passport is listed without revealing which entry it is.
+//
+// The tree is SHA-256 with RFC 6962 style domain bytes (0x00 for leaves, 0x01 for nodes), so a
+// node can't be passed off as a leaf. Document numbers are sorted and deduplicated like
+// nationality_set_commitment, and the leaf level is padded to a power of two with zero hashes.
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Paths longer than this are rejected by the guest; 2^32 entries is more than any allowlist.
+pub const MAX_MERKLE_DEPTH: usize = 32;
+
+// A leaf's siblings from the bottom level up. Bit i of index is set when the path's node at
+// level i is a right child.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct MerklePath {
+    pub siblings: Vec<[u8; 32]>,
+    pub index: u64,
+}
+
+impl MerklePath {
+    pub fn root(&self, leaf: [u8; 32]) -> [u8; 32] {
+        self.siblings.iter().enumerate().fold(leaf, |node, (level, sibling)| {
+            if self.index >> level & 1 == 1 {
+                node_hash(sibling, &node)
+            } else {
+                node_hash(&node, sibling)
+            }
+        })
+    }
+}
+
+pub fn leaf_hash(document_number: &str) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(document_number.as_bytes());
+    hasher.finalize().into()
+}
+
+fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+// False for an all-zero root, which is what the guest commits when no allowlist was used.
+pub fn is_member(document_number: &str, path: &MerklePath, root: &[u8; 32]) -> bool {
+    *root != [0u8; 32] && path.siblings.len() <= MAX_MERKLE_DEPTH && path.root(leaf_hash(document_number)) == *root
+}
+
+// Host-side: the whole tree, kept so paths can be read off it.
+pub struct MerkleTree {
+    documents: Vec<String>,
+    // levels[0] is the padded leaf level, the last level is the root alone
+    levels: Vec<Vec<[u8; 32]>>,
+}
+
+impl MerkleTree {
+    pub fn new(document_numbers: &[String]) -> Self {
+        let mut documents = document_numbers.to_vec();
+        documents.sort_unstable();
+        documents.dedup();
+        let mut leaves: Vec<[u8; 32]> = documents.iter().map(|document| leaf_hash(document)).collect();
+        leaves.resize(leaves.len().max(1).next_power_of_two(), [0u8; 32]);
+        let mut levels = vec![leaves];
+        while levels[levels.len() - 1].len() > 1 {
+            let next = levels[levels.len() - 1]
+                .chunks(2)
+                .map(|pair| node_hash(&pair[0], &pair[1]))
+                .collect();
+            levels.push(next);
+        }
+        MerkleTree { documents, levels }
+    }
+
+    // Zero for an empty list, matching the "no allowlist" root.
+    pub fn root(&self) -> [u8; 32] {
+        if self.documents.is_empty() {
+            return [0u8; 32];
+        }
+        self.levels[self.levels.len() - 1][0]
+    }
+
+    // None when the document number isn't listed.
+    pub fn path(&self, document_number: &str) -> Option<MerklePath> {
+        let position = self.documents.binary_search_by(|document| document.as_str().cmp(document_number)).ok()?;
+        let siblings = self.levels[..self.levels.len() - 1]
+            .iter()
+            .enumerate()
+            .map(|(level, nodes)| nodes[(position >> level) ^ 1])
+            .collect();
+        Some(MerklePath {
+            siblings,
+            index: position as u64,
+        })
+    }
+}
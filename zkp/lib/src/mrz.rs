@@ -0,0 +1,225 @@
+use crate::{mrz_check_digit, AttributeError, Date, DocumentNumber, MrzCheckDigits, Nationality, PassportAttributes};
+
+const TD3_LINE_LENGTH: usize = 44;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MrzError {
+    InvalidLength { line: u8, length: usize },
+    NotPassport,
+    InvalidDate(&'static str),
+    CheckDigit(&'static str),
+    InvalidSex(char),
+    Invalid(AttributeError),
+}
+
+impl std::fmt::Display for MrzError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MrzError::InvalidLength { line, length } => {
+                write!(f, "MRZ line {} has {} characters, expected {}", line, length, TD3_LINE_LENGTH)
+            }
+            MrzError::NotPassport => write!(f, "MRZ is not a TD3 passport (line 1 must start with 'P')"),
+            MrzError::InvalidDate(field) => write!(f, "invalid {} in MRZ", field),
+            MrzError::CheckDigit(field) => write!(f, "{} check digit does not match", field),
+            MrzError::InvalidSex(sex) => write!(f, "invalid sex {:?} in MRZ, expected M, F, X or <", sex),
+            MrzError::Invalid(err) => err.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for MrzError {}
+
+impl From<AttributeError> for MrzError {
+    fn from(err: AttributeError) -> Self {
+        MrzError::Invalid(err)
+    }
+}
+
+// Parses the two 44-character lines of a TD3 (passport) MRZ and validates every check digit.
+// Birth years are two digits, so current decides the century: years up to current's are 20xx,
+// later ones 19xx. Expiry years are always 20xx. The name comes out as "GIVEN NAMES SURNAME",
+// matching the OCR service so both paths produce the same identity hash.
+pub fn parse_td3_mrz(line1: &str, line2: &str, current: &Date) -> Result<PassportAttributes, MrzError> {
+    let line1 = line1.trim().as_bytes();
+    if line1.len() != TD3_LINE_LENGTH {
+        return Err(MrzError::InvalidLength { line: 1, length: line1.len() });
+    }
+    if line1[0] != b'P' {
+        return Err(MrzError::NotPassport);
+    }
+    td3_check_digits(line2)?;
+    let line2 = line2.trim().as_bytes();
+
+    // Checked, since current is the caller's: a year below 100 has no previous century.
+    let century = current.year - current.year % 100;
+    let date_of_birth = mrz_field_date(&line2[13..19], "date of birth", |yy| {
+        if yy <= current.year % 100 {
+            century.checked_add(yy)
+        } else {
+            century.checked_sub(100).map(|previous| previous + yy)
+        }
+    })?;
+    let date_of_expiry = mrz_field_date(&line2[21..27], "date of expiry", |yy| Some(2000 + yy))?;
+
+    // ICAO 9303 writes an unspecified sex as '<' or, since 2021, 'X'. Both become "X", the value
+    // passport JSON and requiredSex use for it.
+    let sex = match line2[20] {
+        b'M' => "M",
+        b'F' => "F",
+        b'X' | b'<' => "X",
+        other => return Err(MrzError::InvalidSex(other as char)),
+    };
+
+    Ok(PassportAttributes {
+        document_number: DocumentNumber::new(strip_filler(&line2[0..9]))?,
+        date_of_birth,
+        date_of_expiry,
+        nationality: Nationality::new(strip_filler(&line2[10..13]))?,
+        name: mrz_name(&line1[5..]),
+        sex: sex.to_string(),
+    })
+}
+
+// One check digit of TD3 line 2: what is printed and what ICAO 9303 computes for its field.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CheckDigitResult {
+    pub field: &'static str,
+    pub printed: char,
+    pub computed: u8,
+}
+
+impl CheckDigitResult {
+    // An empty optional data field may carry '<' instead of 0 as its check digit.
+    pub fn printed_value(&self) -> Option<u8> {
+        match self.printed {
+            '0'..='9' => Some(self.printed as u8 - b'0'),
+            '<' => Some(0),
+            _ => None,
+        }
+    }
+
+    pub fn is_valid(&self) -> bool {
+        self.printed_value() == Some(self.computed)
+    }
+}
+
+// Every check digit of TD3 line 2 in MRZ order (document number, date of birth, date of expiry,
+// optional data, composite), valid or not, so a typed MRZ can be checked field by field.
+pub fn td3_check_digit_results(line2: &str) -> Result<[CheckDigitResult; 5], MrzError> {
+    let line2 = line2.trim().as_bytes();
+    if line2.len() != TD3_LINE_LENGTH {
+        return Err(MrzError::InvalidLength { line: 2, length: line2.len() });
+    }
+
+    let result = |field: &[u8], digit: u8, name: &'static str| CheckDigitResult {
+        field: name,
+        printed: digit as char,
+        computed: mrz_check_digit(field),
+    };
+    let composite: Vec<u8> = [&line2[0..10], &line2[13..20], &line2[21..43]].concat();
+    Ok([
+        result(&line2[0..9], line2[9], "document number"),
+        result(&line2[13..19], line2[19], "date of birth"),
+        result(&line2[21..27], line2[27], "date of expiry"),
+        result(&line2[28..42], line2[42], "optional data"),
+        result(&composite, line2[43], "composite"),
+    ])
+}
+
+// Validates the document number, date, optional data and composite check digits of TD3 line 2
+// and returns the first three for the guest to re-check against the structured fields.
+pub fn td3_check_digits(line2: &str) -> Result<MrzCheckDigits, MrzError> {
+    let results = td3_check_digit_results(line2)?;
+    if let Some(failed) = results.iter().find(|result| !result.is_valid()) {
+        return Err(MrzError::CheckDigit(failed.field));
+    }
+    Ok(MrzCheckDigits {
+        document_number: results[0].computed,
+        date_of_birth: results[1].computed,
+        date_of_expiry: results[2].computed,
+    })
+}
+
+fn mrz_field_date(field: &[u8], name: &'static str, century: impl Fn(u16) -> Option<u16>) -> Result<Date, MrzError> {
+    let pair = |i: usize| match (field[i], field[i + 1]) {
+        (a @ b'0'..=b'9', b @ b'0'..=b'9') => Ok((a - b'0') * 10 + (b - b'0')),
+        _ => Err(MrzError::InvalidDate(name)),
+    };
+    let (yy, month, day) = (pair(0)?, pair(2)?, pair(4)?);
+    let year = century(yy as u16).ok_or(MrzError::InvalidDate(name))?;
+    Date::new(year, month, day).map_err(|_| MrzError::InvalidDate(name))
+}
+
+fn strip_filler(field: &[u8]) -> String {
+    field.iter().filter(|&&c| c != b'<').map(|&c| c as char).collect()
+}
+
+// "SURNAME<<GIVEN<NAMES<<<" -> "GIVEN NAMES SURNAME"
+// A typed name in the form parse_td3_mrz returns from the MRZ, so a name entered by hand hashes
+// like the same name read from the chip. Letters are uppercased and transliterated per ICAO 9303
+// Part 3 (Ü -> UE, Å -> AA, Ø -> OE, ß -> SS, É -> E). Apostrophes are dropped, and other
+// punctuation separates words like the MRZ's '<' filler. Letters outside the Latin table, e.g.
+// Cyrillic or Greek, have no single transliteration and are dropped; pass the Latin name as
+// printed in the passport instead.
+pub fn transliterate_to_mrz(name: &str) -> String {
+    let mut mrz = String::with_capacity(name.len());
+    for c in name.chars().flat_map(char::to_uppercase) {
+        match c {
+            'A'..='Z' => mrz.push(c),
+            '\'' | '\u{2019}' => {}
+            _ => match transliterate_letter(c) {
+                Some(latin) => mrz.push_str(latin),
+                None if c.is_alphabetic() => {}
+                None => mrz.push(' '),
+            },
+        }
+    }
+    mrz.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn transliterate_letter(c: char) -> Option<&'static str> {
+    Some(match c {
+        'À' | 'Á' | 'Â' | 'Ã' | 'Ā' | 'Ă' | 'Ą' => "A",
+        'Ä' | 'Æ' => "AE",
+        'Å' => "AA",
+        'Ç' | 'Ć' | 'Ĉ' | 'Ċ' | 'Č' => "C",
+        'Ð' | 'Ď' | 'Đ' => "D",
+        'È' | 'É' | 'Ê' | 'Ë' | 'Ē' | 'Ĕ' | 'Ė' | 'Ę' | 'Ě' => "E",
+        'Ĝ' | 'Ğ' | 'Ġ' | 'Ģ' => "G",
+        'Ĥ' | 'Ħ' => "H",
+        'Ì' | 'Í' | 'Î' | 'Ï' | 'Ĩ' | 'Ī' | 'Ĭ' | 'Į' | 'İ' => "I",
+        'Ĳ' => "IJ",
+        'Ĵ' => "J",
+        'Ķ' => "K",
+        'Ĺ' | 'Ļ' | 'Ľ' | 'Ŀ' | 'Ł' => "L",
+        'Ñ' | 'Ń' | 'Ņ' | 'Ň' | 'Ŋ' => "N",
+        'Ò' | 'Ó' | 'Ô' | 'Õ' | 'Ō' | 'Ŏ' | 'Ő' => "O",
+        'Ö' | 'Ø' | 'Œ' => "OE",
+        'Ŕ' | 'Ŗ' | 'Ř' => "R",
+        'Ś' | 'Ŝ' | 'Ş' | 'Š' => "S",
+        'Ţ' | 'Ť' | 'Ŧ' => "T",
+        'Þ' => "TH",
+        'Ù' | 'Ú' | 'Û' | 'Ũ' | 'Ū' | 'Ŭ' | 'Ů' | 'Ű' | 'Ų' => "U",
+        'Ü' => "UE",
+        'Ŵ' => "W",
+        'Ý' | 'Ÿ' | 'Ŷ' => "Y",
+        'Ź' | 'Ż' | 'Ž' => "Z",
+        _ => return None,
+    })
+}
+
+fn mrz_name(field: &[u8]) -> String {
+    let field: String = field.iter().map(|&c| c as char).collect();
+    let mut parts = field.split("<<");
+    let surname = parts.next().unwrap_or_default().replace('<', " ").trim().to_string();
+    let given_names: Vec<String> = parts
+        .filter(|part| !part.is_empty())
+        .map(|part| part.replace('<', " ").trim().to_string())
+        .collect();
+    let given_names = given_names.join(" ").trim().to_string();
+    if given_names.is_empty() {
+        surname
+    } else {
+        format!("{} {}", given_names, surname)
+    }
+}
@@ -0,0 +1,60 @@
+// Evaluation time signed by a trusted time oracle. Without one the prover picks "now", and a
+// date a few years ahead passes any age check; with one the guest only accepts the time the
+// oracle signed, and commits a hash of the oracle's key so a verifier can check whose clock it was.
+//
+// The oracle signs SHA-256(ORACLE_DOMAIN || timestamp as 8 big-endian bytes) with ECDSA P-256,
+// DER-encoded like passport signatures.
+use p256::ecdsa::signature::hazmat::{PrehashSigner, PrehashVerifier};
+use p256::ecdsa::{Signature, SigningKey, VerifyingKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+// Keeps an oracle signature from being replayed as a signature over anything else.
+pub const ORACLE_DOMAIN: &[u8] = b"passport-verifier/time-oracle/v1";
+
+// The oracle's signature over current_timestamp.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct OracleSignature {
+    // SEC1-encoded P-256 public key
+    pub public_key: Vec<u8>,
+    // DER ECDSA signature over timestamp_digest(current_timestamp)
+    pub signature: Vec<u8>,
+}
+
+pub fn timestamp_digest(timestamp: u64) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update(ORACLE_DOMAIN);
+    hasher.update(timestamp.to_be_bytes());
+    hasher.finalize().into()
+}
+
+// False for a malformed key or signature rather than a panic, like verify_passport_signature.
+pub fn verify_timestamp(oracle: &OracleSignature, timestamp: u64) -> bool {
+    let Ok(public_key) = VerifyingKey::from_sec1_bytes(&oracle.public_key) else {
+        return false;
+    };
+    let Ok(signature) = Signature::from_der(&oracle.signature) else {
+        return false;
+    };
+    public_key.verify_prehash(&timestamp_digest(timestamp), &signature).is_ok()
+}
+
+// Committed as audience_binding.time_oracle. Hashes the uncompressed point, so the compressed and
+// uncompressed encodings of one key give the same hash; a malformed key hashes its raw bytes.
+pub fn oracle_key_hash(public_key: &[u8]) -> [u8; 32] {
+    match VerifyingKey::from_sec1_bytes(public_key) {
+        Ok(key) => Sha256::digest(key.to_encoded_point(false).as_bytes()).into(),
+        Err(_) => Sha256::digest(public_key).into(),
+    }
+}
+
+// Host helper for tests and local setups that act as their own oracle: signs timestamp with a
+// raw P-256 secret key. None when secret_key isn't a valid scalar.
+pub fn sign_timestamp(secret_key: &[u8; 32], timestamp: u64) -> Option<OracleSignature> {
+    let key = SigningKey::from_bytes(secret_key.into()).ok()?;
+    let signature: Signature = key.sign_prehash(&timestamp_digest(timestamp)).ok()?;
+    Some(OracleSignature {
+        public_key: key.verifying_key().to_encoded_point(false).as_bytes().to_vec(),
+        signature: signature.to_der().as_bytes().to_vec(),
+    })
+}
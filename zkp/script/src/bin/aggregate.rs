@@ -0,0 +1,134 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use passport_verifier_script::envelope::ProofEnvelope;
+use passport_verifier_script::public_values::{aggregate_values_to_json, decode_aggregate_values, format_date};
+use passport_verifier_script::utils::*;
+use sp1_sdk::{HashableKey, SP1Proof, SP1ProofWithPublicValues, SP1Stdin};
+use std::time::Instant;
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+const AGGREGATION_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-aggregation-program");
+
+/// Fold compressed passport proofs (evm --proof-system compressed) into a single proof that
+/// counts how many distinct identities passed the same age check
+#[derive(Parser)]
+struct Args {
+    /// Proof files written by evm with --proof-system compressed
+    #[arg(required = true)]
+    proofs: Vec<String>,
+
+    /// Minimum age every inner proof must have checked and disclosed
+    #[arg(long, value_parser = parse_min_age)]
+    min_age: u16,
+
+    /// Proof to generate for the aggregate; see evm --help for the tradeoffs
+    #[arg(long, value_enum, default_value_t = ProofSystem::Groth16)]
+    proof_system: ProofSystem,
+
+    /// Directory the aggregate proof file is written to
+    #[arg(long, env = "PROOF_DIR", default_value = "../proofs")]
+    output_dir: String,
+
+    /// Replace an aggregate proof file that already exists instead of failing
+    #[arg(long)]
+    overwrite: bool,
+
+    /// Write the aggregate proof file gzip-compressed, as .json.gz
+    #[arg(long)]
+    gzip: bool,
+}
+
+fn main() -> Result<()> {
+    assert_valid_elf(PASSPORT_ELF);
+    assert_valid_elf(AGGREGATION_ELF);
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    assert_prover_config();
+    sp1_sdk::utils::setup_logger();
+    print_banner();
+
+    print_step("Initializing SP1 Prover (Aggregation)...");
+    let client = prover_client();
+    let (_, passport_vk) = setup_cached(&client, PASSPORT_ELF);
+    let (pk, vk) = setup_cached(&client, AGGREGATION_ELF);
+    print_success("Prover initialized");
+
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&passport_vk.hash_u32());
+    stdin.write(&args.min_age);
+    let mut public_values = Vec::with_capacity(args.proofs.len());
+    let mut inner_proofs = Vec::with_capacity(args.proofs.len());
+    for path in &args.proofs {
+        let envelope = ProofEnvelope::load(path)?;
+        envelope.header().check(&passport_vk.bytes32()).with_context(|| format!("{} can't be aggregated", path))?;
+        if envelope.mode != "compressed" {
+            bail!("{} is a {} proof; only compressed proofs can be aggregated", path, envelope.mode);
+        }
+        let proof: SP1ProofWithPublicValues = bincode::deserialize(&envelope.proof_bytes()?)
+            .with_context(|| format!("{} does not hold a serialized SP1 proof", path))?;
+        let SP1Proof::Compressed(inner) = proof.proof else {
+            bail!("{} does not hold a compressed proof", path);
+        };
+        public_values.push(proof.public_values.to_vec());
+        inner_proofs.push(*inner);
+    }
+    stdin.write(&public_values);
+    // The guest verifies proofs in the order they were written, matching public_values.
+    for inner in inner_proofs {
+        stdin.write_proof(inner, passport_vk.vk.clone());
+    }
+    print_info("Inner proofs", &args.proofs.len().to_string());
+
+    let system = args.proof_system;
+    print_step(&format!("Generating {} aggregate proof...", system.name()));
+    let start = Instant::now();
+    let proof = with_spinner("Proving", || {
+        let builder = client.prove(&pk, &stdin);
+        match system {
+            ProofSystem::Core => builder.core().run(),
+            ProofSystem::Compressed => builder.compressed().run(),
+            ProofSystem::Groth16 => builder.groth16().run(),
+            ProofSystem::Plonk => builder.plonk().run(),
+        }
+    })
+    .map_err(|e| anyhow!("{} aggregate proof generation failed: {:#}", system.name(), e))?;
+    print_success(&format!("Proof generated in {:.2?}", start.elapsed()));
+
+    let public_values = proof.public_values.as_slice();
+    let mut proof_bytes = match system {
+        ProofSystem::Core | ProofSystem::Compressed => {
+            bincode::serialize(&proof).context("failed to serialize proof")?
+        }
+        ProofSystem::Groth16 | ProofSystem::Plonk => proof.bytes(),
+    };
+    // Same mock-mode placeholder as evm.
+    let mock = mock_prover() || proof_bytes.is_empty();
+    if proof_bytes.is_empty() {
+        proof_bytes = vec![0];
+    }
+    let output = decode_aggregate_values(public_values).context("failed to decode aggregate public values")?;
+    let mut envelope = ProofEnvelope::new(system.mode(), &proof_bytes, public_values, vk.bytes32(), evaluation_timestamp(None)?);
+    envelope.decoded_public_values = Some(aggregate_values_to_json(&output));
+    envelope.meta.insert("aggregate".to_string(), true.into());
+    envelope.meta.insert("mock".to_string(), mock.into());
+    let job_id = std::env::var("PROOF_JOB_ID").unwrap_or_else(|_| artifact_id(public_values));
+    let (proof_path, size) = save_artifact(
+        &args.output_dir,
+        &format!("passport_aggregate_{}.json", job_id),
+        &envelope,
+        args.overwrite,
+        args.gzip,
+    )?;
+    print_success(&format!("Proof saved to {}", proof_path));
+    print_info("Proof size", &format!("{} bytes", proof_bytes.len()));
+    print_info("File size", &size.describe());
+
+    print_info("Passport vkey", &format!("0x{}", hex::encode(output.passport_vkey)));
+    print_info("Min age", &output.min_age.to_string());
+    print_info("Proofs", &output.proof_count.to_string());
+    print_info("Over min age", &format!("{} distinct identities", output.over_min_age_count));
+    print_info("Identity set", &format!("0x{}", hex::encode(output.identity_set_commitment)));
+    print_info("Oldest evaluation", &format_date(output.oldest_timestamp.to()));
+    print_divider();
+    Ok(())
+}
@@ -0,0 +1,221 @@
+use anyhow::{anyhow, bail, Result};
+use clap::Parser;
+use passport_verifier_lib::Date;
+use passport_verifier_script::envelope::{ProofEnvelope, SavedSize};
+use passport_verifier_script::inputs::*;
+use passport_verifier_script::public_values::{decode_public_values, public_values_to_json};
+use passport_verifier_script::utils::*;
+use serde::Deserialize;
+use sp1_sdk::{EnvProver, HashableKey, SP1ProvingKey};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+/// Prove many passports with a single prover setup, writing one proof file per record
+#[derive(Parser)]
+struct Args {
+    /// JSON array of {id?, passport, requirements} records, a .jsonl file with one record per
+    /// line, or - for an array on stdin. Records are read as they are proved, so the file can be
+    /// any size
+    input: String,
+
+    /// Proofs to generate at once. Each Groth16 proof needs roughly 16 GB of RAM, so keep this
+    /// within the machine's memory
+    #[arg(long, default_value_t = 1)]
+    jobs: usize,
+
+    /// Directory the proof files are written to
+    #[arg(long, env = "PROOF_DIR", default_value = "../proofs")]
+    output_dir: String,
+
+    /// Replace proof files that already exist instead of failing those records
+    #[arg(long)]
+    overwrite: bool,
+
+    /// Write the proof files gzip-compressed, as .json.gz
+    #[arg(long)]
+    gzip: bool,
+
+    /// Evaluate every proof as of this date (YYYY-MM-DD, midnight UTC) instead of the system clock
+    #[arg(long, value_parser = parse_iso_date)]
+    current_date: Option<Date>,
+
+    /// Refuse to prove unless the proof system offers at least this many bits of security. Every
+    /// SP1 proof system offers ~100, so this refuses all of them above 100 and none at or below
+    #[arg(long, env = "MIN_SECURITY_BITS", default_value_t = 0)]
+    min_security_bits: u32,
+
+    /// Retries after a network failure (timeout, rate limit) before giving up; other errors fail
+    /// at once
+    #[arg(long, env = "PROVER_MAX_RETRIES", default_value_t = 3)]
+    max_retries: u32,
+
+    /// Seconds to wait before the first retry; the wait doubles after each one
+    #[arg(long, default_value_t = 5)]
+    retry_delay: u64,
+
+    /// Also execute each record's guest to count its RISC-V cycles; logged per record and saved
+    /// as meta.cycles
+    #[arg(long)]
+    verbose: bool,
+}
+
+#[derive(Deserialize)]
+struct BatchRecord {
+    // Names the proof file, which is otherwise named by artifact_id; logs use the record's
+    // position in the input when absent
+    #[serde(default)]
+    id: Option<String>,
+    passport: PassportInput,
+    requirements: VerificationRequirements,
+}
+
+fn main() -> Result<()> {
+    assert_valid_elf(PASSPORT_ELF);
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    assert_prover_config();
+    sp1_sdk::utils::setup_logger();
+
+    print_banner();
+    check_security_level(ProofSystem::Groth16, args.min_security_bits).map_err(|e| anyhow!(e))?;
+    if args.jobs == 0 {
+        bail!("--jobs must be at least 1");
+    }
+
+    // Records are parsed as workers take them, so only the ones being proved are in memory.
+    let records = Mutex::new(JsonRecords::<BatchRecord>::open(&args.input, "batch input")?.enumerate());
+
+    print_step("Initializing SP1 Prover (EVM Mode)...");
+    let client = prover_client();
+    let (pk, vk) = setup_cached(&client, PASSPORT_ELF);
+    let vkey = vk.bytes32();
+    print_success("Prover initialized");
+
+    // Workers take the next record off the shared stream, so a slow proof doesn't hold up the others.
+    let summary = Mutex::new(BatchSummary::default());
+    let batch_start = Instant::now();
+    std::thread::scope(|scope| {
+        for _ in 0..args.jobs {
+            scope.spawn(|| loop {
+                let Some((index, record)) = records.lock().unwrap().next() else {
+                    break;
+                };
+                let record = match record {
+                    Ok(record) => record,
+                    Err(e) => {
+                        print_error(&format!("[{}] {:#}", index, e));
+                        summary.lock().unwrap().record(index.to_string(), Duration::ZERO, Err(e));
+                        continue;
+                    }
+                };
+                let id = record.id.clone().unwrap_or_else(|| index.to_string());
+                let start = Instant::now();
+                let result = evaluation_timestamp(args.current_date.as_ref())
+                    .and_then(|now| prove_record(&client, &pk, &vkey, &record, &id, now, &args));
+                let elapsed = start.elapsed();
+                match &result {
+                    Ok((path, size)) => print_success(&format!(
+                        "[{}] proved in {:.2?}, saved to {} ({})",
+                        id,
+                        elapsed,
+                        path,
+                        size.describe()
+                    )),
+                    Err(e) => print_error(&format!("[{}] failed after {:.2?}: {:#}", id, elapsed, e)),
+                }
+                summary.lock().unwrap().record(id, elapsed, result.map(|(_, size)| size));
+            });
+        }
+    });
+
+    let summary = summary.into_inner().unwrap();
+    print_divider();
+    print_info("Proved", &format!("{} of {}", summary.total - summary.failed.len(), summary.total));
+    print_info("Wall time", &format!("{:.2?}", batch_start.elapsed()));
+    print_info("Proving time", &format!("{:.2?}", summary.proving_time));
+    if summary.stored.file > 0 {
+        print_info("Stored", &summary.stored.describe());
+    }
+    if !summary.failed.is_empty() {
+        print_info("Failed", &summary.failed.join(", "));
+        bail!("{} of {} proofs failed", summary.failed.len(), summary.total);
+    }
+    Ok(())
+}
+
+// Running totals, so a batch of millions keeps only the ids of failed records rather than every
+// record's result.
+#[derive(Default)]
+struct BatchSummary {
+    total: usize,
+    failed: Vec<String>,
+    proving_time: Duration,
+    stored: SavedSize,
+}
+
+impl BatchSummary {
+    fn record(&mut self, id: String, elapsed: Duration, result: Result<SavedSize>) {
+        self.total += 1;
+        self.proving_time += elapsed;
+        match result {
+            Ok(size) => {
+                self.stored.json += size.json;
+                self.stored.file += size.file;
+            }
+            Err(_) => self.failed.push(id),
+        }
+    }
+}
+
+fn prove_record(
+    client: &EnvProver,
+    pk: &SP1ProvingKey,
+    vkey: &str,
+    record: &BatchRecord,
+    id: &str,
+    now: u64,
+    args: &Args,
+) -> Result<(String, SavedSize)> {
+    if let Some(file_id) = &record.id {
+        check_file_id(file_id).map_err(|e| anyhow!(e))?;
+    }
+    let options = GuestOptions::default();
+    check_options(&record.requirements, &options)?;
+    if record.requirements.required_age == 0 {
+        print_info("Warning", &format!("[{}] required_age is 0, so the age check proves nothing", id));
+    }
+    let witness = to_passport_witness(record.passport.clone())?;
+    let stdin = guest_stdin(&witness, &record.requirements, &options, now)?;
+    let cycles = if args.verbose {
+        let (_, execution) = client.execute(PASSPORT_ELF, &stdin).run()?;
+        print_info(&format!("[{}] Cycles", id), &execution.total_instruction_count().to_string());
+        Some(execution.total_instruction_count())
+    } else {
+        None
+    };
+
+    let retry_delay = Duration::from_secs(args.retry_delay);
+    let proof = retry_proving(args.max_retries, retry_delay, || client.prove(pk, &stdin).groth16().run()).map_err(|e| {
+        let message = format!("{:#}", e);
+        anyhow!("{} ({})", message, classify_proving_error(&message).hint())
+    })?;
+
+    // Mock mode produces no Groth16 bytes; see evm for the placeholder.
+    let mut proof_bytes = proof.bytes();
+    let mock = mock_prover() || proof_bytes.is_empty();
+    if proof_bytes.is_empty() {
+        proof_bytes = vec![0];
+    }
+    let mut envelope = ProofEnvelope::groth16(&proof_bytes, proof.public_values.as_slice(), vkey.to_string(), now);
+    envelope.decoded_public_values =
+        decode_public_values(proof.public_values.as_slice()).ok().map(|output| public_values_to_json(&output));
+    envelope.meta.insert("jobId".to_string(), id.into());
+    envelope.meta.insert("mock".to_string(), mock.into());
+    if let Some(cycles) = cycles {
+        envelope.meta.insert("cycles".to_string(), cycles.into());
+    }
+    let file_id = record.id.clone().unwrap_or_else(|| artifact_id(proof.public_values.as_slice()));
+    save_artifact(&args.output_dir, &format!("passport_proof_evm_{}.json", file_id), &envelope, args.overwrite, args.gzip)
+}
@@ -0,0 +1,190 @@
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use passport_verifier_lib::{Date, Disclosures, PassportAttributesBuilder};
+use passport_verifier_script::inputs::{check_options, guest_stdin, GuestOptions, PassportWitness, VerificationRequirements};
+use passport_verifier_script::utils::*;
+use serde_json::json;
+use std::time::{Duration, Instant};
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+// What a benchmarked proof discloses. Every mode runs the same guest; they differ in the
+// requirements checked and the predicates revealed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Mode {
+    // Minimum age only
+    Age,
+    // Nationality only
+    Nationality,
+    // Every predicate, bound to a wallet
+    Full,
+}
+
+impl Mode {
+    fn name(self) -> &'static str {
+        match self {
+            Mode::Age => "age",
+            Mode::Nationality => "nationality",
+            Mode::Full => "full",
+        }
+    }
+
+    fn requirements(self) -> Result<VerificationRequirements> {
+        let required_nationality = if self == Mode::Age { "" } else { "MYS" };
+        serde_json::from_value(json!({
+            "walletAddress": format!("0x{}", "5a".repeat(20)),
+            "verifierAddress": format!("0x{}", "11".repeat(20)),
+            "requiredAge": if self == Mode::Nationality { 0 } else { 18 },
+            "requiredNationality": required_nationality,
+            "requiredSex": "",
+        }))
+        .context("invalid benchmark requirements")
+    }
+
+    fn disclosures(self) -> Disclosures {
+        Disclosures {
+            age: self != Mode::Nationality,
+            nationality: self != Mode::Age,
+            expiry: self == Mode::Full,
+            wallet: self == Mode::Full,
+        }
+    }
+}
+
+/// Time proving and verification for each disclosure mode and proof system on a fixed passport,
+/// to size proving infrastructure and choose a proof system
+#[derive(Parser)]
+struct Args {
+    /// Modes to benchmark
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [Mode::Age, Mode::Nationality, Mode::Full])]
+    modes: Vec<Mode>,
+
+    /// Proof systems to benchmark; see evm --help for the tradeoffs
+    #[arg(long, value_enum, value_delimiter = ',', default_values_t = [ProofSystem::Core, ProofSystem::Groth16, ProofSystem::Plonk])]
+    proof_systems: Vec<ProofSystem>,
+
+    /// Proofs generated per mode and proof system
+    #[arg(long, default_value_t = 3, value_parser = clap::value_parser!(u32).range(1..))]
+    iterations: u32,
+
+    /// Also write one row per iteration to this CSV file
+    #[arg(long)]
+    csv: Option<String>,
+}
+
+struct Sample {
+    mode: Mode,
+    system: ProofSystem,
+    iteration: u32,
+    prove: Duration,
+    verify: Duration,
+}
+
+fn mean(durations: impl Iterator<Item = Duration>) -> Duration {
+    let (total, count) = durations.fold((Duration::ZERO, 0u32), |(total, count), d| (total + d, count + 1));
+    total / count.max(1)
+}
+
+fn main() -> Result<()> {
+    assert_valid_elf(PASSPORT_ELF);
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    assert_prover_config();
+    sp1_sdk::utils::setup_logger();
+    print_banner();
+
+    // Setup is timed once: with the key cache warm this is the load time every binary pays, and
+    // repeating it per proof system would only measure the cache.
+    print_step("Initializing SP1 Prover (Benchmark)...");
+    let client = prover_client();
+    let start = Instant::now();
+    let (pk, vk) = setup_cached(&client, PASSPORT_ELF);
+    let setup = start.elapsed();
+    print_success(&format!("Setup took {:.2?}", setup));
+
+    let passport = PassportAttributesBuilder::new()
+        .document_number("A12345678")
+        .birth_date(Date { year: 1994, month: 6, day: 15 })
+        .expiry_date(Date { year: 2032, month: 1, day: 1 })
+        .nationality("MYS")
+        .name("AHMAD BIN ABDULLAH")
+        .sex("M")
+        .build()
+        .unwrap();
+    let witness = PassportWitness::from(passport);
+    let now = evaluation_timestamp(Some(&Date { year: 2025, month: 11, day: 1 }))?;
+
+    let mut samples = Vec::new();
+    for &mode in &args.modes {
+        let reqs = mode.requirements()?;
+        let options = GuestOptions {
+            disclosures: mode.disclosures(),
+            ..GuestOptions::default()
+        };
+        check_options(&reqs, &options)?;
+        let stdin = guest_stdin(&witness, &reqs, &options, now)?;
+        for &system in &args.proof_systems {
+            for iteration in 1..=args.iterations {
+                print_step(&format!("{} / {}: iteration {} of {}", mode.name(), system.name(), iteration, args.iterations));
+                let start = Instant::now();
+                let proof = with_spinner("Proving", || {
+                    let builder = client.prove(&pk, &stdin);
+                    match system {
+                        ProofSystem::Core => builder.core().run(),
+                        ProofSystem::Compressed => builder.compressed().run(),
+                        ProofSystem::Groth16 => builder.groth16().run(),
+                        ProofSystem::Plonk => builder.plonk().run(),
+                    }
+                })
+                .map_err(|e| anyhow!("{} proof generation failed: {:#}", system.name(), e))?;
+                let prove = start.elapsed();
+
+                let start = Instant::now();
+                client.verify(&proof, &vk).map_err(|e| anyhow!("{} proof did not verify: {}", system.name(), e))?;
+                let verify = start.elapsed();
+                samples.push(Sample { mode, system, iteration, prove, verify });
+            }
+        }
+    }
+
+    print_divider();
+    println!("  {:<12} {:<11} {:>12} {:>12} {:>12} {:>12}", "Mode", "System", "Prove mean", "Prove min", "Prove max", "Verify mean");
+    for &mode in &args.modes {
+        for &system in &args.proof_systems {
+            let runs = || samples.iter().filter(move |s| s.mode == mode && s.system == system);
+            let proves = || runs().map(|s| s.prove);
+            println!(
+                "  {:<12} {:<11} {:>12.2?} {:>12.2?} {:>12.2?} {:>12.2?}",
+                mode.name(),
+                system.name(),
+                mean(proves()),
+                proves().min().unwrap_or_default(),
+                proves().max().unwrap_or_default(),
+                mean(runs().map(|s| s.verify)),
+            );
+        }
+    }
+    print_divider();
+    print_info("Setup", &format!("{:.2?} (once, through the key cache)", setup));
+    if mock_prover() {
+        print_info("Warning", "the mock prover is on, so these times don't reflect real proving");
+    }
+
+    if let Some(path) = &args.csv {
+        let mut csv = String::from("mode,proof_system,iteration,setup_ms,prove_ms,verify_ms\n");
+        for s in &samples {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{}\n",
+                s.mode.name(),
+                s.system.mode(),
+                s.iteration,
+                setup.as_millis(),
+                s.prove.as_millis(),
+                s.verify.as_millis(),
+            ));
+        }
+        std::fs::write(path, csv).with_context(|| format!("failed to write {}", path))?;
+        print_success(&format!("CSV written to {}", path));
+    }
+    Ok(())
+}
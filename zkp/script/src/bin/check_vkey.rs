@@ -0,0 +1,51 @@
+use clap::Parser;
+use passport_verifier_script::utils::*;
+
+/// ELF binary for the passport verification program
+const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+/// Fail if the guest's verification key no longer matches the locked value
+#[derive(Parser)]
+struct Args {
+    /// File holding the expected vkey hash
+    #[arg(long, default_value = "vkey.lock")]
+    lock: String,
+
+    /// Write the current vkey hash to the lock file instead of comparing
+    #[arg(long)]
+    update: bool,
+}
+
+fn main() {
+    assert_valid_elf(PASSPORT_ELF);
+    let args = Args::parse();
+    sp1_sdk::utils::setup_logger();
+
+    print_step("Computing verification key...");
+    let client = prover_client();
+    let current = vkey_hash(&client, PASSPORT_ELF);
+    print_info("Current vkey", &current);
+
+    if args.update {
+        std::fs::write(&args.lock, format!("{}\n", current))
+            .unwrap_or_else(|_| panic!("Failed to write {}", args.lock));
+        print_success(&format!("Updated {}", args.lock));
+        return;
+    }
+
+    let locked = match std::fs::read_to_string(&args.lock) {
+        Ok(contents) => contents,
+        Err(_) => {
+            print_error(&format!("No lock file at {}; run with --update to create it", args.lock));
+            std::process::exit(1);
+        }
+    };
+    print_info("Locked vkey", locked.trim());
+
+    if !vkey_matches_lock(&locked, &current) {
+        print_error("Verification key drifted from the locked value; deployed AppVerifiers will reject new proofs");
+        print_error("If the guest change is intentional, redeploy the verifiers and run with --update");
+        std::process::exit(1);
+    }
+    print_success("Verification key matches the lock file");
+}
@@ -0,0 +1,62 @@
+use passport_verifier_lib::{identity_hash_preimage, Date, PassportAttributesBuilder};
+use passport_verifier_script::utils::*;
+
+// Yellow-paper / EIP-2929 figures for a warm call to the SHA-256 precompile and the KECCAK256 opcode.
+const SHA256_BASE_GAS: u64 = 60;
+const SHA256_WORD_GAS: u64 = 12;
+const PRECOMPILE_CALL_GAS: u64 = 100;
+const KECCAK_BASE_GAS: u64 = 30;
+const KECCAK_WORD_GAS: u64 = 6;
+
+// poseidon-solidity's PoseidonT3 (two BN254 inputs per permutation), as benchmarked in its README.
+const POSEIDON_T3_GAS: u64 = 21_124;
+// Field elements hold 31 bytes so every chunk stays below the BN254 modulus.
+const POSEIDON_CHUNK_BYTES: usize = 31;
+
+fn words(len: usize) -> u64 {
+    len.div_ceil(32) as u64
+}
+
+fn sha256_gas(len: usize) -> u64 {
+    PRECOMPILE_CALL_GAS + SHA256_BASE_GAS + SHA256_WORD_GAS * words(len)
+}
+
+fn keccak_gas(len: usize) -> u64 {
+    KECCAK_BASE_GAS + KECCAK_WORD_GAS * words(len)
+}
+
+// PoseidonT3 absorbs two field elements per permutation.
+fn poseidon_gas(len: usize) -> u64 {
+    let elements = len.div_ceil(POSEIDON_CHUNK_BYTES).max(1);
+    POSEIDON_T3_GAS * elements.div_ceil(2) as u64
+}
+
+fn main() {
+    print_banner();
+    print_step("Estimating on-chain cost of recomputing the identity commitment...");
+
+    let passport = PassportAttributesBuilder::new()
+        .document_number("A12345678")
+        .birth_date(Date { year: 1994, month: 6, day: 15 })
+        .expiry_date(Date { year: 2032, month: 1, day: 1 })
+        .nationality("MYS")
+        .name("AHMAD BIN ABDULLAH")
+        .sex("M")
+        .build()
+        .unwrap();
+    let preimage = identity_hash_preimage(&passport);
+    print_info("Preimage bytes", &preimage.len().to_string());
+    print_divider();
+
+    println!("  {:<12} {:>10}", "Scheme", "Gas");
+    for (scheme, gas) in [
+        ("SHA-256", sha256_gas(preimage.len())),
+        ("Keccak-256", keccak_gas(preimage.len())),
+        ("Poseidon", poseidon_gas(preimage.len())),
+    ] {
+        println!("  {:<12} {:>10}", scheme, gas);
+    }
+    print_divider();
+    println!("Estimates cover the hash only; calldata and memory expansion are the same for every scheme.");
+    println!("Keccak-256 is available as \"commitmentHash\": \"keccak256\" in the requirements file.");
+}
@@ -0,0 +1,87 @@
+use anyhow::Result;
+use clap::Parser;
+use passport_verifier_lib::{
+    derive_audience_commitment, derive_identity_hash_poseidon, derive_identity_hash_with, derive_nullifier,
+    derive_wallet_binding, full_record_commitment, CommitmentFields, CommitmentHash, WalletEntry,
+};
+use passport_verifier_script::inputs::*;
+use passport_verifier_script::utils::*;
+use serde_json::{Map, Value};
+
+/// Print a passport's commitments without proving: the identity hash under each hash function,
+/// the audit hash, and the nullifier, audience and wallet binding when their inputs are given.
+/// They are the values a proof of this passport would commit
+#[derive(Parser)]
+struct Args {
+    /// Passport JSON in the same shape evm reads, or - for stdin; not needed with the passport
+    /// field flags
+    passport: Option<String>,
+
+    /// Passport fields in the identity hash, as given to evm; defaults to dob,nationality,names
+    #[arg(long, value_parser = parse_identity_fields)]
+    identity_fields: Option<CommitmentFields>,
+
+    /// 32-byte hex externalNullifier (nullifier scope) to derive the nullifier for
+    #[arg(long)]
+    external_nullifier: Option<String>,
+
+    /// 32-byte hex verifier id to derive the audience commitment for
+    #[arg(long, env = "VERIFIER_ID")]
+    verifier_id: Option<String>,
+
+    /// Wallet to include in the wallet binding, as CHAIN_ID:HEX_ADDRESS; repeat for several, as
+    /// with evm --link-wallet
+    #[arg(long = "link-wallet", value_parser = parse_wallet_entry)]
+    linked_wallets: Vec<WalletEntry>,
+
+    /// Print the commitments as a JSON object instead of text
+    #[arg(long)]
+    json: bool,
+
+    #[command(flatten)]
+    fields: PassportArgs,
+}
+
+fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    let passport = args.fields.read_passport(args.passport.as_deref())?;
+
+    // Named like the output fields that carry them.
+    let mut commitments = Map::new();
+    let mut add = |name: &str, value: [u8; 32]| {
+        commitments.insert(name.to_string(), Value::String(format!("0x{}", hex::encode(value))));
+    };
+    let fields = args.identity_fields.unwrap_or_default();
+    for (name, hash) in [("identity_hash", CommitmentHash::Sha256), ("identity_hash_keccak256", CommitmentHash::Keccak256)] {
+        if let Some(identity_hash) = derive_identity_hash_with(&passport, hash, fields) {
+            add(name, identity_hash);
+        }
+    }
+    // Poseidon hashes a fixed set of fields.
+    if fields == CommitmentFields::default() {
+        add("identity_hash_poseidon", derive_identity_hash_poseidon(&passport));
+    }
+    add("full_record_commitment", full_record_commitment(&passport));
+    if let Some(scope) = &args.external_nullifier {
+        add("nullifier", derive_nullifier(&passport, &decode_hex_array(scope, "external nullifier")?));
+    }
+    if let Some(verifier_id) = &args.verifier_id {
+        add("audience_commitment", derive_audience_commitment(&passport, &decode_hex_array(verifier_id, "verifier id")?));
+    }
+    if !args.linked_wallets.is_empty() {
+        add("binding_commitment", derive_wallet_binding(&passport, &args.linked_wallets));
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&commitments)?);
+        return Ok(());
+    }
+    print_banner();
+    print_step("Commitments");
+    for (name, value) in &commitments {
+        print_info(name, value.as_str().unwrap_or_default());
+    }
+    print_divider();
+    Ok(())
+}
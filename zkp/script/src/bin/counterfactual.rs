@@ -0,0 +1,46 @@
+use anyhow::{Context, Result};
+use clap::Parser;
+use passport_verifier_lib::{counterfactual_salt, derive_counterfactual_address, to_eip55};
+use passport_verifier_script::inputs::decode_hex_array;
+use passport_verifier_script::utils::*;
+
+/// Derive the address a wallet factory would deploy an identity's smart wallet at with CREATE2,
+/// before it is deployed. The CREATE2 salt is keccak256(identity hash ++ salt)
+#[derive(Parser)]
+struct Args {
+    /// 32-byte hex identity hash, as a proof commits it (decode prints it)
+    identity_hash: String,
+
+    /// Address of the wallet factory that deploys with CREATE2
+    #[arg(long, env = "WALLET_FACTORY")]
+    factory: String,
+
+    /// keccak256 of the wallet's creation code with its constructor arguments, 32-byte hex
+    #[arg(long, env = "WALLET_INIT_CODE_HASH")]
+    init_code_hash: String,
+
+    /// 32-byte hex salt that tells several wallets of one identity apart; zero by default
+    #[arg(long)]
+    salt: Option<String>,
+}
+
+fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    print_banner();
+
+    let identity_hash: [u8; 32] = decode_hex_array(&args.identity_hash, "identity hash")?;
+    let factory = parse_wallet_address(&args.factory).context("invalid factory address")?;
+    let init_code_hash: [u8; 32] = decode_hex_array(&args.init_code_hash, "init code hash")?;
+    let salt: [u8; 32] = match &args.salt {
+        Some(salt) => decode_hex_array(salt, "salt")?,
+        None => [0u8; 32],
+    };
+
+    let address = derive_counterfactual_address(&identity_hash, &factory, &salt, &init_code_hash);
+    print_divider();
+    print_info("Factory", &to_eip55(&factory));
+    print_info("CREATE2 salt", &format!("0x{}", hex::encode(counterfactual_salt(&identity_hash, &salt))));
+    print_success(&format!("Wallet address: {}", to_eip55(&address)));
+    Ok(())
+}
@@ -0,0 +1,79 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use passport_verifier_lib::is_timestamp_plausible;
+use passport_verifier_script::envelope::ProofEnvelope;
+use passport_verifier_script::public_values::*;
+use passport_verifier_script::utils::*;
+
+/// Decode the public values of a saved proof, passport or aggregate, or of verifyClaim calldata
+#[derive(Parser)]
+struct Args {
+    /// Proof file written by the evm binary
+    #[arg(required_unless_present = "calldata")]
+    proof: Option<String>,
+
+    /// Hex calldata of an AppVerifier.verifyClaim transaction, instead of a proof file
+    #[arg(long, conflicts_with = "proof")]
+    calldata: Option<String>,
+
+    /// Print the decoded fields as a JSON object instead of text
+    #[arg(long, conflicts_with = "report")]
+    json: bool,
+
+    /// Print a plain-language summary instead of the raw fields
+    #[arg(long)]
+    report: bool,
+
+    /// Reject proofs whose committed timestamp is more than this many seconds in the future
+    #[arg(long, default_value_t = 300)]
+    max_clock_skew: u64,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+
+    let output = match (&args.proof, &args.calldata) {
+        (_, Some(calldata)) => {
+            let calldata = hex::decode(calldata.trim().trim_start_matches("0x")).context("calldata is not valid hex")?;
+            let output = decode_calldata(&calldata).context("failed to decode verifyClaim calldata")?.0;
+            PublicOutputs::Passport(Box::new(output))
+        }
+        // A proof file says which program made it, so aggregate proofs decode as well.
+        (Some(proof), None) => ProofEnvelope::load(proof)?.public_outputs()?,
+        (None, None) => unreachable!("clap requires a proof file or --calldata"),
+    };
+    let fields = output.to_json();
+
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("system clock is before 1970")?
+        .as_secs();
+    let timestamp = output.timestamp();
+    if !is_timestamp_plausible(timestamp, now, args.max_clock_skew) {
+        bail!(
+            "proof timestamp {} ({}) is in the future; the prover's clock is wrong or the proof is forged",
+            timestamp,
+            format_date(timestamp)
+        );
+    }
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&fields)?);
+        return Ok(());
+    }
+    if args.report {
+        println!("{}", output);
+        return Ok(());
+    }
+
+    print_banner();
+    print_step("Decoded public values");
+    for (key, value) in fields.as_object().unwrap() {
+        match value {
+            serde_json::Value::String(s) => print_info(key, s),
+            other => print_info(key, &other.to_string()),
+        }
+    }
+    print_divider();
+    Ok(())
+}
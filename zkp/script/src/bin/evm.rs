@@ -1,128 +1,560 @@
-use passport_verifier_lib::{Date, PassportAttributes};
-use sp1_sdk::{ProverClient, SP1Stdin, HashableKey};
-use std::time::Instant;
+use passport_verifier_lib::{
+    timestamp_to_date, to_eip55, verify_passport_signature_detailed, CommitmentFields, Date, Disclosures,
+    VerificationPolicy, WalletEntry,
+};
+use passport_verifier_lib::dg::dg1_mrz_lines;
+use passport_verifier_lib::oracle::oracle_key_hash;
+use sp1_sdk::{EnvProver, HashableKey, SP1Stdin};
+use std::time::{Duration, Instant};
+use passport_verifier_script::envelope::ProofEnvelope;
+use passport_verifier_script::inputs::*;
+use passport_verifier_script::prover::unmet_requirements;
+use passport_verifier_script::public_values::*;
+use passport_verifier_script::qr::read_passport_qr;
 use passport_verifier_script::utils::*;
-use serde::Deserialize;
+use clap::Parser;
+use anyhow::{anyhow, bail, Context, Result};
 
 const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct PassportInput {
-    document_number: String,
-    birth_year: u16,
-    birth_month: u8,
-    birth_day: u8,
-    expiry_year: u16,
-    expiry_month: u8,
-    expiry_day: u8,
-    nationality: String,
-    name: String,
-    sex: String,
-}
 
-#[derive(Deserialize)]
-#[serde(rename_all = "camelCase")]
-struct VerificationRequirements {
-    wallet_address: String,
-    verifier_address: String,
-    required_age: u16,
-    required_nationality: String,
-    required_sex: String,
+#[derive(Parser)]
+struct Args {
+    /// Proof to generate. groth16 has the cheapest on-chain verification (~270k gas) but relies on
+    /// SP1's circuit-specific trusted setup; plonk costs ~300k gas and uses a universal setup;
+    /// core skips the wrapper entirely and can only be verified off-chain; compressed is also
+    /// off-chain only, but can be aggregated by the aggregate binary
+    #[arg(long, value_enum, default_value_t = ProofSystem::Groth16)]
+    proof_system: ProofSystem,
+
+    /// Run the guest and print its public values and cycle count without generating a proof,
+    /// to check inputs in seconds
+    #[arg(long)]
+    execute_only: bool,
+
+    /// Check the requirements on the host first and refuse to prove, or execute, when any of
+    /// them fails, instead of committing false for it
+    #[arg(long)]
+    strict: bool,
+
+    /// Also execute the guest to count the RISC-V cycles the proof consumed, which tracks prover
+    /// cost; printed after proving and saved as meta.cycles
+    #[arg(long)]
+    verbose: bool,
+
+    /// Print one JSON object (proof_path, vkey_hash, public_outputs, duration_ms, success) on
+    /// stdout instead of the formatted output, which moves to stderr
+    #[arg(long)]
+    json: bool,
+
+    /// Refuse to prove unless the proof system offers at least this many bits of security. Every
+    /// SP1 proof system offers ~100, so this refuses all of them above 100 and none at or below
+    #[arg(long, env = "MIN_SECURITY_BITS", default_value_t = 0)]
+    min_security_bits: u32,
+
+    /// Retries after a network failure (timeout, rate limit) before giving up; other errors fail
+    /// at once
+    #[arg(long, env = "PROVER_MAX_RETRIES", default_value_t = 3)]
+    max_retries: u32,
+
+    /// Seconds to wait before the first retry; the wait doubles after each one
+    #[arg(long, default_value_t = 5)]
+    retry_delay: u64,
+
+    /// Upper age bound to prove, overriding maxAge from the requirements file
+    #[arg(long)]
+    max_age: Option<u16>,
+
+    /// 32-byte hex id of the verifier the proof is meant for; it is committed as the audience
+    #[arg(long, env = "VERIFIER_ID")]
+    verifier_id: Option<String>,
+
+    /// Passport fields in the identity hash (doc, dob, nationality, names, expiry); defaults to dob,nationality,names
+    #[arg(long, value_parser = parse_identity_fields)]
+    identity_fields: Option<CommitmentFields>,
+
+    /// Also commit full_record_commitment, a hash of every passport field including the names
+    /// and expiry, so an auditor holding the record can later match it to the proof. Anyone who
+    /// can guess the whole record can confirm it too, so only use it where an audit trail is required
+    #[arg(long)]
+    audit: bool,
+
+    /// Predicates to reveal (age, nationality, expiry, wallet, or none); withheld ones are committed as zero
+    #[arg(long, value_parser = parse_disclosures)]
+    disclose: Option<Disclosures>,
+
+    /// Prove the passport has expired instead of that it is still valid, for renewal services
+    #[arg(long)]
+    expired: bool,
+
+    /// Nationalities that all count as a match (e.g. FRA,D,ITA, or alpha-2 codes or country names);
+    /// only a hash of the list is committed
+    #[arg(long, value_delimiter = ',', value_parser = parse_nationality)]
+    target_nationalities: Vec<String>,
+
+    /// Commit which of --target-nationalities matched as target_nationality, e.g. for routing by
+    /// tax treaty. Off by default, so the verifier learns only that one of them did
+    #[arg(long, requires = "target_nationalities")]
+    reveal_matched_nationality: bool,
+
+    /// Nationalities to reject (e.g. sanctioned states); commits is_not_blocked and a hash of the list
+    #[arg(long, value_delimiter = ',', value_parser = parse_nationality)]
+    blocked_nationalities: Vec<String>,
+
+    /// Commit requiredNationality only as a hash salted with the requirements'
+    /// targetNationalitySalt, so observers learn whether it matched but not which country it was
+    #[arg(long, conflicts_with = "target_nationalities")]
+    hide_target_nationality: bool,
+
+    /// Check a stateless or refugee holder (nationality XXA, XXB or XXC) against the nationality
+    /// requirements as a national of the state that issued the document; commits
+    /// stateless_by_issuer
+    #[arg(long)]
+    stateless_by_issuer: bool,
+
+    /// Evaluate the proof as of this date (YYYY-MM-DD, midnight UTC) instead of the system clock.
+    /// This is the committed timestamp verifiers check for freshness, and the date expiry is
+    /// checked against
+    #[arg(long, value_parser = parse_iso_date)]
+    current_date: Option<Date>,
+
+    /// Evaluate the proof at the time a trusted time oracle signed, from a JSON file with
+    /// timestamp, publicKey and signature. The guest checks the signature and commits the
+    /// oracle's key hash, so the prover can't pick a favourable date
+    #[arg(long, env = "TIME_ORACLE_FILE", conflicts_with = "current_date")]
+    time_oracle: Option<String>,
+
+    /// Check the holder's age as of this date (YYYY-MM-DD) instead of --current-date, e.g. the
+    /// day of a past purchase. Committed next to the minimum age; freshness is unaffected
+    #[arg(long, value_parser = parse_iso_date)]
+    reference_date: Option<Date>,
+
+    /// Wallet the identity is bound to now: prove a rebind of the identity to the requirements'
+    /// wallet, for AppVerifier.rebindWallet. Needs a chip signature that chains to cscaRoots
+    #[arg(long)]
+    rebind_from: Option<String>,
+
+    /// Another wallet to tie to the identity, as CHAIN_ID:HEX_ADDRESS (e.g. 501:0x... for Solana);
+    /// repeat for several. All of them are committed with one wallet_binding_commitment
+    #[arg(long = "link-wallet", value_parser = parse_wallet_entry)]
+    linked_wallets: Vec<WalletEntry>,
+
+    /// Prove even though walletAddress is the zero address. The guest never binds it, so the
+    /// proof commits the wallet as undisclosed
+    #[arg(long = "allow-zero")]
+    allow_zero_wallet: bool,
+
+    /// Commit an expiry for the proof this many seconds after its timestamp, so a verifier can
+    /// refuse it once the window has passed
+    #[arg(long, env = "PROOF_VALID_FOR_SECONDS")]
+    valid_for_seconds: Option<u64>,
+
+    /// JSON array of allowed document numbers: prove the passport is on the list, committing only
+    /// the list's Merkle root (document_membership)
+    #[arg(long)]
+    allowlist: Option<String>,
+
+    /// Raw TD3 MRZ (both lines), used instead of the passport fields and stdin JSON
+    #[arg(long, num_args = 2, value_names = ["LINE1", "LINE2"])]
+    mrz: Option<Vec<String>>,
+
+    /// DG1 data group as read from the chip over NFC (binary file), used like --mrz
+    #[arg(long, conflicts_with = "mrz")]
+    dg1: Option<String>,
+
+    /// QR code image (PNG) holding the passport JSON, as passport_qr --emit-qr writes it; read
+    /// instead of --passport, with the passport field flags still applied on top
+    #[arg(long, conflicts_with_all = ["mrz", "dg1"])]
+    qr: Option<String>,
+
+    /// Print the proof file's JSON on stdout instead of saving it, for piping into another tool
+    #[arg(long, conflicts_with_all = ["json", "execute_only"])]
+    no_save: bool,
+
+    /// Print only the proof bytes, as 0x-prefixed hex, on stdout instead of saving a file
+    #[arg(long = "stdout", conflicts_with_all = ["json", "execute_only", "no_save"])]
+    proof_to_stdout: bool,
+
+    /// Directory the proof file is written to
+    #[arg(long, env = "PROOF_DIR", default_value = "../proofs")]
+    output_dir: String,
+
+    /// Replace a proof file that already exists at the output path instead of failing
+    #[arg(long)]
+    overwrite: bool,
+
+    /// Write the proof file gzip-compressed, as .json.gz; verify and decode read either form
+    #[arg(long)]
+    gzip: bool,
+
+    /// Write Prometheus metrics for this run (duration, cycles, outcome) to this file
+    #[arg(long)]
+    metrics_file: Option<String>,
+
+    /// Passport JSON file, or - for stdin
+    #[arg(long = "passport", env = "PASSPORT_FILE", default_value = "-")]
+    passport_file: String,
+
+    /// Verification requirements JSON file, or - for stdin
+    #[arg(long, env = "VERIFICATION_REQUIREMENTS_PATH", default_value = "../verification_requirements.json")]
+    requirements: String,
+
+    /// VerificationPolicy JSON file (minAge, allowedNationalities, requireUnexpired) that replaces
+    /// the requirements' age and nationality rules; its hash is committed as policy_commitment
+    #[arg(long, env = "VERIFICATION_POLICY_PATH", conflicts_with = "target_nationalities")]
+    policy: Option<String>,
+
+    #[command(flatten)]
+    passport: PassportArgs,
 }
 
-fn main() {
-    std::env::set_var("RUST_LOG", "debug");
-    sp1_sdk::utils::setup_logger();
+fn main() -> Result<()> {
+    assert_valid_elf(PASSPORT_ELF);
     dotenv::dotenv().ok();
+    let args = Args::parse();
+    set_json_output(args.json);
+    if args.no_save || args.proof_to_stdout {
+        reserve_stdout();
+    }
+    assert_prover_config();
+    // The SP1 logger writes to stdout, which JSON mode keeps for the report and the piping flags
+    // keep for the proof.
+    if !args.json && !args.no_save && !args.proof_to_stdout {
+        std::env::set_var("RUST_LOG", "debug");
+        sp1_sdk::utils::setup_logger();
+    }
+
+    let mut report = RunReport::default();
+    let result = run(args, &mut report);
+    report.success = result.is_ok();
+    if let Err(e) = &result {
+        report.error = Some(format!("{:#}", e));
+    }
+    report.emit();
+    result
+}
 
+fn run(args: Args, report: &mut RunReport) -> Result<()> {
     print_banner();
     print_step("Initializing SP1 Prover (EVM Mode)...");
 
-    let passport_input: PassportInput = serde_json::from_reader(std::io::stdin())
-        .expect("Failed to parse passport JSON from stdin");
+    check_security_level(args.proof_system, args.min_security_bits).map_err(|e| anyhow!(e))?;
 
-    let reqs_path = std::env::var("VERIFICATION_REQUIREMENTS_PATH")
-        .unwrap_or_else(|_| "../verification_requirements.json".to_string());
-    let reqs_file = std::fs::File::open(&reqs_path).unwrap_or_else(|_| panic!("Failed to open {}", reqs_path));
-    let reqs: VerificationRequirements = serde_json::from_reader(reqs_file).expect("Failed to parse verification_requirements.json");
+    let reads_passport_json =
+        args.mrz.is_none() && args.dg1.is_none() && args.qr.is_none() && !args.passport.is_complete();
+    if args.requirements == "-" && args.passport_file == "-" && reads_passport_json {
+        bail!("the passport and the requirements can't both be read from stdin; give one of them as a file");
+    }
+    let mut reqs: VerificationRequirements = read_json(&args.requirements, "requirements")?;
 
-    print_info("Document", &passport_input.document_number);
-    print_info("Binding To", &reqs.wallet_address);
+    let signed_time: Option<SignedTimestamp> =
+        args.time_oracle.as_deref().map(|path| read_json(path, "time oracle attestation")).transpose()?;
+    let now = match &signed_time {
+        Some(signed) => signed.timestamp,
+        None => evaluation_timestamp(args.current_date.as_ref())?,
+    };
 
-    let passport = PassportAttributes {
-        document_number: passport_input.document_number,
-        date_of_birth: Date {
-            year: passport_input.birth_year,
-            month: passport_input.birth_month,
-            day: passport_input.birth_day,
+    let mut passports: Vec<PassportWitness> = match (&args.mrz, &args.dg1, &args.qr) {
+        (Some(lines), _, _) => vec![mrz_witness(&lines[0], &lines[1], &timestamp_to_date(now))?],
+        (None, Some(path), _) => {
+            let dg1 = std::fs::read(path).with_context(|| format!("failed to read {}", path))?;
+            let (line1, line2) = dg1_mrz_lines(&dg1).with_context(|| format!("{} is not a DG1 data group", path))?;
+            vec![mrz_witness(&line1, &line2, &timestamp_to_date(now))?]
+        }
+        (None, None, Some(path)) => {
+            let mut input = read_passport_qr(path)?;
+            args.passport.apply(&mut input)?;
+            vec![to_passport_witness(input)?]
+        }
+        (None, None, None) => read_passport_inputs(&args.passport_file, &args.passport)?
+            .into_iter()
+            .map(to_passport_witness)
+            .collect::<Result<_>>()?,
+    };
+    if passports.is_empty() {
+        bail!("the passport array is empty");
+    }
+    // The guest proves over whichever passport first meets the requirements.
+    let other_passports = passports.split_off(1);
+    let witness = passports.remove(0);
+    let mut options = GuestOptions {
+        max_age: args.max_age,
+        verifier_id: args
+            .verifier_id
+            .as_deref()
+            .map(|id| decode_hex_array(id, "verifier id"))
+            .transpose()?,
+        identity_fields: args.identity_fields.unwrap_or_default(),
+        disclosures: args.disclose.unwrap_or_default(),
+        expired: args.expired,
+        target_nationalities: args.target_nationalities,
+        blocked_nationalities: args.blocked_nationalities,
+        reference_date: args.reference_date,
+        previous_wallet: args
+            .rebind_from
+            .as_deref()
+            .map(|wallet| parse_wallet_address(wallet).context("invalid --rebind-from wallet"))
+            .transpose()?,
+        document_allowlist: match &args.allowlist {
+            Some(path) => read_json(path, "document allowlist")?,
+            None => Vec::new(),
         },
-        date_of_expiry: Date { 
-            year: passport_input.expiry_year, 
-            month: passport_input.expiry_month, 
-            day: passport_input.expiry_day 
-        }, 
-        nationality: passport_input.nationality,
-        name: passport_input.name,
-        sex: passport_input.sex,
+        hide_target_nationality: args.hide_target_nationality,
+        linked_wallets: args.linked_wallets,
+        valid_for_seconds: args.valid_for_seconds,
+        allow_zero_wallet: args.allow_zero_wallet,
+        stateless_by_issuer: args.stateless_by_issuer,
+        audit: args.audit,
+        reveal_matched_nationality: args.reveal_matched_nationality,
+        time_oracle: signed_time.as_ref().map(SignedTimestamp::oracle_signature).transpose()?,
+        other_passports,
     };
+    if let Some(path) = &args.policy {
+        let policy: VerificationPolicy = read_json(path, "policy")?;
+        apply_policy(&policy, &mut reqs, &mut options)?;
+        let committed = verification_policy(&reqs, &options);
+        print_info("Policy", &format!("0x{}", hex::encode(committed.commitment())));
+    }
+    check_options(&reqs, &options)?;
+    if reqs.required_age == 0 {
+        print_info("Warning", "required_age is 0, so the age check proves nothing");
+    }
+    if let Some(bytes @ 1..=15) = reqs.commitment_bytes {
+        print_info("Warning", &format!("a {}-byte identity hash makes collisions between holders feasible", bytes));
+    }
 
-    let wallet_bytes = hex::decode(reqs.wallet_address.trim_start_matches("0x")).expect("Invalid wallet address");
-    let mut wallet_array = [0u8; 20];
-    wallet_array.copy_from_slice(&wallet_bytes);
+    if !options.other_passports.is_empty() {
+        print_info("Passports", &(options.other_passports.len() + 1).to_string());
+    }
+    for passport in std::iter::once(&witness).chain(&options.other_passports) {
+        print_info("Document", passport.passport.document_number.as_str());
+    }
+    if let Some(signature) = witness.document_signature.as_ref().filter(|s| !s.algorithm.is_supported()) {
+        print_info(
+            "Warning",
+            &format!("{:?} chip signatures can't be verified in the guest; is_valid_signature will be false", signature.algorithm),
+        );
+    }
+    print_info("Binding To", &to_eip55(&parse_wallet_address(&reqs.wallet_address)?));
+    if let Some(previous_wallet) = &options.previous_wallet {
+        print_info("Rebinding From", &to_eip55(previous_wallet));
+        if witness.document_signature.is_none() || witness.data_groups.is_none() || reqs.csca_roots.is_empty() {
+            print_info("Warning", "rebindWallet needs a chip signature, DG1 and SOD, and cscaRoots; this proof will be rejected");
+        }
+    }
+    if let Some(signature) = &options.time_oracle {
+        print_info("Time Oracle", &format!("0x{}", hex::encode(oracle_key_hash(&signature.public_key))));
+    }
+    for wallet in &options.linked_wallets {
+        print_info("Linking", &format!("chain {}: 0x{}", wallet.chain_id, hex::encode(&wallet.address)));
+    }
+    if !options.document_allowlist.is_empty() {
+        print_info("Allowlist", &format!("{} document numbers", options.document_allowlist.len()));
+    }
+    if let Some(seconds) = options.valid_for_seconds {
+        print_info("Valid Until", &format_date(now.saturating_add(seconds)));
+    }
+    if let Some(date) = &options.reference_date {
+        print_info("Age As Of", &format!("{:04}-{:02}-{:02}", date.year, date.month, date.day));
+    }
+    if args.strict {
+        let unmet = unmet_requirements(&witness, &reqs, &options, now)?;
+        if !unmet.is_empty() {
+            for requirement in &unmet {
+                print_error(&format!("Requirement not met: {}", requirement));
+            }
+            bail!("strict mode: the passport doesn't meet {}, so no proof was generated", unmet.join(", "));
+        }
+        print_success("Every requirement is met");
+    }
+    let mut timer = PhaseTimer::new();
+    let stdin = timer.time("inputs", || guest_stdin(&witness, &reqs, &options, now))?;
 
-    let verifier_bytes = hex::decode(reqs.verifier_address.trim_start_matches("0x")).expect("Invalid verifier address");
-    let mut verifier_array = [0u8; 20];
-    verifier_array.copy_from_slice(&verifier_bytes);
-    
-    let now = std::time::SystemTime::now().duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
-
-    let client = ProverClient::from_env();
-    let (pk, vk) = client.setup(PASSPORT_ELF);
+    let client = prover_client();
+    if args.execute_only {
+        return execute_only(&client, &stdin, &witness, report);
+    }
+    let (pk, vk) = timer.time("setup", || setup_cached(&client, PASSPORT_ELF));
+    report.vkey_hash = Some(vk.bytes32());
     print_success("Prover initialized");
+    if mock_prover() {
+        print_info("Prover", "mock (the proof will not verify outside mock mode)");
+    }
 
-    let mut stdin = SP1Stdin::new();
-    stdin.write(&passport);
-    stdin.write(&wallet_array);
-    stdin.write(&verifier_array);
-    stdin.write(&now);
-    stdin.write(&reqs.required_age);
-    stdin.write(&reqs.required_nationality);
-    stdin.write(&reqs.required_sex);
+    // Cycle counts come from a separate execution, so only pay for it when they are wanted.
+    let cycles = if args.verbose || args.metrics_file.is_some() {
+        let (_, execution) = timer.time("execute", || client.execute(PASSPORT_ELF, &stdin).run())?;
+        Some(execution.total_instruction_count())
+    } else {
+        None
+    };
 
-    print_step("Generating EVM Proof (Groth16)...");
+    let system = args.proof_system;
+    print_step(&format!("Generating EVM Proof ({})...", system.name()));
     let start = Instant::now();
-    let proof = client.prove(&pk, &stdin).groth16().run().expect("Groth16 proof failed");
+    let result = retry_proving(args.max_retries, Duration::from_secs(args.retry_delay), || {
+        with_spinner("Proving", || {
+            timer.time("prove", || {
+                let builder = client.prove(&pk, &stdin);
+                match system {
+                    ProofSystem::Core => builder.core().run(),
+                    ProofSystem::Compressed => builder.compressed().run(),
+                    ProofSystem::Groth16 => builder.groth16().run(),
+                    ProofSystem::Plonk => builder.plonk().run(),
+                }
+            })
+        })
+    });
+    let proof = match result {
+        Ok(proof) => proof,
+        Err(e) => {
+            let message = format!("{:#}", e);
+            eprintln!("Error: {} proof generation failed: {}", system.name(), message);
+            eprintln!("Hint: {}", classify_proving_error(&message).hint());
+            write_metrics(args.metrics_file.as_deref(), system, false, start.elapsed().as_secs_f64(), cycles)?;
+            report.duration_ms = Some(start.elapsed().as_millis() as u64);
+            report.error = Some(message);
+            report.emit();
+            std::process::exit(PROVER_FAILURE_EXIT_CODE);
+        }
+    };
 
     print_success(&format!("Proof generated in {:.2?}", start.elapsed()));
+    if let (true, Some(cycles)) = (args.verbose, cycles) {
+        print_info("Cycles", &cycles.to_string());
+    }
+    report.duration_ms = Some(start.elapsed().as_millis() as u64);
+    report.cycles = cycles;
+    write_metrics(args.metrics_file.as_deref(), system, true, start.elapsed().as_secs_f64(), cycles)?;
 
-    let proof_dir = std::env::var("PROOF_DIR").unwrap_or_else(|_| "../proofs".to_string());
-    std::fs::create_dir_all(&proof_dir).unwrap();
-
-    let job_id = std::env::var("PROOF_JOB_ID").unwrap_or_else(|_| "default".to_string());
-    let proof_filename = format!("passport_proof_evm_{}.json", job_id);
-
-    let proof_bytes = proof.bytes();
     let public_values = proof.public_values.as_slice();
+    let job_id = std::env::var("PROOF_JOB_ID")
+        .unwrap_or_else(|_| format!("{}_{}", artifact_id(public_values), system.mode()));
+    let proof_filename = format!("passport_proof_evm_{}.json", job_id);
+    // Core and compressed proofs have no on-chain encoding, so the file carries the whole proof.
+    let mut proof_bytes = timer.time("serialize", || match system {
+        ProofSystem::Core | ProofSystem::Compressed => bincode::serialize(&proof).context("failed to serialize proof"),
+        ProofSystem::Groth16 | ProofSystem::Plonk => Ok(proof.bytes()),
+    })?;
 
-    // In SP1 mock mode, Groth16 encoded proof bytes are not generated.
+    // In SP1 mock mode, Groth16 and PLONK encoded proof bytes are not generated.
     // Use a single zero byte so the flow proceeds with MockSP1Verifier (which ignores proof bytes).
-    let proof_hex = if proof_bytes.is_empty() {
+    // Mock core and compressed proofs do serialize, so the file is tagged from the prover mode.
+    let mock = mock_prover() || proof_bytes.is_empty();
+    if proof_bytes.is_empty() {
         print_step("Mock mode detected: using placeholder proof bytes (deploy MockSP1Verifier on-chain)");
-        "00".to_string()
+        proof_bytes = vec![0];
+    }
+    print_info("Proof size", &format!("{} bytes", proof_bytes.len()));
+    report.proof_size = Some(proof_bytes.len() as u64);
+
+    let decoded = decode_public_values(public_values).ok().map(|output| public_values_to_json(&output));
+    let mut envelope = ProofEnvelope::new(system.mode(), &proof_bytes, public_values, vk.bytes32(), now);
+    envelope.decoded_public_values = decoded.clone();
+    envelope.meta.insert("jobId".to_string(), job_id.into());
+    envelope.meta.insert("mock".to_string(), mock.into());
+    if let Some(cycles) = cycles {
+        envelope.meta.insert("cycles".to_string(), cycles.into());
+    }
+    print_step("Time by phase");
+    timer.print();
+    envelope.meta.insert("phases".to_string(), timer.to_json());
+    report.phases_ms = Some(timer.to_json());
+    if args.proof_to_stdout {
+        println!("0x{}", envelope.proof);
+        print_success("Proof written to stdout");
+    } else if args.no_save {
+        println!("{}", envelope.to_json()?);
+        print_success("Proof file written to stdout");
     } else {
-        hex::encode(&proof_bytes)
+        let (proof_path, size) = save_artifact(&args.output_dir, &proof_filename, &envelope, args.overwrite, args.gzip)?;
+        print_success(&format!("Proof saved to {}", proof_path));
+        print_info("File size", &size.describe());
+        report.proof_path = Some(proof_path);
+        report.file_size = Some(size.file);
+    }
+    report.public_outputs = decoded;
+    Ok(())
+}
+
+// Executes the guest without proving, so a failed assertion shows up in seconds rather than
+// after setup and a full proof.
+fn execute_only(client: &EnvProver, stdin: &SP1Stdin, witness: &PassportWitness, report: &mut RunReport) -> Result<()> {
+    print_step("Executing program (no proof)...");
+    let start = Instant::now();
+    let (public_values, execution) = client
+        .execute(PASSPORT_ELF, stdin)
+        .run()
+        .map_err(|e| anyhow!("guest execution failed: {:#}", e))?;
+    print_success(&format!("Executed in {:.2?}", start.elapsed()));
+    print_info("Cycles", &execution.total_instruction_count().to_string());
+
+    let output = decode_public_values(public_values.as_slice()).context("failed to decode public values")?;
+    let fields = public_values_to_json(&output);
+    for (key, value) in fields.as_object().unwrap() {
+        match value {
+            serde_json::Value::String(s) => print_info(key, s),
+            other => print_info(key, &other.to_string()),
+        }
+    }
+    // The guest only commits whether the signature verified; the host can say why it didn't.
+    if !output.document_integrity.is_valid_signature {
+        let reason = match &witness.document_signature {
+            None => "no document signature supplied".to_string(),
+            Some(signature) => match verify_passport_signature_detailed(signature) {
+                Err(e) => e.to_string(),
+                Ok(()) => "valid on the host".to_string(),
+            },
+        };
+        print_info("Signature rejected", &reason);
+    }
+    print_divider();
+    report.duration_ms = Some(start.elapsed().as_millis() as u64);
+    report.cycles = Some(execution.total_instruction_count());
+    report.public_outputs = Some(fields);
+    Ok(())
+}
+
+fn write_metrics(
+    path: Option<&str>,
+    system: ProofSystem,
+    succeeded: bool,
+    duration_secs: f64,
+    cycles: Option<u64>,
+) -> Result<()> {
+    let Some(path) = path else {
+        return Ok(());
     };
+    let metrics = ProofMetrics {
+        mode: system.mode(),
+        succeeded,
+        duration_secs,
+        cycles,
+    };
+    std::fs::write(path, metrics.to_prometheus()).with_context(|| format!("failed to write {}", path))
+}
 
-    let proof_data = serde_json::json!({
-        "proof": proof_hex,
-        "publicValues": hex::encode(public_values),
-        "vkey": vk.bytes32()
-    });
-    std::fs::write(
-        format!("{}/{}", proof_dir, proof_filename),
-        serde_json::to_string_pretty(&proof_data).unwrap(),
-    ).unwrap();
 
-    print_success(&format!("Proof saved to {}/{}", proof_dir, proof_filename));
+// Passport JSON, with any fields given as flags or environment variables applied on top. Dual
+// nationals may send an array of every passport they hold; only one of them gets proven. Parsed
+// by shape first so an error names the field that is wrong.
+fn read_passport_inputs(path: &str, fields: &PassportArgs) -> Result<Vec<PassportInput>> {
+    if fields.is_complete() {
+        let mut input = PassportInput::default();
+        fields.apply(&mut input)?;
+        return Ok(vec![input]);
+    }
+
+    let json: serde_json::Value = read_json(path, "passport JSON")?;
+    if json.is_array() {
+        if !fields.is_empty() {
+            bail!("passport field overrides can't be combined with several passports");
+        }
+        return serde_json::from_value(json).context("invalid passport in the array");
+    }
+    let mut input: PassportInput = serde_json::from_value(json).context("invalid passport JSON")?;
+    fields.apply(&mut input)?;
+    Ok(vec![input])
 }
+
+
@@ -1,34 +1,72 @@
-use sp1_sdk::{HashableKey, ProverClient};
+use clap::Parser;
+use passport_verifier_script::utils::{assert_valid_elf, prover_client, setup_cached, vkey_hash};
+use sp1_sdk::HashableKey;
 
 /// ELF binary for the passport verification program
 const PASSPORT_ELF: &[u8] = include_bytes!("../../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
 
+#[derive(Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+enum Format {
+    /// vkey.json, the full key verify reads
+    Json,
+    /// vkey_hash.txt (the bytes32 hex) and vkey.bin (the same 32 bytes, raw)
+    Hash,
+    /// Both
+    All,
+}
+
+/// Write the passport program's verification key and the bytes32 hash contracts are deployed with
+#[derive(Parser)]
+struct Args {
+    /// Which files to write
+    #[arg(long, value_enum, default_value_t = Format::All)]
+    format: Format,
+}
+
 fn main() {
+    assert_valid_elf(PASSPORT_ELF);
+    let args = Args::parse();
+
     // Setup logging
     sp1_sdk::utils::setup_logger();
 
     println!("Extracting verification key for Groth16 proof...");
 
     // Setup prover client
-    let client = ProverClient::from_env();
+    let client = prover_client();
 
-    // Generate proving and verifying keys
-    let (_, vk) = client.setup(PASSPORT_ELF);
+    // The hash alone is read from the key cache without setup; vkey.json needs the full key,
+    // generated or loaded from the cache when the ELF is unchanged
+    let (vk, hash) = if args.format == Format::Hash {
+        (None, vkey_hash(&client, PASSPORT_ELF))
+    } else {
+        let (_, vk) = setup_cached(&client, PASSPORT_ELF);
+        let hash = vk.bytes32();
+        (Some(vk), hash)
+    };
 
     // Print the verification key
     println!("\nVerification Key:");
-    println!("VKey Hash: {}", vk.bytes32());
-
-    // Save to file
-    let vkey_bytes = serde_json::to_string_pretty(&vk)
-        .expect("Failed to serialize verification key");
+    println!("VKey Hash: {}", hash);
 
-    std::fs::write("vkey.json", &vkey_bytes)
-        .expect("Failed to write verification key to file");
+    if let Some(vk) = &vk {
+        let vkey_bytes = serde_json::to_string_pretty(vk)
+            .expect("Failed to serialize verification key");
 
-    std::fs::write("vkey_hash.txt", vk.bytes32())
-        .expect("Failed to write verification key hash to file");
+        std::fs::write("vkey.json", &vkey_bytes)
+            .expect("Failed to write verification key to file");
+        println!("\nVerification key saved to: vkey.json");
+        println!("This verification key can be used for both regular and Groth16 proofs");
+    }
 
-    println!("\nVerification key saved to: vkey.json");
-    println!("This verification key can be used for both regular and Groth16 proofs");
+    if args.format != Format::Json {
+        // The programVKey deploy.js and the contracts take, so it never has to be copied by hand.
+        std::fs::write("vkey_hash.txt", &hash)
+            .expect("Failed to write verification key hash to file");
+        let raw = hex::decode(hash.trim_start_matches("0x"))
+            .expect("bytes32() is hex");
+        std::fs::write("vkey.bin", raw)
+            .expect("Failed to write raw verification key hash to file");
+        println!("\nVerification key hash saved to: vkey_hash.txt and vkey.bin");
+    }
 }
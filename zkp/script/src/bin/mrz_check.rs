@@ -0,0 +1,52 @@
+use anyhow::{bail, Result};
+use clap::Parser;
+use passport_verifier_lib::mrz::td3_check_digit_results;
+use passport_verifier_script::utils::*;
+
+/// Check the check digits of a typed TD3 (passport) MRZ field by field, without proving. Uses
+/// the same ICAO 9303 7-3-1 check as evm --mrz and the guest's MRZ check
+#[derive(Parser)]
+struct Args {
+    /// First MRZ line (44 characters, starting with P)
+    line1: String,
+
+    /// Second MRZ line (44 characters)
+    line2: String,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    print_banner();
+
+    // Line 1 carries no check digits, only its shape can be wrong.
+    let line1 = args.line1.trim();
+    let line1_problem = if line1.len() != 44 {
+        Some(format!("line 1 has {} characters, expected 44", line1.len()))
+    } else if !line1.starts_with('P') {
+        Some("line 1 must start with 'P' for a passport".to_string())
+    } else {
+        None
+    };
+
+    let results = td3_check_digit_results(&args.line2)?;
+    print_divider();
+    for result in &results {
+        let line = format!("{:<16} printed {}, computed {}", result.field, result.printed, result.computed);
+        if result.is_valid() {
+            print_success(&line);
+        } else {
+            print_error(&line);
+        }
+    }
+    print_divider();
+
+    let failed: Vec<&str> = results.iter().filter(|r| !r.is_valid()).map(|r| r.field).collect();
+    if !failed.is_empty() {
+        bail!("check digit mismatch: {}", failed.join(", "));
+    }
+    if let Some(problem) = line1_problem {
+        bail!("check digits match, but {}", problem);
+    }
+    print_success("All check digits match");
+    Ok(())
+}
@@ -0,0 +1,83 @@
+use anyhow::{bail, Context, Result};
+use clap::{Arg, Command};
+use std::path::PathBuf;
+
+// Subcommand, the binary it runs, and what it does.
+const TOOLS: [(&str, &str, &str); 16] = [
+    ("prove", "evm", "Generate a proof of a passport against a requirements file"),
+    ("batch", "batch", "Prove many passports with a single prover setup"),
+    ("aggregate", "aggregate", "Fold compressed age proofs into one aggregate proof"),
+    ("verify", "verify", "Verify a proof file without proving"),
+    ("decode", "decode", "Decode the public values of a proof file or verifyClaim calldata"),
+    ("vkey", "vkey", "Write the verification key and the bytes32 hash contracts are deployed with"),
+    ("check-vkey", "check_vkey", "Fail if the verification key no longer matches the locked value"),
+    ("registry", "registry", "Check whether an identity is already registered in a PassportRegistry"),
+    ("commitments", "commitments", "Print a passport's identity hash and other commitments without proving"),
+    ("wallet-address", "counterfactual", "Derive an identity's counterfactual CREATE2 wallet address"),
+    ("mrz-check", "mrz_check", "Check a typed MRZ's check digits without proving"),
+    ("qr", "passport_qr", "Encode passport JSON as a QR code PNG for evm --qr"),
+    ("sol-types", "sol_types", "Generate contracts/PassportTypes.sol from the sol! types in lib"),
+    ("requirements-schema", "requirements_schema", "Generate the JSON schema for requirements files"),
+    ("bench", "bench", "Benchmark proving time for each proof system"),
+    ("commitment-bench", "commitment_bench", "Compare the gas cost of the identity hash functions"),
+];
+
+fn cli() -> Command {
+    let mut cli = Command::new("passport")
+        .about("The passport verifier's tools behind one command; each subcommand takes the flags of the binary it runs")
+        .subcommand_required(true)
+        .arg_required_else_help(true);
+    for (name, binary, about) in TOOLS {
+        cli = cli.subcommand(
+            Command::new(name)
+                .about(format!("{} (the {} binary)", about, binary))
+                // --help belongs to the binary, which knows its own flags.
+                .disable_help_flag(true)
+                .arg(Arg::new("args").num_args(0..).trailing_var_arg(true).allow_hyphen_values(true)),
+        );
+    }
+    cli
+}
+
+fn main() -> Result<()> {
+    let matches = cli().get_matches();
+    let (name, sub) = matches.subcommand().expect("a subcommand is required");
+    let binary = TOOLS.iter().find(|(tool, _, _)| *tool == name).expect("every subcommand is a tool").1;
+    let args: Vec<&String> = sub.get_many::<String>("args").map(Iterator::collect).unwrap_or_default();
+
+    let path = sibling_binary(binary)?;
+    if !path.exists() {
+        bail!("{} is not built; run `cargo build --release` in script/ to build every tool", path.display());
+    }
+    let status = std::process::Command::new(&path)
+        .args(args)
+        .status()
+        .with_context(|| format!("failed to run {}", path.display()))?;
+    // Pass the exit code through, so scripts can still tell a prover failure from a bad input.
+    std::process::exit(status.code().unwrap_or(1));
+}
+
+// The tools are built into the same target directory as this binary.
+fn sibling_binary(name: &str) -> Result<PathBuf> {
+    let exe = std::env::current_exe().context("can't locate the passport binary")?;
+    let dir = exe.parent().context("the passport binary has no parent directory")?;
+    Ok(dir.join(format!("{}{}", name, std::env::consts::EXE_SUFFIX)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cli_is_well_formed() {
+        cli().debug_assert();
+    }
+
+    #[test]
+    fn every_tool_is_a_binary_of_this_crate() {
+        let manifest = include_str!("../../Cargo.toml");
+        for (_, binary, _) in TOOLS {
+            assert!(manifest.contains(&format!("name = \"{}\"", binary)), "{} is not a [[bin]]", binary);
+        }
+    }
+}
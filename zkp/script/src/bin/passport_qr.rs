@@ -0,0 +1,30 @@
+use anyhow::Result;
+use clap::Parser;
+use passport_verifier_script::inputs::read_json;
+use passport_verifier_script::qr::{read_passport_qr, write_passport_qr};
+use passport_verifier_script::utils::*;
+
+/// Encode passport JSON as a QR code PNG, the form the mobile app hands a prepared record to the
+/// prover in; evm --qr reads it back
+#[derive(Parser)]
+struct Args {
+    /// Passport JSON file (the shape evm reads, without chip data), or - for stdin
+    #[arg(default_value = "-")]
+    passport: String,
+
+    /// Where to write the QR code PNG
+    #[arg(long)]
+    emit_qr: String,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    print_banner();
+
+    let json: serde_json::Value = read_json(&args.passport, "passport JSON")?;
+    write_passport_qr(&json, &args.emit_qr)?;
+    // Read it back, so a code the decoder can't scan is caught here rather than at proving time.
+    read_passport_qr(&args.emit_qr)?;
+    print_success(&format!("Passport QR code written to {}", args.emit_qr));
+    Ok(())
+}
@@ -0,0 +1,116 @@
+use alloy_sol_types::{sol, SolCall};
+use anyhow::{anyhow, bail, Context, Result};
+use clap::Parser;
+use passport_verifier_lib::{derive_identity_hash_with, to_eip55, truncate_commitment, CommitmentFields, CommitmentHash};
+use passport_verifier_script::inputs::*;
+use passport_verifier_script::utils::*;
+use serde_json::{json, Value};
+
+sol! {
+    function isRegistered(bytes32 commitment) external view returns (bool);
+    function getWallet(bytes32 commitment) external view returns (address);
+}
+
+/// Check whether a passport's identity hash is already registered in a PassportRegistry
+#[derive(Parser)]
+struct Args {
+    /// Passport JSON in the same shape evm reads, or - for stdin; not needed with --identity-hash
+    /// or the passport field flags
+    passport: Option<String>,
+
+    /// Look up this 32-byte hex identity hash instead of deriving one from a passport
+    #[arg(long, conflicts_with = "passport")]
+    identity_hash: Option<String>,
+
+    /// Passport fields in the identity hash, as given to evm; defaults to dob,nationality,names
+    #[arg(long, value_parser = parse_identity_fields)]
+    identity_fields: Option<CommitmentFields>,
+
+    /// Derive the Poseidon identity hash instead of SHA-256
+    #[arg(long, conflicts_with = "identity_fields")]
+    poseidon: bool,
+
+    /// Derive the Keccak-256 identity hash instead of SHA-256
+    #[arg(long, conflicts_with = "poseidon")]
+    keccak: bool,
+
+    /// Leading bytes of the identity hash the proofs kept (commitmentBytes), 1 to 32
+    #[arg(long, default_value_t = 32, value_parser = clap::value_parser!(u8).range(1..=32))]
+    commitment_bytes: u8,
+
+    /// JSON-RPC endpoint of the chain the registry is deployed on
+    #[arg(long, env = "RPC_URL")]
+    rpc_url: String,
+
+    /// PassportRegistry contract address
+    #[arg(long, env = "REGISTRY_ADDRESS")]
+    registry: String,
+
+    #[command(flatten)]
+    fields: PassportArgs,
+}
+
+fn main() -> Result<()> {
+    dotenv::dotenv().ok();
+    let args = Args::parse();
+    print_banner();
+
+    let registry = to_eip55(&parse_wallet_address(&args.registry).context("invalid registry address")?);
+    let identity_hash: [u8; 32] = match (&args.identity_hash, &args.passport) {
+        (Some(hash), _) => decode_hex_array(hash, "identity hash")?,
+        (None, None) if args.fields.is_empty() => bail!("give a passport file, the passport fields or --identity-hash"),
+        (None, path) => {
+            let passport = args.fields.read_passport(path.as_deref())?;
+            let hash = match (args.poseidon, args.keccak) {
+                (true, _) => CommitmentHash::Poseidon,
+                (_, true) => CommitmentHash::Keccak256,
+                _ => CommitmentHash::Sha256,
+            };
+            let identity_hash = derive_identity_hash_with(&passport, hash, args.identity_fields.unwrap_or_default())
+                .ok_or_else(|| anyhow!("no identity hash for this field selection"))?;
+            truncate_commitment(identity_hash, args.commitment_bytes)
+        }
+    };
+    print_info("Registry", &registry);
+    print_info("Identity hash", &format!("0x{}", hex::encode(identity_hash)));
+
+    print_step("Querying registry...");
+    let client = reqwest::blocking::Client::new();
+    let registered = eth_call(&client, &args.rpc_url, &registry, isRegisteredCall { commitment: identity_hash.into() })?;
+    if !registered {
+        print_success("Not registered: this identity can still bind a wallet");
+        return Ok(());
+    }
+    let wallet = eth_call(&client, &args.rpc_url, &registry, getWalletCall { commitment: identity_hash.into() })?;
+    print_success("Registered");
+    print_info("Bound wallet", &wallet.to_checksum(None));
+    Ok(())
+}
+
+// A read-only call against the latest block, decoded as the function's single return value.
+fn eth_call<C: SolCall>(client: &reqwest::blocking::Client, rpc_url: &str, to: &str, call: C) -> Result<C::Return> {
+    let request = json!({
+        "jsonrpc": "2.0",
+        "id": 1,
+        "method": "eth_call",
+        "params": [{ "to": to, "data": format!("0x{}", hex::encode(call.abi_encode())) }, "latest"],
+    });
+    let response: Value = client
+        .post(rpc_url)
+        .json(&request)
+        .send()
+        .and_then(|response| response.error_for_status())
+        .and_then(|response| response.json())
+        .with_context(|| format!("RPC request to {} failed", rpc_url))?;
+    if let Some(error) = response.get("error") {
+        bail!("eth_call failed: {}", error);
+    }
+    let result = response["result"]
+        .as_str()
+        .ok_or_else(|| anyhow!("RPC response has no result"))?;
+    let data = hex::decode(result.trim_start_matches("0x")).context("RPC result is not valid hex")?;
+    if data.is_empty() {
+        bail!("{} returned no data; is it a PassportRegistry on this chain?", to);
+    }
+    C::abi_decode_returns(&data).context("failed to decode the registry's response")
+}
@@ -0,0 +1,64 @@
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use passport_verifier_script::inputs::VerificationRequirements;
+use passport_verifier_script::utils::*;
+
+/// Write the JSON schema for verification_requirements.json, generated from the
+/// VerificationRequirements type every binary parses it into, for editors and other tooling
+#[derive(Parser)]
+struct Args {
+    /// Schema file to write
+    #[arg(long, default_value = "../verification_requirements.schema.json")]
+    out: String,
+
+    /// Fail if the file is missing or out of date instead of writing it
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let schema = render();
+
+    if args.check {
+        let current = std::fs::read_to_string(&args.out).with_context(|| format!("failed to read {}", args.out))?;
+        if current != schema {
+            print_error(&format!("{} is out of date with VerificationRequirements", args.out));
+            bail!("run requirements_schema without --check to regenerate it");
+        }
+        print_success(&format!("{} matches VerificationRequirements", args.out));
+        return Ok(());
+    }
+
+    std::fs::write(&args.out, schema).with_context(|| format!("failed to write {}", args.out))?;
+    print_success(&format!("Wrote {}", args.out));
+    Ok(())
+}
+
+fn render() -> String {
+    let schema = schemars::schema_for!(VerificationRequirements);
+    let mut out = serde_json::to_string_pretty(&schema).expect("a schema serializes to JSON");
+    out.push('\n');
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checked_in_schema_is_current() {
+        assert_eq!(
+            render(),
+            include_str!("../../../verification_requirements.schema.json"),
+            "verification_requirements.schema.json is stale; run `cargo run --bin requirements_schema` to regenerate it"
+        );
+    }
+
+    #[test]
+    fn example_requirements_are_valid() {
+        let reqs: VerificationRequirements =
+            serde_json::from_str(include_str!("../../../verification_requirements.json")).unwrap();
+        reqs.validate().unwrap();
+    }
+}
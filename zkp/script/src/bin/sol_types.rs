@@ -0,0 +1,134 @@
+use alloy_sol_types::SolStruct;
+use anyhow::{bail, Context, Result};
+use clap::Parser;
+use passport_verifier_lib::PassportVerificationOutput;
+use passport_verifier_script::utils::*;
+
+/// Write Solidity struct definitions matching the guest's public output, generated from the
+/// sol! types in lib so abi.decode in a contract always lines up with what the guest commits
+#[derive(Parser)]
+struct Args {
+    /// Solidity file to write
+    #[arg(long, default_value = "../contracts/PassportTypes.sol")]
+    out: String,
+
+    /// Fail if the file is missing or out of date instead of writing it
+    #[arg(long)]
+    check: bool,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    let source = render();
+
+    if args.check {
+        let current = std::fs::read_to_string(&args.out).with_context(|| format!("failed to read {}", args.out))?;
+        if current != source {
+            print_error(&format!("{} is out of date with the sol! types in lib", args.out));
+            bail!("run sol_types without --check to regenerate it");
+        }
+        print_success(&format!("{} matches the sol! types", args.out));
+        return Ok(());
+    }
+
+    std::fs::write(&args.out, source).with_context(|| format!("failed to write {}", args.out))?;
+    print_success(&format!("Wrote {}", args.out));
+    Ok(())
+}
+
+fn render() -> String {
+    let mut source = String::from(
+        "// SPDX-License-Identifier: MIT\n\
+         pragma solidity ^0.8.20;\n\
+         \n\
+         // Generated by `cargo run --bin sol_types` from the sol! types in lib. Do not edit by hand.\n\
+         // Decode a proof's public values with abi.decode(publicValues, (PassportVerificationOutput)).\n",
+    );
+    // Nested structs first, each once, in the order the output first uses them.
+    let mut seen = Vec::new();
+    for component in PassportVerificationOutput::eip712_components() {
+        if !seen.contains(&component) {
+            source.push('\n');
+            source.push_str(&solidity_struct(&component));
+            seen.push(component);
+        }
+    }
+    source.push('\n');
+    source.push_str(&solidity_struct(&PassportVerificationOutput::eip712_root_type()));
+    source
+}
+
+// "Name(type a,type b)" -> "struct Name {\n    type a;\n    type b;\n}\n"
+fn solidity_struct(encode_type: &str) -> String {
+    let (name, members) = encode_type
+        .trim_end_matches(')')
+        .split_once('(')
+        .expect("EIP-712 type is Name(members)");
+    let mut out = format!("struct {} {{\n", name);
+    for member in members.split(',') {
+        out.push_str(&format!("    {};\n", member));
+    }
+    out.push_str("}\n");
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passport_verifier_lib::{CommitmentFields, CommitmentHash, COMMITMENT_VERSION};
+
+    // Member types with nested structs expanded in place, which is how the ABI lays out a static
+    // nested struct and so how a hand-written tuple in a contract must list them.
+    fn flattened_types(encode_type: &str, components: &[String]) -> Vec<String> {
+        let (_, members) = encode_type.trim_end_matches(')').split_once('(').unwrap();
+        members
+            .split(',')
+            .flat_map(|member| {
+                let ty = member.split_once(' ').unwrap().0;
+                match components.iter().find(|c| c.starts_with(&format!("{}(", ty))) {
+                    Some(component) => flattened_types(component, components),
+                    None => vec![ty.to_string()],
+                }
+            })
+            .collect()
+    }
+
+    #[test]
+    fn checked_in_solidity_types_are_current() {
+        assert_eq!(
+            render(),
+            include_str!("../../../contracts/PassportTypes.sol"),
+            "contracts/PassportTypes.sol is stale; run `cargo run --bin sol_types` to regenerate it"
+        );
+    }
+
+    // AppVerifier decodes a prefix of the output as a plain tuple; adding or reordering a field
+    // before the end of that prefix would silently shift every value it reads.
+    #[test]
+    fn app_verifier_tuple_matches_output_prefix() {
+        let contract = include_str!("../../../contracts/AppVerifier.sol");
+        let start = contract.find("abi.decode(dataToDecode, (").expect("AppVerifier decodes the public values");
+        let tuple = &contract[start + "abi.decode(dataToDecode, (".len()..];
+        let tuple: Vec<&str> = tuple[..tuple.find(')').unwrap()].split(',').map(str::trim).collect();
+
+        let components: Vec<String> =
+            PassportVerificationOutput::eip712_components().into_iter().map(|c| c.into_owned()).collect();
+        let output = flattened_types(&PassportVerificationOutput::eip712_root_type(), &components);
+        assert_eq!(tuple, output[..tuple.len()]);
+    }
+
+    // AppVerifier only registers identity hashes made with evm's defaults, so a change to the
+    // defaults has to change the pinned values with it.
+    #[test]
+    fn app_verifier_pins_the_default_identity_commitment() {
+        let contract = include_str!("../../../contracts/AppVerifier.sol");
+        let pinned = |name: &str| {
+            let start = contract.find(&format!("{} = ", name)).expect(name) + name.len() + 3;
+            &contract[start..start + contract[start..].find(';').unwrap()]
+        };
+        assert_eq!(pinned("IDENTITY_COMMITMENT_HASH"), (CommitmentHash::default() as u8).to_string());
+        assert_eq!(pinned("IDENTITY_FIELD_MASK"), format!("bytes32(uint256({}))", CommitmentFields::default().bits()));
+        assert_eq!(pinned("IDENTITY_COMMITMENT_BYTES"), "32");
+        assert_eq!(pinned("IDENTITY_COMMITMENT_VERSION"), COMMITMENT_VERSION.to_string());
+    }
+}
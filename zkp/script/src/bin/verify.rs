@@ -0,0 +1,176 @@
+use anyhow::{anyhow, bail, Context, Result};
+use clap::{Parser, ValueEnum};
+use passport_verifier_script::envelope::ProofEnvelope;
+use passport_verifier_script::inputs::decode_hex_array;
+use passport_verifier_script::public_values::*;
+use passport_verifier_script::utils::*;
+use sp1_sdk::{HashableKey, SP1ProofWithPublicValues, SP1VerifyingKey};
+use sp1_verifier::{Groth16Verifier, PlonkVerifier};
+
+/// Verify a saved proof against a serialized verification key, without the guest ELF or a
+/// proving key
+#[derive(Parser)]
+struct Args {
+    /// JSON proof file written by the evm binary (.json, or .json.gz with --gzip), or any other file saved with
+    /// SP1ProofWithPublicValues::save
+    #[arg(long)]
+    proof: String,
+
+    /// Verification key written by the vkey binary
+    #[arg(long, default_value = "vkey.json")]
+    vkey: String,
+
+    /// Print a plain-language summary instead of the raw fields
+    #[arg(long)]
+    report: bool,
+
+    /// Proof system the destination verifier accepts, e.g. plonk for an SP1 PLONK verifier
+    /// contract; a proof of another kind fails with a mismatch error before any verification
+    #[arg(long, value_enum)]
+    expect: Option<ProofSystem>,
+
+    /// identity_hash the proof must commit, e.g. the one registered for this user earlier; a
+    /// valid proof of another identity fails
+    #[arg(long)]
+    expected_commitment: Option<String>,
+}
+
+fn main() -> Result<()> {
+    let args = Args::parse();
+    print_banner();
+
+    let vk_file = std::fs::File::open(&args.vkey).with_context(|| format!("failed to open {}", args.vkey))?;
+    let vk: SP1VerifyingKey =
+        serde_json::from_reader(vk_file).with_context(|| format!("failed to parse {}", args.vkey))?;
+    let vkey_hash = vk.bytes32();
+    print_info("VKey", &vkey_hash);
+
+    // A proof file says which program made it; a bare SP1 proof is taken to be a passport proof.
+    let (public_values, kind) = if args.proof.ends_with(".json") || args.proof.ends_with(".json.gz") {
+        verify_proof_file(&args.proof, &vk, &vkey_hash, args.expect)?
+    } else {
+        (verify_sp1_proof(&args.proof, &vk, args.expect)?, OutputKind::Passport)
+    };
+    print_success("Proof is valid");
+
+    let output = PublicOutputs::decode(kind, &public_values).context("failed to decode public values")?;
+    if let Some(expected) = &args.expected_commitment {
+        let PublicOutputs::Passport(output) = &output else {
+            bail!("--expected-commitment needs a passport proof, and this is an aggregate proof");
+        };
+        let expected: [u8; 32] = decode_hex_array(expected, "expected commitment")?;
+        if output.identity_hash != expected {
+            bail!(
+                "proof is for identity 0x{}, not the expected 0x{}",
+                hex::encode(output.identity_hash),
+                hex::encode(expected)
+            );
+        }
+        print_success("Identity matches the expected commitment");
+    }
+    if args.report {
+        println!("{}", output);
+        print_divider();
+        return Ok(());
+    }
+    for (key, value) in output.to_json().as_object().unwrap() {
+        match value {
+            serde_json::Value::String(s) => print_info(key, s),
+            other => print_info(key, &other.to_string()),
+        }
+    }
+    print_divider();
+    Ok(())
+}
+
+// Checks the proof in an evm or aggregate proof file and returns its public values and which
+// program committed them. Groth16 and PLONK bytes go
+// through sp1-verifier, the same check the on-chain SP1 verifier runs; core and compressed proofs
+// are deserialized and checked by the prover client.
+fn verify_proof_file(
+    path: &str,
+    vk: &SP1VerifyingKey,
+    vkey_hash: &str,
+    expect: Option<ProofSystem>,
+) -> Result<(Vec<u8>, OutputKind)> {
+    let envelope = ProofEnvelope::load(path)?;
+    let proof = envelope.proof_bytes()?;
+    let public_values = envelope.public_values_bytes()?;
+
+    envelope.header().check(vkey_hash).with_context(|| format!("{} can't be verified here", path))?;
+
+    // evm writes a single zero byte in mock mode, which only MockSP1Verifier accepts.
+    if proof == [0u8] {
+        bail!("mock proof: there is no {} proof to verify", envelope.mode);
+    }
+    // A mock core or compressed proof only passes the mock verifier.
+    if envelope.meta.get("mock").and_then(|mock| mock.as_bool()) == Some(true) && !mock_prover() {
+        bail!("mock proof: set PASSPORT_MOCK_PROVER=1 to check it against the mock verifier");
+    }
+
+    let Ok(system) = ProofSystem::from_str(&envelope.mode, true) else {
+        bail!("{} holds a {} proof, which verify does not support", path, envelope.mode);
+    };
+    print_info("Proof System", system.name());
+    // A file labelled with one wrapper but holding the other would fail with an opaque
+    // pairing error, so compare the label with the bytes' selector first.
+    if let Some(actual) = wrapped_proof_system(&proof) {
+        check_proof_system(actual, system).map_err(|e| anyhow!("{}: {} is labelled {}", e, path, envelope.mode))?;
+    }
+    if let Some(expected) = expect {
+        check_proof_system(system, expected).map_err(|e| anyhow!(e))?;
+    }
+
+    match envelope.mode.as_str() {
+        "groth16" => {
+            print_step("Verifying Groth16 proof...");
+            Groth16Verifier::verify(&proof, &public_values, vkey_hash, *sp1_verifier::GROTH16_VK_BYTES)
+                .map_err(|err| anyhow!("proof is invalid: {}", err))?;
+        }
+        "plonk" => {
+            print_step("Verifying PLONK proof...");
+            PlonkVerifier::verify(&proof, &public_values, vkey_hash, *sp1_verifier::PLONK_VK_BYTES)
+                .map_err(|err| anyhow!("proof is invalid: {}", err))?;
+        }
+        "core" | "compressed" => {
+            let proof: SP1ProofWithPublicValues =
+                bincode::deserialize(&proof).context("proof is not a serialized SP1 proof")?;
+            print_step(&format!("Verifying {} proof...", envelope.mode));
+            prover_client().verify(&proof, vk).context("proof is invalid")?;
+            if proof.public_values.as_slice() != public_values.as_slice() {
+                bail!("publicValues do not match the proof");
+            }
+        }
+        other => bail!("{} holds a {} proof, which verify does not support", path, other),
+    }
+    // decodedPublicValues isn't covered by the proof, so an edited copy must not pass for the real one.
+    if let (Some(decoded), Ok(output)) = (&envelope.decoded_public_values, envelope.public_outputs()) {
+        if *decoded != output.to_json() {
+            print_info("Warning", "decodedPublicValues does not match the proved publicValues; ignoring it");
+        }
+    }
+    Ok((public_values, envelope.output_kind()))
+}
+
+// Full SP1 proofs of any kind (core, compressed, Groth16, PLONK). Verifying needs the prover
+// client but not a proving key, so there is no setup.
+fn verify_sp1_proof(path: &str, vk: &SP1VerifyingKey, expect: Option<ProofSystem>) -> Result<Vec<u8>> {
+    let proof = SP1ProofWithPublicValues::load(path).with_context(|| format!("failed to load {}", path))?;
+    let system = sp1_proof_system(&proof.proof);
+    print_info("Proof System", system.name());
+    if let Some(expected) = expect {
+        check_proof_system(system, expected).map_err(|e| anyhow!(e))?;
+    }
+    if proof.sp1_version != sp1_sdk::SP1_CIRCUIT_VERSION {
+        bail!(
+            "SP1 version mismatch: {} was made with {}, but this build verifies {}",
+            path,
+            proof.sp1_version,
+            sp1_sdk::SP1_CIRCUIT_VERSION
+        );
+    }
+
+    print_step("Verifying SP1 proof...");
+    prover_client().verify(&proof, vk).context("proof is invalid")?;
+    Ok(proof.public_values.to_vec())
+}
@@ -0,0 +1,322 @@
+use crate::public_values::{OutputKind, PublicOutputs};
+use crate::utils::vkey_matches_lock;
+use anyhow::{bail, Context, Result};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::io::{Read, Write};
+
+pub const ENVELOPE_VERSION: u32 = 1;
+
+// Every gzip stream starts with these two bytes, so load can tell a compressed file from JSON
+// whatever it is named.
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+// What save wrote: the JSON and the bytes on disk, which differ when the file is compressed.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SavedSize {
+    pub json: u64,
+    pub file: u64,
+}
+
+impl SavedSize {
+    // How many times smaller the file is than its JSON; 1 for an uncompressed file.
+    pub fn ratio(self) -> f64 {
+        self.json as f64 / self.file.max(1) as f64
+    }
+
+    pub fn describe(self) -> String {
+        if self.json == self.file {
+            format!("{} bytes", self.file)
+        } else {
+            format!("{} bytes ({} uncompressed, {:.1}x smaller)", self.file, self.json, self.ratio())
+        }
+    }
+}
+
+// On-disk format of a proof file. Keys are camelCase, so proof, publicValues and vkey keep the
+// names app.py and the deploy scripts already read. Files without a version are the legacy
+// {proof, publicValues, vkey} shape written before the envelope existed.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProofEnvelope {
+    pub version: u32,
+    // SP1 proof kind: "groth16" or "plonk" for on-chain proofs, or "core" or "compressed", whose
+    // proof is the bincode-serialized SP1ProofWithPublicValues
+    pub mode: String,
+    pub proof_system: String,
+    // How proof and publicValues are written; always "hex" so far
+    pub encoding: String,
+    // SP1_CIRCUIT_VERSION of the prover, which selects the on-chain verifier
+    #[serde(default)]
+    pub sp1_version: String,
+    // Unix seconds when the proof was written; 0 in legacy files
+    #[serde(default)]
+    pub created_at: u64,
+    pub vkey: Option<String>,
+    pub public_values: String,
+    pub proof: String,
+    #[serde(default)]
+    pub meta: Map<String, Value>,
+    // publicValues decoded into named fields, for people reading the file. Nothing checks
+    // against it; verify and contracts go by the hex
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub decoded_public_values: Option<Value>,
+}
+
+// The parts of a proof file that say what it can be verified with. SP1 proofs don't verify across
+// incompatible SP1 versions, so a file handed over months later carries these to fail with a
+// plain reason before any verification is attempted.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ProofHeader {
+    // ENVELOPE_VERSION the file was written with; 0 for legacy files
+    pub schema_version: u32,
+    // SP1_CIRCUIT_VERSION of the prover; empty in legacy files
+    pub sp1_version: String,
+    pub vkey: Option<String>,
+    pub mode: String,
+}
+
+impl ProofHeader {
+    // Fails if the file is from a newer envelope format, was made for another program key than
+    // vkey_hash, or by an SP1 version this build can't verify. Fields a legacy file lacks pass.
+    pub fn check(&self, vkey_hash: &str) -> Result<()> {
+        if self.schema_version > ENVELOPE_VERSION {
+            bail!(
+                "proof file version {} is newer than this build reads ({})",
+                self.schema_version,
+                ENVELOPE_VERSION
+            );
+        }
+        if let Some(vkey) = self.vkey.as_deref() {
+            if !vkey_matches_lock(vkey, vkey_hash) {
+                bail!("vkey mismatch: the proof is for {}, but the verifying key is {}", vkey, vkey_hash);
+            }
+        }
+        if !self.sp1_version.is_empty() && self.sp1_version != sp1_sdk::SP1_CIRCUIT_VERSION {
+            bail!(
+                "SP1 version mismatch: the proof was made with {}, but this build verifies {}",
+                self.sp1_version,
+                sp1_sdk::SP1_CIRCUIT_VERSION
+            );
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyProofFile {
+    proof: String,
+    public_values: String,
+    vkey: Option<String>,
+}
+
+impl ProofEnvelope {
+    pub fn groth16(proof: &[u8], public_values: &[u8], vkey: String, created_at: u64) -> Self {
+        Self::new("groth16", proof, public_values, vkey, created_at)
+    }
+
+    pub fn new(mode: &str, proof: &[u8], public_values: &[u8], vkey: String, created_at: u64) -> Self {
+        ProofEnvelope {
+            version: ENVELOPE_VERSION,
+            mode: mode.to_string(),
+            proof_system: "sp1".to_string(),
+            encoding: "hex".to_string(),
+            sp1_version: sp1_sdk::SP1_CIRCUIT_VERSION.to_string(),
+            created_at,
+            vkey: Some(vkey),
+            public_values: hex::encode(public_values),
+            proof: hex::encode(proof),
+            meta: Map::new(),
+            decoded_public_values: None,
+        }
+    }
+
+    // Legacy files come back as version 0 Groth16 envelopes, which is all evm ever wrote.
+    pub fn from_json(json: &str) -> Result<Self> {
+        let value: Value = serde_json::from_str(json)?;
+        if value.get("version").is_some() {
+            return Ok(serde_json::from_value(value)?);
+        }
+        let legacy: LegacyProofFile = serde_json::from_value(value)?;
+        Ok(ProofEnvelope {
+            version: 0,
+            mode: "groth16".to_string(),
+            proof_system: "sp1".to_string(),
+            encoding: "hex".to_string(),
+            sp1_version: String::new(),
+            created_at: 0,
+            vkey: legacy.vkey,
+            public_values: legacy.public_values,
+            proof: legacy.proof,
+            meta: Map::new(),
+            decoded_public_values: None,
+        })
+    }
+
+    pub fn header(&self) -> ProofHeader {
+        ProofHeader {
+            schema_version: self.version,
+            sp1_version: self.sp1_version.clone(),
+            vkey: self.vkey.clone(),
+            mode: self.mode.clone(),
+        }
+    }
+
+    // The file's contents, as save writes them.
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    // Reads plain and gzip-compressed proof files alike.
+    pub fn load(path: &str) -> Result<Self> {
+        let bytes = std::fs::read(path).with_context(|| format!("failed to read {}", path))?;
+        let json = if bytes.starts_with(&GZIP_MAGIC) {
+            let mut json = String::new();
+            GzDecoder::new(bytes.as_slice())
+                .read_to_string(&mut json)
+                .with_context(|| format!("failed to decompress {}", path))?;
+            json
+        } else {
+            String::from_utf8(bytes).with_context(|| format!("{} is not a proof file", path))?
+        };
+        Self::from_json(&json).with_context(|| format!("{} is not a proof file", path))
+    }
+
+    // The JSON, gzip-compressed when path ends in .gz.
+    fn file_bytes(json: &str, path: &str) -> Result<Vec<u8>> {
+        if !path.ends_with(".gz") {
+            return Ok(json.as_bytes().to_vec());
+        }
+        let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+        encoder.write_all(json.as_bytes())?;
+        Ok(encoder.finish()?)
+    }
+
+    // Writes a temporary file next to path and moves it into place, so a killed process never
+    // leaves a half-written proof behind. Without overwrite an existing file is an error, checked
+    // atomically through a hard link so two provers racing for the same name can't both win.
+    // A path ending in .gz is written gzip-compressed.
+    pub fn save(&self, path: &str, overwrite: bool) -> Result<SavedSize> {
+        let tmp = format!("{}.{}.tmp", path, std::process::id());
+        let json = self.to_json()?;
+        let bytes = Self::file_bytes(&json, path)?;
+        std::fs::write(&tmp, &bytes).with_context(|| format!("failed to write {}", tmp))?;
+        let placed = if overwrite {
+            std::fs::rename(&tmp, path)
+        } else {
+            std::fs::hard_link(&tmp, path).and_then(|()| std::fs::remove_file(&tmp))
+        };
+        if let Err(e) = placed {
+            let _ = std::fs::remove_file(&tmp);
+            if e.kind() == std::io::ErrorKind::AlreadyExists {
+                bail!("{} already exists; pass --overwrite to replace it", path);
+            }
+            return Err(e).with_context(|| format!("failed to write {}", path));
+        }
+        Ok(SavedSize {
+            json: json.len() as u64,
+            file: bytes.len() as u64,
+        })
+    }
+
+    pub fn proof_bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(self.proof.trim_start_matches("0x")).context("proof is not valid hex")
+    }
+
+    pub fn public_values_bytes(&self) -> Result<Vec<u8>> {
+        hex::decode(self.public_values.trim_start_matches("0x")).context("publicValues is not valid hex")
+    }
+
+    // aggregate marks its files with meta.aggregate; everything else is a passport proof.
+    pub fn output_kind(&self) -> OutputKind {
+        if self.meta.get("aggregate").and_then(Value::as_bool) == Some(true) {
+            OutputKind::Aggregate
+        } else {
+            OutputKind::Passport
+        }
+    }
+
+    pub fn public_outputs(&self) -> Result<PublicOutputs> {
+        PublicOutputs::decode(self.output_kind(), &self.public_values_bytes()?).context("failed to decode public values")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn header_check_names_the_mismatch() {
+        let vkey = format!("0x{}", "ab".repeat(32));
+        let envelope = ProofEnvelope::groth16(&[1], &[2], vkey.clone(), 0);
+        assert!(envelope.header().check(&vkey.to_uppercase()).is_ok());
+
+        let other = format!("0x{}", "cd".repeat(32));
+        let err = envelope.header().check(&other).unwrap_err().to_string();
+        assert!(err.starts_with("vkey mismatch"), "{}", err);
+
+        let old = ProofEnvelope {
+            sp1_version: "v1.0.0".to_string(),
+            ..envelope.clone()
+        };
+        assert!(old.header().check(&vkey).unwrap_err().to_string().starts_with("SP1 version mismatch"));
+
+        let legacy = ProofEnvelope::from_json(r#"{"proof": "01", "publicValues": "02"}"#).unwrap();
+        assert!(legacy.header().check(&other).is_ok());
+    }
+
+    #[test]
+    fn decoded_public_values_are_kept_only_when_set() {
+        let mut envelope = ProofEnvelope::groth16(&[1], &[2], "0x01".to_string(), 0);
+        assert!(!envelope.to_json().unwrap().contains("decodedPublicValues"));
+
+        envelope.decoded_public_values = Some(serde_json::json!({ "is_over_min_age": true }));
+        let json = envelope.to_json().unwrap();
+        assert!(json.contains("\"decodedPublicValues\""));
+        assert_eq!(ProofEnvelope::from_json(&json).unwrap(), envelope);
+    }
+
+    #[test]
+    fn save_refuses_to_clobber_without_overwrite() {
+        let dir = std::env::temp_dir().join(format!("envelope-save-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proof.json").to_string_lossy().into_owned();
+        let first = ProofEnvelope::groth16(&[1], &[2], "0x01".to_string(), 0);
+        let second = ProofEnvelope::groth16(&[3], &[4], "0x01".to_string(), 0);
+
+        first.save(&path, false).unwrap();
+        assert!(second.save(&path, false).unwrap_err().to_string().contains("--overwrite"));
+        assert_eq!(ProofEnvelope::load(&path).unwrap(), first);
+        second.save(&path, true).unwrap();
+        assert_eq!(ProofEnvelope::load(&path).unwrap(), second);
+        // Only the proof itself is left behind, no temporary files.
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn gzip_files_load_like_plain_ones() {
+        let dir = std::env::temp_dir().join(format!("envelope-gzip-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("proof.json.gz").to_string_lossy().into_owned();
+        let envelope = ProofEnvelope::groth16(&[7; 256], &[9; 64], "0x01".to_string(), 0);
+
+        let size = envelope.save(&path, false).unwrap();
+        let compressed = std::fs::read(&path).unwrap();
+        assert!(compressed.starts_with(&GZIP_MAGIC));
+        assert_eq!(size.file, compressed.len() as u64);
+        assert_eq!(size.json, envelope.to_json().unwrap().len() as u64);
+        assert!(size.ratio() > 1.0);
+        assert_eq!(ProofEnvelope::load(&path).unwrap(), envelope);
+
+        // Detection goes by content, so a renamed file still loads.
+        let renamed = dir.join("proof.json").to_string_lossy().into_owned();
+        std::fs::rename(&path, &renamed).unwrap();
+        assert_eq!(ProofEnvelope::load(&renamed).unwrap(), envelope);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -0,0 +1,1152 @@
+use crate::utils::{
+    evaluation_timestamp, parse_iso_date, parse_nationality, parse_wallet_address, validate_birth_year, validate_min_age,
+    MAX_REQUIRED_AGE,
+};
+use anyhow::{anyhow, bail, Context, Result};
+use passport_verifier_lib::groupings::MAX_NATIONALITY_SET_SIZE;
+use passport_verifier_lib::merkle::{MerkleTree, MAX_MERKLE_DEPTH};
+use passport_verifier_lib::csca::{IssuerCertificates, MAX_CSCA_ROOTS};
+use passport_verifier_lib::dg::DataGroups;
+use passport_verifier_lib::mrz::{parse_td3_mrz, td3_check_digits, transliterate_to_mrz};
+use passport_verifier_lib::oracle::{self, OracleSignature};
+use passport_verifier_lib::{
+    target_nationality_commitment, timestamp_to_date, CommitmentFields, CommitmentHash, Date, OtherPassport, MAX_OTHER_PASSPORTS,
+    Disclosures, DocumentNumber, DocumentSignature, GuestInput, MrzCheckDigits, Nationality, PassportAttributes, PassportAttributesBuilder,
+    SignatureAlgorithm, VerificationPolicy, WalletEntry, MAX_LINKED_WALLETS, MAX_WALLET_ADDRESS_BYTES,
+};
+use schemars::JsonSchema;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use sp1_sdk::SP1Stdin;
+use std::io::{BufRead, BufReader};
+use std::marker::PhantomData;
+
+// Host-side inputs to the guest: the passport JSON the OCR service and app send, the verifier's
+// requirements file, and the per-proof options. Every proving binary builds its stdin here, as
+// the one GuestInput that program/src/main.rs reads.
+
+#[derive(Deserialize, Clone, Default)]
+#[serde(rename_all = "camelCase")]
+pub struct PassportInput {
+    pub document_number: String,
+    pub birth_year: u16,
+    pub birth_month: u8,
+    pub birth_day: u8,
+    pub expiry_year: u16,
+    pub expiry_month: u8,
+    pub expiry_day: u8,
+    pub nationality: String,
+    pub name: String,
+    pub sex: String,
+    #[serde(default)]
+    pub document_number_check_digit: Option<u8>,
+    #[serde(default)]
+    pub birth_date_check_digit: Option<u8>,
+    #[serde(default)]
+    pub expiry_date_check_digit: Option<u8>,
+    #[serde(default)]
+    pub document_signature: Option<DocumentSignatureInput>,
+    #[serde(default)]
+    pub issuing_country: Option<String>,
+    // Hex EF.DG1 and EF.SOD from the chip; both are needed for is_data_group_consistent
+    #[serde(default)]
+    pub dg1: Option<String>,
+    #[serde(default)]
+    pub sod: Option<String>,
+    // Hex Document Signer Certificate from EF.SOD and the CSCA key that signed it, for
+    // is_trusted_issuer
+    #[serde(default)]
+    pub dsc_certificate: Option<String>,
+    #[serde(default)]
+    pub csca_algorithm: SignatureAlgorithm,
+    #[serde(default)]
+    pub csca_public_key: Option<String>,
+}
+
+// Hex-encoded chip data; only present when the passport was read over NFC.
+#[derive(Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentSignatureInput {
+    #[serde(default)]
+    pub algorithm: SignatureAlgorithm,
+    pub public_key: String,
+    pub signed_attributes: String,
+    pub signature: String,
+}
+
+// A time oracle's attestation as a file, e.g. the response of an oracle service. The proof is
+// evaluated at timestamp, which the guest only accepts with the oracle's signature over it.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SignedTimestamp {
+    pub timestamp: u64,
+    // SEC1 P-256 public key, hex
+    pub public_key: String,
+    // DER ECDSA signature over oracle::timestamp_digest(timestamp), hex
+    pub signature: String,
+}
+
+impl SignedTimestamp {
+    // Signs as a test oracle would, with a raw P-256 secret key.
+    pub fn sign(secret_key: &[u8; 32], timestamp: u64) -> Result<Self> {
+        let signed = oracle::sign_timestamp(secret_key, timestamp).context("invalid time oracle secret key")?;
+        Ok(SignedTimestamp {
+            timestamp,
+            public_key: format!("0x{}", hex::encode(&signed.public_key)),
+            signature: format!("0x{}", hex::encode(&signed.signature)),
+        })
+    }
+
+    pub fn oracle_signature(&self) -> Result<OracleSignature> {
+        Ok(OracleSignature {
+            public_key: hex::decode(self.public_key.trim_start_matches("0x")).context("time oracle public key is not valid hex")?,
+            signature: hex::decode(self.signature.trim_start_matches("0x")).context("time oracle signature is not valid hex")?,
+        })
+    }
+}
+
+// The flat input form of a passport, without check digits or chip data, e.g. to write a
+// passport from another source back out as stdin JSON.
+impl From<&PassportAttributes> for PassportInput {
+    fn from(passport: &PassportAttributes) -> Self {
+        PassportInput {
+            document_number: passport.document_number.as_str().to_string(),
+            birth_year: passport.date_of_birth.year,
+            birth_month: passport.date_of_birth.month,
+            birth_day: passport.date_of_birth.day,
+            expiry_year: passport.date_of_expiry.year,
+            expiry_month: passport.date_of_expiry.month,
+            expiry_day: passport.date_of_expiry.day,
+            nationality: passport.nationality.as_str().to_string(),
+            name: passport.name.clone(),
+            sex: passport.sex.clone(),
+            ..PassportInput::default()
+        }
+    }
+}
+
+// One passport plus the optional data the guest uses to check its integrity.
+#[derive(Clone, Debug)]
+pub struct PassportWitness {
+    pub passport: PassportAttributes,
+    pub mrz_check_digits: Option<MrzCheckDigits>,
+    pub document_signature: Option<DocumentSignature>,
+    pub issuing_country: Option<Nationality>,
+    pub data_groups: Option<DataGroups>,
+    pub issuer_certificates: Option<IssuerCertificates>,
+}
+
+impl PassportWitness {
+    // A passport read over NFC, whose chip signature the guest verifies.
+    pub fn signed(
+        passport: PassportAttributes,
+        document_signature: DocumentSignature,
+        issuing_country: Option<Nationality>,
+    ) -> Self {
+        PassportWitness {
+            passport,
+            mrz_check_digits: None,
+            document_signature: Some(document_signature),
+            issuing_country,
+            data_groups: None,
+            issuer_certificates: None,
+        }
+    }
+}
+
+// A bare passport: the guest commits mrz_checksums_valid and is_valid_signature as false.
+impl From<PassportAttributes> for PassportWitness {
+    fn from(passport: PassportAttributes) -> Self {
+        PassportWitness {
+            passport,
+            mrz_check_digits: None,
+            document_signature: None,
+            issuing_country: None,
+            data_groups: None,
+            issuer_certificates: None,
+        }
+    }
+}
+
+// The verifier's requirements file. Doc comments here become the descriptions in
+// verification_requirements.schema.json, which the requirements_schema binary generates.
+#[derive(Deserialize, JsonSchema)]
+#[serde(rename_all = "camelCase")]
+pub struct VerificationRequirements {
+    /// Wallet the identity is bound to, 0x-prefixed hex; mixed case must be a valid EIP-55 checksum
+    pub wallet_address: String,
+    /// Contract the proof is meant for, 0x-prefixed hex
+    pub verifier_address: String,
+    /// Minimum age in years, at most 150
+    #[schemars(range(max = MAX_REQUIRED_AGE))]
+    pub required_age: u16,
+    /// Minimum age in completed months, e.g. 198 for 16 years and 6 months; 0 for none
+    #[serde(default)]
+    pub min_age_months: u32,
+    /// Nationality to match, or "" for none. Normalized to the ICAO code while parsing, so "MY"
+    /// or "Malaysia" also work
+    #[serde(deserialize_with = "nationality_or_empty")]
+    pub required_nationality: String,
+    /// Sex to match as printed in the MRZ (M, F or X), or "" for none
+    #[schemars(regex(pattern = r"^[MFX]?$"))]
+    pub required_sex: String,
+    /// Days before the minimum age the holder may be and still pass within_grace
+    #[serde(default)]
+    pub grace_days: u16,
+    /// Country whose legal drinking age can_drink is checked against, or "" for none
+    #[serde(default)]
+    pub drinking_destination: String,
+    /// Upper age bound to prove, at most 150
+    #[serde(default)]
+    #[schemars(range(max = MAX_REQUIRED_AGE))]
+    pub max_age: Option<u16>,
+    /// 32-byte hex scope for the nullifier; all zeroes when the application doesn't use one
+    #[serde(default)]
+    pub external_nullifier: Option<String>,
+    /// Hash for the identity commitment
+    #[serde(default)]
+    #[schemars(schema_with = "commitment_hash_schema")]
+    pub commitment_hash: CommitmentHash,
+    /// Leading bytes of the identity hash to keep, 1 to 32; 32 (the whole hash) when absent
+    #[serde(default)]
+    #[schemars(range(min = 1, max = 32))]
+    pub commitment_bytes: Option<u8>,
+    /// Grouping from lib's groupings table the issuing state must belong to, e.g. "G7" or "VWP"
+    #[serde(default)]
+    pub alliance: String,
+    /// Hex csca::csca_key_hash of each trusted CSCA, e.g. from the ICAO master list
+    #[serde(default)]
+    #[schemars(length(max = MAX_CSCA_ROOTS))]
+    pub csca_roots: Vec<String>,
+    /// A hidden target nationality: lib's target_nationality_commitment as hex, in place of
+    /// requiredNationality
+    #[serde(default)]
+    pub target_nationality_commitment: Option<String>,
+    /// The hex 32-byte salt of a hidden target nationality
+    #[serde(default)]
+    pub target_nationality_salt: Option<String>,
+    /// Days the passport must stay valid after the proof date, e.g. 183 for six months; 0 for none
+    #[serde(default)]
+    pub min_validity_days: u16,
+}
+
+fn commitment_hash_schema(_: &mut schemars::SchemaGenerator) -> schemars::Schema {
+    schemars::json_schema!({
+        "type": "string",
+        "enum": ["sha256", "poseidon", "keccak256"],
+        "default": "sha256",
+    })
+}
+
+impl VerificationRequirements {
+    // Field-level checks that don't depend on the proof's options, each naming the JSON key that
+    // is wrong. check_options runs it, so every binary that proves does too.
+    pub fn validate(&self) -> Result<()> {
+        parse_wallet_address(&self.wallet_address).map_err(|e| anyhow!("walletAddress: {}", e))?;
+        parse_wallet_address(&self.verifier_address).map_err(|e| anyhow!("verifierAddress: {}", e))?;
+        validate_min_age(self.required_age).map_err(|e| anyhow!("requiredAge: {}", e))?;
+        if self.min_age_months > u32::from(MAX_REQUIRED_AGE) * 12 {
+            bail!("minAgeMonths is {}; the maximum is {}", self.min_age_months, u32::from(MAX_REQUIRED_AGE) * 12);
+        }
+        if let Some(max_age) = self.max_age {
+            validate_min_age(max_age).map_err(|e| anyhow!("maxAge: {}", e))?;
+        }
+        if !matches!(self.required_sex.as_str(), "" | "M" | "F" | "X") {
+            bail!("requiredSex is {:?}; expected M, F, X or an empty string", self.required_sex);
+        }
+        if let Some(bytes @ (0 | 33..)) = self.commitment_bytes {
+            bail!("commitmentBytes is {}; it must be between 1 and 32", bytes);
+        }
+        if self.csca_roots.len() > MAX_CSCA_ROOTS {
+            bail!("cscaRoots has {} entries; the guest accepts at most {}", self.csca_roots.len(), MAX_CSCA_ROOTS);
+        }
+        Ok(())
+    }
+}
+
+fn nationality_or_empty<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<String, D::Error> {
+    let nationality = String::deserialize(deserializer)?;
+    if nationality.trim().is_empty() {
+        return Ok(String::new());
+    }
+    parse_nationality(&nationality).map_err(serde::de::Error::custom)
+}
+
+// Per-proof choices that aren't part of the verifier's requirements file.
+#[derive(Clone, Debug, Default)]
+pub struct GuestOptions {
+    // Overrides maxAge from the requirements
+    pub max_age: Option<u16>,
+    pub verifier_id: Option<[u8; 32]>,
+    pub identity_fields: CommitmentFields,
+    pub disclosures: Disclosures,
+    pub expired: bool,
+    pub target_nationalities: Vec<String>,
+    pub blocked_nationalities: Vec<String>,
+    // Date the age checks are evaluated at instead of the proof timestamp
+    pub reference_date: Option<Date>,
+    // Currently bound wallet, for a proof that rebinds the identity to reqs.wallet_address
+    pub previous_wallet: Option<[u8; 20]>,
+    // Document numbers of a closed allowlist; the proof shows membership against its Merkle root
+    pub document_allowlist: Vec<String>,
+    // Commit requiredNationality only as a salted hash (targetNationalitySalt), not in plaintext
+    pub hide_target_nationality: bool,
+    // Wallets on other chains to tie to the identity alongside reqs.wallet_address
+    pub linked_wallets: Vec<WalletEntry>,
+    // How long after the proof timestamp verifiers should accept the proof; unbounded when None
+    pub valid_for_seconds: Option<u64>,
+    // Prove with a disclosed zero wallet, which the guest commits as undisclosed
+    pub allow_zero_wallet: bool,
+    // A time oracle's signature over the evaluation time, which the guest checks and commits the
+    // oracle's key hash for
+    pub time_oracle: Option<OracleSignature>,
+    // Check a stateless or refugee holder (XXA, XXB, XXC) against the nationality requirements as
+    // a national of the state that issued their travel document
+    pub stateless_by_issuer: bool,
+    // Commit a hash of every passport field for the verifier's audit trail
+    pub audit: bool,
+    // Commit which of target_nationalities matched, for verifiers that route on it
+    pub reveal_matched_nationality: bool,
+    // The holder's further passports, e.g. a dual national's second one. The guest proves over
+    // whichever first meets the requirements without committing which.
+    pub other_passports: Vec<PassportWitness>,
+}
+
+// Rejects combinations the guest would refuse, before paying for setup or proving.
+pub fn check_options(reqs: &VerificationRequirements, options: &GuestOptions) -> Result<()> {
+    reqs.validate()?;
+    if let Some(max_age) = options.max_age {
+        validate_min_age(max_age).map_err(|e| anyhow!("max_age: {}", e))?;
+    }
+    if reqs.commitment_hash == CommitmentHash::Poseidon && options.identity_fields != CommitmentFields::default() {
+        bail!("the Poseidon identity hash only supports the default identity fields (dob,nationality,names)");
+    }
+    for (what, codes) in [
+        ("target nationalities", &options.target_nationalities),
+        ("blocked nationalities", &options.blocked_nationalities),
+    ] {
+        if codes.len() > MAX_NATIONALITY_SET_SIZE {
+            bail!("{} has {} entries; the guest accepts at most {}", what, codes.len(), MAX_NATIONALITY_SET_SIZE);
+        }
+    }
+    if options.valid_for_seconds == Some(0) {
+        bail!("a proof valid for 0 seconds is expired as soon as it is made");
+    }
+    let zero_wallet = parse_wallet_address(&reqs.wallet_address).is_ok_and(|wallet| wallet == [0u8; 20]);
+    if zero_wallet && options.disclosures.wallet && !options.allow_zero_wallet {
+        bail!("walletAddress is the zero address, which the guest won't bind; pass --allow-zero to prove anyway");
+    }
+    if options.previous_wallet.is_some() && !options.disclosures.wallet {
+        bail!("a wallet rebind has to disclose the wallet it binds to");
+    }
+    if !options.linked_wallets.is_empty() && !options.disclosures.wallet {
+        bail!("linked wallets are only committed when the wallet is disclosed");
+    }
+    if options.linked_wallets.len() > MAX_LINKED_WALLETS {
+        bail!("{} linked wallets given; the guest accepts at most {}", options.linked_wallets.len(), MAX_LINKED_WALLETS);
+    }
+    if options.other_passports.len() > MAX_OTHER_PASSPORTS {
+        bail!("{} passports given; the guest accepts at most {}", options.other_passports.len() + 1, MAX_OTHER_PASSPORTS + 1);
+    }
+    if reqs.target_nationality_commitment.is_some() || options.hide_target_nationality {
+        if reqs.target_nationality_salt.is_none() {
+            bail!("a hidden target nationality needs targetNationalitySalt");
+        }
+        if !options.target_nationalities.is_empty() {
+            bail!("a hidden target nationality can't be combined with a list of target nationalities");
+        }
+    }
+    if reqs.target_nationality_commitment.is_some() && !reqs.required_nationality.is_empty() {
+        bail!("give either requiredNationality or targetNationalityCommitment, not both");
+    }
+    if options.expired && reqs.min_validity_days > 0 {
+        bail!("minValidityDays can't be combined with proving the passport has expired");
+    }
+    if options.hide_target_nationality && reqs.required_nationality.is_empty() {
+        bail!("hiding the target nationality needs a requiredNationality to hide");
+    }
+    Ok(())
+}
+
+// The (commitment, salt) the guest checks a hidden target against: the requirements' commitment,
+// or one made from requiredNationality when the caller asked to hide it.
+fn hidden_target_nationality(reqs: &VerificationRequirements, options: &GuestOptions) -> Result<Option<([u8; 32], [u8; 32])>> {
+    let Some(salt) = &reqs.target_nationality_salt else {
+        return Ok(None);
+    };
+    let salt = decode_hex_array(salt, "target nationality salt")?;
+    match &reqs.target_nationality_commitment {
+        Some(commitment) => Ok(Some((decode_hex_array(commitment, "target nationality commitment")?, salt))),
+        None if options.hide_target_nationality => {
+            Ok(Some((target_nationality_commitment(&reqs.required_nationality, &salt), salt)))
+        }
+        None => Ok(None),
+    }
+}
+
+// The VerificationPolicy the guest derives from these inputs and commits as policy_commitment.
+pub fn verification_policy(reqs: &VerificationRequirements, options: &GuestOptions) -> VerificationPolicy {
+    let allowed_nationalities = if !options.target_nationalities.is_empty() {
+        options.target_nationalities.clone()
+    } else if !reqs.required_nationality.is_empty() && !options.hide_target_nationality {
+        vec![reqs.required_nationality.clone()]
+    } else {
+        Vec::new()
+    };
+    VerificationPolicy {
+        min_age: reqs.required_age,
+        allowed_nationalities,
+        require_unexpired: !options.expired,
+    }
+}
+
+// Takes the minimum age and nationalities from a policy file in place of the requirements file's,
+// so the proof commits exactly that policy. Its expiry rule has to agree with --expired rather
+// than switch modes behind the caller's back.
+pub fn apply_policy(
+    policy: &VerificationPolicy,
+    reqs: &mut VerificationRequirements,
+    options: &mut GuestOptions,
+) -> Result<()> {
+    validate_min_age(policy.min_age).map_err(|e| anyhow!("minAge: {}", e))?;
+    if policy.require_unexpired == options.expired {
+        bail!(
+            "the policy's requireUnexpired is {}, which needs --expired {}",
+            policy.require_unexpired,
+            if options.expired { "left off" } else { "set" }
+        );
+    }
+    let mut nationalities = policy
+        .allowed_nationalities
+        .iter()
+        .map(|code| parse_nationality(code).map_err(|e| anyhow!("allowedNationalities: {}", e)))
+        .collect::<Result<Vec<_>>>()?;
+    reqs.required_age = policy.min_age;
+    reqs.required_nationality = if nationalities.len() == 1 { nationalities.remove(0) } else { String::new() };
+    options.target_nationalities = nationalities;
+    Ok(())
+}
+
+// Writes guest_input to the guest's stdin.
+pub fn guest_stdin(
+    witness: &PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+    now: u64,
+) -> Result<SP1Stdin> {
+    let mut stdin = SP1Stdin::new();
+    stdin.write(&guest_input(witness, reqs, options, now)?);
+    Ok(stdin)
+}
+
+// Builds the guest's single GuestInput.
+pub fn guest_input(
+    witness: &PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+    now: u64,
+) -> Result<GuestInput> {
+    for witness in std::iter::once(witness).chain(&options.other_passports) {
+        validate_birth_year(witness.passport.date_of_birth.year, timestamp_to_date(now).year).map_err(|e| anyhow!(e))?;
+        witness.passport.validate(&timestamp_to_date(now)).context("passport data is inconsistent")?;
+    }
+    if options.time_oracle.as_ref().is_some_and(|signature| !oracle::verify_timestamp(signature, now)) {
+        bail!("the time oracle's signature doesn't cover the evaluation time {}", now);
+    }
+    let wallet_array = parse_wallet_address(&reqs.wallet_address).context("invalid wallet address")?;
+    let verifier_array = parse_wallet_address(&reqs.verifier_address).context("invalid verifier address")?;
+    let external_nullifier: [u8; 32] = match &reqs.external_nullifier {
+        Some(scope) => decode_hex_array(scope, "external nullifier")?,
+        None => [0u8; 32],
+    };
+    let csca_roots = reqs
+        .csca_roots
+        .iter()
+        .map(|root| decode_hex_array::<32>(root, "CSCA root"))
+        .collect::<Result<Vec<_>>>()?;
+    let reference_timestamp = match &options.reference_date {
+        Some(date) => evaluation_timestamp(Some(date)).context("invalid reference date")?,
+        None => now,
+    };
+    let allowlist = options
+        .document_allowlist
+        .iter()
+        .map(|number| {
+            DocumentNumber::new(number.as_str())
+                .map(|number| number.as_str().to_string())
+                .map_err(|e| anyhow!("document allowlist: {}", e))
+        })
+        .collect::<Result<Vec<_>>>()?;
+    let allowlist = MerkleTree::new(&allowlist);
+    let hidden_target = hidden_target_nationality(reqs, options)?;
+
+    let mut input = GuestInput {
+        passport: witness.passport.clone(),
+        wallet_address: wallet_array,
+        verifier_address: verifier_array,
+        current_timestamp: now,
+        min_age: reqs.required_age,
+        target_nationality: if options.target_nationalities.is_empty() && hidden_target.is_none() {
+            reqs.required_nationality.clone()
+        } else {
+            String::new()
+        },
+        target_sex: reqs.required_sex.clone(),
+        mrz_check_digits: witness.mrz_check_digits.clone(),
+        grace_days: reqs.grace_days,
+        document_signature: witness.document_signature.clone(),
+        drinking_destination: reqs.drinking_destination.clone(),
+        max_age: options.max_age.or(reqs.max_age),
+        external_nullifier,
+        commitment_hash: reqs.commitment_hash,
+        issuing_country: witness.issuing_country.clone(),
+        alliance: reqs.alliance.clone(),
+        verifier_id: options.verifier_id,
+        identity_fields: options.identity_fields,
+        disclosures: options.disclosures,
+        expired_mode: options.expired,
+        allowed_nationalities: options.target_nationalities.clone(),
+        blocked_nationalities: options.blocked_nationalities.clone(),
+        data_groups: witness.data_groups.clone(),
+        csca_roots,
+        issuer_certificates: witness.issuer_certificates.clone(),
+        reference_timestamp,
+        previous_wallet: options.previous_wallet,
+        commitment_bytes: reqs.commitment_bytes.unwrap_or(32),
+        min_age_months: reqs.min_age_months,
+        allowlist_root: allowlist.root(),
+        allowlist_path: allowlist.path(witness.passport.document_number.as_str()),
+        target_nationality_commitment: hidden_target,
+        linked_wallets: options.linked_wallets.clone(),
+        min_validity_days: reqs.min_validity_days,
+        valid_until_timestamp: match options.valid_for_seconds {
+            Some(seconds) => now.checked_add(seconds).context("the proof's validity window overflows")?,
+            None => 0,
+        },
+        time_oracle: options.time_oracle.clone(),
+        stateless_by_issuer: options.stateless_by_issuer,
+        audit: options.audit,
+        reveal_matched_nationality: options.reveal_matched_nationality,
+        other_passports: options
+            .other_passports
+            .iter()
+            .map(|other| OtherPassport {
+                passport: other.passport.clone(),
+                mrz_check_digits: other.mrz_check_digits.clone(),
+                document_signature: other.document_signature.clone(),
+                issuing_country: other.issuing_country.clone(),
+                data_groups: other.data_groups.clone(),
+                issuer_certificates: other.issuer_certificates.clone(),
+                allowlist_path: allowlist.path(other.passport.document_number.as_str()),
+            })
+            .collect(),
+    };
+    // Inputs to a network prover leave the machine, so the name is only sent when a commitment
+    // is derived from it.
+    if !needs_name(&input) {
+        input.passport.name = String::new();
+        for other in &mut input.other_passports {
+            other.passport.name = String::new();
+        }
+    }
+    validate_input_sizes(&input)?;
+    Ok(input)
+}
+
+// Upper bounds on what the guest is sent. Every byte is read and hashed inside the zkVM, so an
+// oversized field costs cycles and proving time without making the proof any more useful. The
+// limits leave headroom over real documents: a TD3 name is at most 39 characters, DG1 is under
+// 100 bytes, and an RSA-4096 SOD with its certificate is a few kilobytes.
+const MAX_DOCUMENT_NUMBER_LEN: usize = 20;
+const MAX_NAME_LEN: usize = 64;
+const MAX_SHORT_FIELD_LEN: usize = 16;
+const MAX_KEY_BYTES: usize = 1024;
+const MAX_SIGNED_ATTRIBUTES_BYTES: usize = 2048;
+const MAX_DG1_BYTES: usize = 256;
+const MAX_SOD_BYTES: usize = 16 * 1024;
+const MAX_CERTIFICATE_BYTES: usize = 8 * 1024;
+
+// Rejects a GuestInput with a field longer than the guest should ever need, naming the field.
+pub fn validate_input_sizes(input: &GuestInput) -> Result<()> {
+    let check = |what: &str, len: usize, max: usize| {
+        if len > max {
+            bail!("{} is {} bytes; the guest accepts at most {}", what, len, max);
+        }
+        Ok(())
+    };
+    check("document number", input.passport.document_number.as_str().len(), MAX_DOCUMENT_NUMBER_LEN)?;
+    check("nationality", input.passport.nationality.as_str().len(), 3)?;
+    check("name", input.passport.name.len(), MAX_NAME_LEN)?;
+    check("sex", input.passport.sex.len(), 1)?;
+    check("target nationality", input.target_nationality.len(), 3)?;
+    check("target sex", input.target_sex.len(), 1)?;
+    check("drinking destination", input.drinking_destination.len(), MAX_SHORT_FIELD_LEN)?;
+    check("alliance", input.alliance.len(), MAX_SHORT_FIELD_LEN)?;
+    if let Some(country) = &input.issuing_country {
+        check("issuing country", country.as_str().len(), 3)?;
+    }
+    for (what, codes) in [
+        ("target nationalities", &input.allowed_nationalities),
+        ("blocked nationalities", &input.blocked_nationalities),
+    ] {
+        if codes.len() > MAX_NATIONALITY_SET_SIZE {
+            bail!("{} has {} entries; the guest accepts at most {}", what, codes.len(), MAX_NATIONALITY_SET_SIZE);
+        }
+        for code in codes {
+            check(what, code.len(), 3)?;
+        }
+    }
+    if input.csca_roots.len() > MAX_CSCA_ROOTS {
+        bail!("cscaRoots has {} entries; the guest accepts at most {}", input.csca_roots.len(), MAX_CSCA_ROOTS);
+    }
+    if input.linked_wallets.len() > MAX_LINKED_WALLETS {
+        bail!("{} linked wallets given; the guest accepts at most {}", input.linked_wallets.len(), MAX_LINKED_WALLETS);
+    }
+    for wallet in &input.linked_wallets {
+        check("linked wallet address", wallet.address.len(), MAX_WALLET_ADDRESS_BYTES)?;
+    }
+    if let Some(path) = &input.allowlist_path {
+        if path.siblings.len() > MAX_MERKLE_DEPTH {
+            bail!("allowlist path is {} levels deep; the guest accepts at most {}", path.siblings.len(), MAX_MERKLE_DEPTH);
+        }
+    }
+    if let Some(signature) = &input.document_signature {
+        check("document signer public key", signature.public_key.len(), MAX_KEY_BYTES)?;
+        check("signed attributes", signature.signed_attributes.len(), MAX_SIGNED_ATTRIBUTES_BYTES)?;
+        check("document signature", signature.signature.len(), MAX_KEY_BYTES)?;
+    }
+    if let Some(data_groups) = &input.data_groups {
+        check("DG1", data_groups.dg1.len(), MAX_DG1_BYTES)?;
+        check("SOD", data_groups.sod.len(), MAX_SOD_BYTES)?;
+    }
+    if let Some(certificates) = &input.issuer_certificates {
+        check("document signer certificate", certificates.dsc.len(), MAX_CERTIFICATE_BYTES)?;
+        check("CSCA public key", certificates.csca_public_key.len(), MAX_KEY_BYTES)?;
+    }
+    if input.other_passports.len() > MAX_OTHER_PASSPORTS {
+        bail!("{} passports given; the guest accepts at most {}", input.other_passports.len() + 1, MAX_OTHER_PASSPORTS + 1);
+    }
+    for other in &input.other_passports {
+        validate_input_sizes(&other.clone().in_place_of(input))?;
+    }
+    Ok(())
+}
+
+// The name goes into the identity hash when NAMES is selected, and through the default identity
+// hash into a scoped nullifier and the audience and wallet bindings. DG1 is compared against it.
+// All of these hash it, so no output carries the name itself.
+fn needs_name(input: &GuestInput) -> bool {
+    input.identity_fields.contains(CommitmentFields::NAMES)
+        || input.external_nullifier != [0u8; 32]
+        || input.verifier_id.is_some()
+        || !input.linked_wallets.is_empty()
+        || input.data_groups.is_some()
+        || input.other_passports.iter().any(|other| other.data_groups.is_some())
+        || input.audit
+}
+
+/// Passport fields given directly instead of as JSON, shared by the binaries that read a passport.
+/// Flags override environment variables, which override the JSON. Prefer the environment for
+/// personal data since flags are visible to other users in `ps`.
+#[derive(clap::Args)]
+pub struct PassportArgs {
+    #[arg(long, env = "PASSPORT_DOCUMENT_NUMBER")]
+    pub document_number: Option<String>,
+    /// Date of birth as YYYY-MM-DD
+    #[arg(long, env = "PASSPORT_BIRTH_DATE")]
+    pub birth_date: Option<String>,
+    /// Date of expiry as YYYY-MM-DD
+    #[arg(long, env = "PASSPORT_EXPIRY_DATE")]
+    pub expiry_date: Option<String>,
+    #[arg(long, env = "PASSPORT_NATIONALITY")]
+    pub nationality: Option<String>,
+    #[arg(long, env = "PASSPORT_NAME")]
+    pub name: Option<String>,
+    #[arg(long, env = "PASSPORT_SEX")]
+    pub sex: Option<String>,
+}
+
+impl PassportArgs {
+    // With every field the commitments depend on, no passport JSON is read at all.
+    pub fn is_complete(&self) -> bool {
+        self.document_number.is_some()
+            && self.birth_date.is_some()
+            && self.expiry_date.is_some()
+            && self.nationality.is_some()
+            && self.name.is_some()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.document_number.is_none()
+            && self.birth_date.is_none()
+            && self.expiry_date.is_none()
+            && self.nationality.is_none()
+            && self.name.is_none()
+            && self.sex.is_none()
+    }
+
+    pub fn apply(&self, input: &mut PassportInput) -> Result<()> {
+        if let Some(document_number) = &self.document_number {
+            input.document_number = document_number.clone();
+        }
+        if let Some(birth_date) = &self.birth_date {
+            let date = parse_iso_date(birth_date).map_err(|e| anyhow!("birth date: {}", e))?;
+            (input.birth_year, input.birth_month, input.birth_day) = (date.year, date.month, date.day);
+        }
+        if let Some(expiry_date) = &self.expiry_date {
+            let date = parse_iso_date(expiry_date).map_err(|e| anyhow!("expiry date: {}", e))?;
+            (input.expiry_year, input.expiry_month, input.expiry_day) = (date.year, date.month, date.day);
+        }
+        if let Some(nationality) = &self.nationality {
+            input.nationality = nationality.clone();
+        }
+        // Typed by hand, so brought into the form an MRZ or chip read of the same passport gives.
+        if let Some(name) = &self.name {
+            input.name = transliterate_to_mrz(name);
+            if input.name.is_empty() {
+                bail!("name {:?} has no letters the MRZ can carry; give the Latin name printed in the passport", name);
+            }
+        }
+        if let Some(sex) = &self.sex {
+            input.sex = sex.clone();
+        }
+        Ok(())
+    }
+
+    // The passport from the flags alone, through the same date and field checks as JSON input.
+    pub fn to_passport_attributes(&self) -> Result<PassportAttributes> {
+        if !self.is_complete() {
+            bail!("--document-number, --birth-date, --expiry-date, --nationality and --name are all required");
+        }
+        let mut input = PassportInput::default();
+        self.apply(&mut input)?;
+        Ok(to_passport_witness(input)?.passport)
+    }
+
+    // For the binaries that take an optional passport file: the file with these fields applied on
+    // top, or the fields alone when there is no file.
+    pub fn read_passport(&self, path: Option<&str>) -> Result<PassportAttributes> {
+        match path {
+            Some(path) => {
+                let mut input: PassportInput = read_json(path, "passport JSON")?;
+                self.apply(&mut input)?;
+                Ok(to_passport_witness(input)?.passport)
+            }
+            None if self.is_empty() => bail!("give a passport file or the passport fields"),
+            None => self.to_passport_attributes(),
+        }
+    }
+}
+
+// Parses a JSON file, or stdin when path is "-" so a parent process can pipe inputs in. serde's
+// message names the field that failed, e.g. "missing field `birthYear`".
+pub fn read_json<T: DeserializeOwned>(path: &str, what: &str) -> Result<T> {
+    if path == "-" {
+        return serde_json::from_reader(std::io::stdin().lock())
+            .with_context(|| format!("failed to parse {} from stdin", what));
+    }
+    let file = std::fs::File::open(path).with_context(|| format!("failed to open {} {}", what, path))?;
+    serde_json::from_reader(BufReader::new(file)).with_context(|| format!("failed to parse {} {}", what, path))
+}
+
+// Reads a file of records one at a time, so batch never holds a large input in memory: a JSON
+// array, or newline-delimited JSON (one record per line) when path ends in .jsonl or .ndjson.
+// "-" reads a JSON array from stdin. Records must be JSON objects. A malformed line fails only
+// that line; a malformed array element ends the stream, since the rest can't be found reliably.
+pub struct JsonRecords<T> {
+    reader: Box<dyn BufRead + Send>,
+    jsonl: bool,
+    what: String,
+    // Array elements or lines read so far, for error messages
+    position: usize,
+    started: bool,
+    done: bool,
+    _record: PhantomData<T>,
+}
+
+impl<T: DeserializeOwned> JsonRecords<T> {
+    pub fn open(path: &str, what: &str) -> Result<Self> {
+        if path == "-" {
+            return Ok(Self::from_reader(BufReader::new(std::io::stdin()), false, what));
+        }
+        let file = std::fs::File::open(path).with_context(|| format!("failed to open {} {}", what, path))?;
+        let jsonl = path.ends_with(".jsonl") || path.ends_with(".ndjson");
+        Ok(Self::from_reader(BufReader::new(file), jsonl, what))
+    }
+
+    pub fn from_reader(reader: impl BufRead + Send + 'static, jsonl: bool, what: &str) -> Self {
+        JsonRecords {
+            reader: Box::new(reader),
+            jsonl,
+            what: what.to_string(),
+            position: 0,
+            started: false,
+            done: false,
+            _record: PhantomData,
+        }
+    }
+
+    fn next_line(&mut self) -> Result<Option<T>> {
+        let mut line = String::new();
+        loop {
+            line.clear();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Ok(None);
+            }
+            self.position += 1;
+            if !line.trim().is_empty() {
+                break;
+            }
+        }
+        let record = serde_json::from_str(line.trim())
+            .with_context(|| format!("failed to parse {} line {}", self.what, self.position))?;
+        Ok(Some(record))
+    }
+
+    fn next_element(&mut self) -> Result<Option<T>> {
+        if !self.started {
+            if self.peek_token()? != Some(b'[') {
+                bail!("{} must be a JSON array, or .jsonl with one record per line", self.what);
+            }
+            self.reader.consume(1);
+            self.started = true;
+        }
+        match self.peek_token()? {
+            Some(b']') => {
+                self.reader.consume(1);
+                if self.peek_token()?.is_some() {
+                    bail!("{} has data after its closing ]", self.what);
+                }
+                return Ok(None);
+            }
+            Some(b',') if self.position > 0 => self.reader.consume(1),
+            None => bail!("{} ends before its closing ]", self.what),
+            Some(_) if self.position > 0 => bail!("{} expects , or ] after record {}", self.what, self.position - 1),
+            Some(_) => {}
+        }
+        // Deserializing an object stops at its closing brace, so the reader is left at the separator.
+        let record = T::deserialize(&mut serde_json::Deserializer::from_reader(&mut self.reader))
+            .with_context(|| format!("failed to parse {} record {}", self.what, self.position))?;
+        self.position += 1;
+        Ok(Some(record))
+    }
+
+    // Skips whitespace and returns the next byte without consuming it; None at the end of input.
+    fn peek_token(&mut self) -> Result<Option<u8>> {
+        loop {
+            match self.reader.fill_buf()?.first().copied() {
+                Some(byte) if byte.is_ascii_whitespace() => self.reader.consume(1),
+                byte => return Ok(byte),
+            }
+        }
+    }
+}
+
+impl<T: DeserializeOwned> Iterator for JsonRecords<T> {
+    type Item = Result<T>;
+
+    fn next(&mut self) -> Option<Result<T>> {
+        if self.done {
+            return None;
+        }
+        let record = if self.jsonl { self.next_line() } else { self.next_element() };
+        match record {
+            Ok(Some(record)) => Some(Ok(record)),
+            Ok(None) => {
+                self.done = true;
+                None
+            }
+            Err(e) => {
+                self.done = !self.jsonl;
+                Some(Err(e))
+            }
+        }
+    }
+}
+
+// "0x"-prefixed or bare hex that must decode to exactly N bytes.
+pub fn decode_hex_array<const N: usize>(value: &str, what: &str) -> Result<[u8; N]> {
+    let bytes = hex::decode(value.trim_start_matches("0x")).with_context(|| format!("{} is not valid hex", what))?;
+    let len = bytes.len();
+    bytes
+        .try_into()
+        .map_err(|_| anyhow!("{} must be {} bytes, got {}", what, N, len))
+}
+
+// A pasted MRZ carries its own check digits, so the guest re-checks them against the parsed fields.
+pub fn mrz_witness(line1: &str, line2: &str, current: &Date) -> Result<PassportWitness> {
+    let passport = parse_td3_mrz(line1, line2, current).context("invalid MRZ")?;
+    let check_digits = td3_check_digits(line2).context("invalid MRZ")?;
+    Ok(PassportWitness {
+        passport,
+        mrz_check_digits: Some(check_digits),
+        document_signature: None,
+        issuing_country: Nationality::new(line1.trim().get(2..5).unwrap_or_default().replace('<', "")).ok(),
+        data_groups: None,
+        issuer_certificates: None,
+    })
+}
+
+pub fn to_passport_witness(input: PassportInput) -> Result<PassportWitness> {
+    // Without all three digits the guest commits mrz_checksums_valid = false.
+    let mrz_check_digits = match (
+        input.document_number_check_digit,
+        input.birth_date_check_digit,
+        input.expiry_date_check_digit,
+    ) {
+        (Some(document_number), Some(date_of_birth), Some(date_of_expiry)) => Some(MrzCheckDigits {
+            document_number,
+            date_of_birth,
+            date_of_expiry,
+        }),
+        _ => None,
+    };
+
+    let document_signature = input
+        .document_signature
+        .map(|sig| -> Result<DocumentSignature> {
+            Ok(DocumentSignature {
+                algorithm: sig.algorithm,
+                public_key: hex::decode(sig.public_key.trim_start_matches("0x"))
+                    .context("document signer public key is not valid hex")?,
+                signed_attributes: hex::decode(sig.signed_attributes.trim_start_matches("0x"))
+                    .context("signed attributes are not valid hex")?,
+                signature: hex::decode(sig.signature.trim_start_matches("0x"))
+                    .context("document signature is not valid hex")?,
+            })
+        })
+        .transpose()?;
+
+    let passport = PassportAttributesBuilder::new()
+        .document_number(input.document_number)
+        .birth_date(
+            Date::new(input.birth_year, input.birth_month, input.birth_day).context("invalid date of birth")?,
+        )
+        .expiry_date(
+            Date::new(input.expiry_year, input.expiry_month, input.expiry_day).context("invalid date of expiry")?,
+        )
+        .nationality(input.nationality)
+        .name(input.name)
+        .sex(input.sex)
+        .build()
+        .map_err(|errors| {
+            let messages: Vec<String> = errors.iter().map(|e| e.to_string()).collect();
+            anyhow!("invalid passport: {}", messages.join("; "))
+        })?;
+    let issuing_country = input
+        .issuing_country
+        .filter(|country| !country.is_empty())
+        .map(|country| Nationality::new(country).context("invalid issuing country"))
+        .transpose()?;
+    let data_groups = match (input.dg1, input.sod) {
+        (Some(dg1), Some(sod)) => Some(DataGroups {
+            dg1: hex::decode(dg1.trim_start_matches("0x")).context("DG1 is not valid hex")?,
+            sod: hex::decode(sod.trim_start_matches("0x")).context("SOD is not valid hex")?,
+        }),
+        (None, None) => None,
+        _ => bail!("dg1 and sod must be given together"),
+    };
+    let issuer_certificates = match (input.dsc_certificate, input.csca_public_key) {
+        (Some(dsc), Some(csca_public_key)) => Some(IssuerCertificates {
+            dsc: hex::decode(dsc.trim_start_matches("0x")).context("DSC certificate is not valid hex")?,
+            csca_algorithm: input.csca_algorithm,
+            csca_public_key: hex::decode(csca_public_key.trim_start_matches("0x"))
+                .context("CSCA public key is not valid hex")?,
+        }),
+        (None, None) => None,
+        _ => bail!("dscCertificate and cscaPublicKey must be given together"),
+    };
+
+    Ok(PassportWitness {
+        passport,
+        mrz_check_digits,
+        document_signature,
+        issuing_country,
+        data_groups,
+        issuer_certificates,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use passport_verifier_lib::DocumentNumber;
+
+    fn sample_passport() -> PassportAttributes {
+        PassportAttributes {
+            document_number: DocumentNumber::new("L898902C3").unwrap(),
+            date_of_birth: Date::new(1974, 8, 12).unwrap(),
+            date_of_expiry: Date::new(2030, 4, 15).unwrap(),
+            nationality: Nationality::new("UTO").unwrap(),
+            name: "ANNA MARIA ERIKSSON".to_string(),
+            sex: "F".to_string(),
+        }
+    }
+
+    // An 18+ check with no nationality or sex requirement.
+    fn sample_requirements() -> VerificationRequirements {
+        serde_json::from_value(serde_json::json!({
+            "walletAddress": format!("0x{}", "00".repeat(20)),
+            "verifierAddress": format!("0x{}", "00".repeat(20)),
+            "requiredAge": 18,
+            "requiredNationality": "",
+            "requiredSex": "",
+        }))
+        .unwrap()
+    }
+
+    // Catches a field added to PassportAttributes but not carried through PassportInput.
+    #[test]
+    fn passport_input_round_trips() {
+        let passport = sample_passport();
+        let witness = to_passport_witness(PassportInput::from(&passport)).unwrap();
+        assert_eq!(format!("{:?}", witness.passport), format!("{:?}", passport));
+        assert!(witness.mrz_check_digits.is_none());
+        assert!(witness.document_signature.is_none());
+    }
+
+    #[test]
+    fn name_is_only_sent_when_a_commitment_uses_it() {
+        let passport = sample_passport();
+        let reqs = sample_requirements();
+        let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+        let witness = PassportWitness::from(passport);
+        let name = |options: &GuestOptions| guest_input(&witness, &reqs, options, now).unwrap().passport.name;
+
+        // The default identity hash covers the name.
+        assert_eq!(name(&GuestOptions::default()), "ANNA MARIA ERIKSSON");
+        let without_names = GuestOptions {
+            identity_fields: CommitmentFields::DATE_OF_BIRTH | CommitmentFields::NATIONALITY,
+            ..GuestOptions::default()
+        };
+        assert_eq!(name(&without_names), "");
+        let scoped = GuestOptions {
+            verifier_id: Some([1u8; 32]),
+            ..without_names
+        };
+        assert_eq!(name(&scoped), "ANNA MARIA ERIKSSON");
+    }
+
+    #[test]
+    fn oversized_inputs_are_rejected_before_proving() {
+        let passport = sample_passport();
+        let reqs = sample_requirements();
+        let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+        let input = guest_input(&PassportWitness::from(passport), &reqs, &GuestOptions::default(), now).unwrap();
+        assert!(validate_input_sizes(&input).is_ok());
+
+        let mut long_name = input.clone();
+        long_name.passport.name = "A".repeat(MAX_NAME_LEN + 1);
+        let error = validate_input_sizes(&long_name).unwrap_err().to_string();
+        assert!(error.contains("name is 65 bytes"), "{}", error);
+
+        let mut long_sex = input.clone();
+        long_sex.target_sex = "FEMALE".to_string();
+        assert!(validate_input_sizes(&long_sex).is_err());
+
+        let mut huge_sod = input;
+        huge_sod.data_groups = Some(DataGroups {
+            dg1: vec![0; 93],
+            sod: vec![0; MAX_SOD_BYTES + 1],
+        });
+        assert!(validate_input_sizes(&huge_sod).is_err());
+    }
+
+    #[test]
+    fn policy_replaces_age_and_nationality_rules() {
+        let mut reqs: VerificationRequirements = serde_json::from_value(serde_json::json!({
+            "walletAddress": "",
+            "verifierAddress": "",
+            "requiredAge": 0,
+            "requiredNationality": "USA",
+            "requiredSex": "",
+        }))
+        .unwrap();
+        let mut options = GuestOptions::default();
+        let policy = VerificationPolicy {
+            min_age: 21,
+            allowed_nationalities: vec!["Malaysia".to_string()],
+            require_unexpired: true,
+        };
+        apply_policy(&policy, &mut reqs, &mut options).unwrap();
+        assert_eq!((reqs.required_age, reqs.required_nationality.as_str()), (21, "MYS"));
+        assert_eq!(verification_policy(&reqs, &options).allowed_nationalities, ["MYS"]);
+
+        let expired_only = VerificationPolicy {
+            require_unexpired: false,
+            ..policy
+        };
+        assert!(apply_policy(&expired_only, &mut reqs, &mut options).is_err());
+    }
+
+    #[test]
+    fn required_nationality_is_normalized() {
+        let parse = |nationality: &str| {
+            serde_json::from_value::<VerificationRequirements>(serde_json::json!({
+                "walletAddress": "",
+                "verifierAddress": "",
+                "requiredAge": 18,
+                "requiredNationality": nationality,
+                "requiredSex": "",
+            }))
+            .map(|reqs| reqs.required_nationality)
+        };
+        assert_eq!(parse("MY").unwrap(), "MYS");
+        assert_eq!(parse("germany").unwrap(), "D");
+        assert_eq!(parse("").unwrap(), "");
+        assert!(parse("MYSS").is_err());
+    }
+
+    #[test]
+    fn malformed_requirements_fail_with_a_descriptive_error() {
+        let path = std::env::temp_dir().join(format!("requirements-{}.json", std::process::id()));
+        let read = |json: serde_json::Value| {
+            std::fs::write(&path, json.to_string()).unwrap();
+            read_json::<VerificationRequirements>(path.to_str().unwrap(), "requirements").and_then(|reqs| reqs.validate())
+        };
+        let missing_age = read(serde_json::json!({
+            "walletAddress": "0x24ad7ea77bb5d67a7e630b35c6c58e239bb0e381",
+            "verifierAddress": "0x0000000000000000000000000000000000000000",
+            "requiredNationality": "MYS",
+            "requiredSex": "M",
+        }));
+        let bad_wallet = read(serde_json::json!({
+            "walletAddress": "0x1234",
+            "verifierAddress": "0x0000000000000000000000000000000000000000",
+            "requiredAge": 18,
+            "requiredNationality": "MYS",
+            "requiredSex": "M",
+        }));
+        std::fs::remove_file(&path).unwrap();
+
+        let missing_age = format!("{:#}", missing_age.unwrap_err());
+        assert!(missing_age.contains("failed to parse requirements"), "{}", missing_age);
+        assert!(missing_age.contains("missing field `requiredAge`"), "{}", missing_age);
+        assert!(format!("{:#}", bad_wallet.unwrap_err()).starts_with("walletAddress: "));
+    }
+
+    #[test]
+    fn records_stream_from_an_array_or_one_per_line() {
+        let read = |input: &'static str, jsonl: bool| -> Vec<Result<u64, String>> {
+            JsonRecords::<serde_json::Value>::from_reader(input.as_bytes(), jsonl, "records")
+                .map(|record| record.map(|r| r["id"].as_u64().unwrap()).map_err(|e| format!("{:#}", e)))
+                .collect()
+        };
+        assert_eq!(read(" [ {\"id\": 0},\n{\"id\": 1} ,{\"id\":2}]\n", false), vec![Ok(0), Ok(1), Ok(2)]);
+        assert!(read("[ ]", false).is_empty());
+        assert_eq!(read("{\"id\": 0}\n\n{\"id\": 1}\n", true), vec![Ok(0), Ok(1)]);
+
+        // A bad line fails alone; a bad array element ends the stream.
+        let lines = read("{\"id\": 0}\nnot json\n{\"id\": 2}", true);
+        assert_eq!(lines.len(), 3);
+        assert!(lines[1].as_ref().unwrap_err().contains("line 2"), "{:?}", lines[1]);
+        assert_eq!(lines[2], Ok(2));
+        let array = read("[{\"id\": 0} {\"id\": 1}]", false);
+        assert_eq!(array.len(), 2);
+        assert!(array[1].as_ref().unwrap_err().contains("expects , or ]"), "{:?}", array[1]);
+
+        assert!(read("{\"id\": 0}", false)[0].as_ref().unwrap_err().contains("must be a JSON array"));
+        assert!(read("[{\"id\": 0}", false)[1].as_ref().unwrap_err().contains("before its closing ]"));
+        assert!(read("[{\"id\": 0}] x", false)[1].as_ref().unwrap_err().contains("after its closing ]"));
+    }
+}
@@ -1 +1,8 @@
+#![recursion_limit = "256"]
+
 pub mod utils;
+pub mod public_values;
+pub mod envelope;
+pub mod inputs;
+pub mod prover;
+pub mod qr;
@@ -0,0 +1,179 @@
+use crate::inputs::{check_options, guest_input, guest_stdin, GuestOptions, PassportWitness, VerificationRequirements};
+use crate::public_values::decode_public_values;
+use crate::utils::{assert_valid_elf, evaluation_timestamp, prover_client, setup_cached};
+use anyhow::{anyhow, bail, Context, Result};
+use passport_verifier_lib::evaluate::evaluate_passport;
+use passport_verifier_lib::{
+    CommitmentHash, Date, Disclosures, PassportAttributes, PassportVerificationOutput,
+};
+use sp1_sdk::{EnvProver, SP1ProofWithPublicValues, SP1ProvingKey, SP1VerifyingKey};
+use std::sync::OnceLock;
+
+// Host-side API for services that prove or verify in-process instead of running the binaries.
+// The ELF and the prover setup stay behind it: the first call runs setup_cached, and later
+// calls reuse the same keys.
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+struct PassportProver {
+    client: EnvProver,
+    pk: SP1ProvingKey,
+    vk: SP1VerifyingKey,
+}
+
+fn prover() -> &'static PassportProver {
+    static PROVER: OnceLock<PassportProver> = OnceLock::new();
+    PROVER.get_or_init(|| {
+        assert_valid_elf(PASSPORT_ELF);
+        let client = prover_client();
+        let (pk, vk) = setup_cached(&client, PASSPORT_ELF);
+        PassportProver { client, pk, vk }
+    })
+}
+
+// The key proofs from this module verify against; vk.bytes32() is the on-chain program key.
+pub fn verifying_key() -> &'static SP1VerifyingKey {
+    &prover().vk
+}
+
+// Proves a witness against a requirements file, exactly as the evm binary would, but as a
+// compressed proof for off-chain verification rather than Groth16.
+pub fn prove(
+    witness: &PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+    now: u64,
+) -> Result<SP1ProofWithPublicValues> {
+    check_options(reqs, options)?;
+    let stdin = guest_stdin(witness, reqs, options, now)?;
+    let prover = prover();
+    prover
+        .client
+        .prove(&prover.pk, &stdin)
+        .compressed()
+        .run()
+        .map_err(|e| anyhow!("proof generation failed: {:#}", e))
+}
+
+// The public values prove would commit for the same inputs, computed on the host in
+// milliseconds by the function the guest runs. Useful to fill in a UI or sanity-check inputs
+// before paying for a proof. Where the guest would abort, this returns the reason as an error.
+pub fn expected_outputs(
+    witness: &PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+    now: u64,
+) -> Result<PassportVerificationOutput> {
+    check_options(reqs, options)?;
+    let input = guest_input(witness, reqs, options, now)?;
+    std::panic::catch_unwind(|| evaluate_passport(input)).map_err(|panic| {
+        let reason = panic
+            .downcast_ref::<&str>()
+            .map(|s| s.to_string())
+            .or_else(|| panic.downcast_ref::<String>().cloned())
+            .unwrap_or_else(|| "unknown reason".to_string());
+        anyhow!("the guest would reject these inputs: {}", reason)
+    })
+}
+
+// The requirements a proof over these inputs would commit as failed, named for the person running
+// the prover, e.g. ["minimum age", "nationality"]. Empty when every check that was asked for
+// passes. Only checks the requirements or options ask for count, and withheld outputs are checked
+// too, since the guest computes them before zeroing.
+pub fn unmet_requirements(
+    witness: &PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+    now: u64,
+) -> Result<Vec<&'static str>> {
+    let disclosed = GuestOptions {
+        disclosures: Disclosures::default(),
+        ..options.clone()
+    };
+    let output = expected_outputs(witness, reqs, &disclosed, now)?;
+    let nationality_required = !reqs.required_nationality.is_empty()
+        || !options.target_nationalities.is_empty()
+        || reqs.target_nationality_commitment.is_some();
+    let checks = [
+        (reqs.required_age > 0, output.min_age_check.is_over_min_age, "minimum age"),
+        (reqs.min_age_months > 0, output.max_age_bound.is_over_min_age_months, "minimum age in months"),
+        (options.max_age.or(reqs.max_age).is_some(), output.max_age_bound.is_under_max_age, "maximum age"),
+        (nationality_required, output.is_nationality_match, "nationality"),
+        (!options.blocked_nationalities.is_empty(), output.nationality_policy.is_not_blocked, "blocked nationality"),
+        (!reqs.required_sex.is_empty(), output.is_sex_match, "sex"),
+        (!reqs.drinking_destination.is_empty(), output.drinking_check.can_drink, "drinking age"),
+        (reqs.min_validity_days > 0, output.expiry_check.has_min_validity, "minimum validity"),
+        (witness.document_signature.is_some(), output.document_integrity.is_valid_signature, "chip signature"),
+        (witness.data_groups.is_some(), output.document_integrity.is_data_group_consistent, "chip data groups"),
+        (!reqs.csca_roots.is_empty(), output.document_integrity.is_trusted_issuer, "trusted issuer"),
+        (!options.document_allowlist.is_empty(), output.document_membership.is_member, "document allowlist"),
+    ];
+    Ok(checks
+        .into_iter()
+        .filter(|(required, passed, _)| *required && !passed)
+        .map(|(.., name)| name)
+        .collect())
+}
+
+// An age-only proof as of current_date (midnight UTC). Nationality, expiry and the wallet are
+// withheld, so the proof shows nothing about the holder beyond the age check and the identity hash.
+pub fn generate_age_proof(
+    passport: &PassportAttributes,
+    current_date: &Date,
+    min_age: u16,
+) -> Result<SP1ProofWithPublicValues> {
+    let witness = PassportWitness::from(passport.clone());
+    let zero_address = format!("0x{}", "00".repeat(20));
+    let reqs = VerificationRequirements {
+        wallet_address: zero_address.clone(),
+        verifier_address: zero_address,
+        required_age: min_age,
+        min_age_months: 0,
+        required_nationality: String::new(),
+        required_sex: String::new(),
+        grace_days: 0,
+        drinking_destination: String::new(),
+        max_age: None,
+        external_nullifier: None,
+        commitment_hash: CommitmentHash::default(),
+        commitment_bytes: None,
+        alliance: String::new(),
+        csca_roots: Vec::new(),
+        target_nationality_commitment: None,
+        target_nationality_salt: None,
+        min_validity_days: 0,
+    };
+    let options = GuestOptions {
+        disclosures: Disclosures {
+            age: true,
+            nationality: false,
+            expiry: false,
+            wallet: false,
+        },
+        ..GuestOptions::default()
+    };
+    let now = evaluation_timestamp(Some(current_date))?;
+    prove(&witness, &reqs, &options, now)
+}
+
+// Verifies any proof of the passport program against vk and returns its decoded public values.
+pub fn verify_and_decode(proof: &SP1ProofWithPublicValues, vk: &SP1VerifyingKey) -> Result<PassportVerificationOutput> {
+    prover().client.verify(proof, vk).context("proof is invalid")?;
+    decode_public_values(proof.public_values.as_slice()).context("failed to decode public values")
+}
+
+// Whether a proof is valid and for the identity a relying party registered earlier: after
+// verifying, the committed identity_hash must equal expected_commitment, so a valid proof of
+// someone else's passport is refused. Truncated commitments compare with their zero tail.
+pub fn verify_commitment_match(proof: &SP1ProofWithPublicValues, vk: &SP1VerifyingKey, expected_commitment: &[u8; 32]) -> bool {
+    verify_and_decode(proof, vk).is_ok_and(|output| output.identity_hash == expected_commitment)
+}
+
+// verify_and_decode for age checks: also fails when the proof withholds its age result.
+pub fn verify_and_decode_age(proof: &SP1ProofWithPublicValues, vk: &SP1VerifyingKey) -> Result<PassportVerificationOutput> {
+    let output = verify_and_decode(proof, vk)?;
+    if output.disclosed_flags & Disclosures::AGE == 0 {
+        bail!("proof does not disclose its age check");
+    }
+    Ok(output)
+}
@@ -0,0 +1,551 @@
+use alloy_sol_types::private::U256;
+use alloy_sol_types::{sol, SolCall, SolValue};
+use passport_verifier_lib::{
+    timestamp_to_date, AggregateAgeOutput, Disclosures, PassportVerificationOutput, SignatureStatus, AGE_BRACKETS, VALIDITY_MONTHS_CAP,
+};
+use serde_json::{json, Value};
+
+// The guest only commits uint256 values that fit in a u64, and everything below renders them as
+// one. Public values are untrusted input, so larger ones are rejected here rather than panicking
+// in the conversion.
+fn check_fits_u64(values: &[(&'static str, U256)]) -> Result<(), alloy_sol_types::Error> {
+    for (name, value) in values {
+        if u64::try_from(*value).is_err() {
+            return Err(alloy_sol_types::Error::custom(format!("{} is {}, larger than the guest ever commits", name, value)));
+        }
+    }
+    Ok(())
+}
+
+pub fn decode_public_values(bytes: &[u8]) -> Result<PassportVerificationOutput, alloy_sol_types::Error> {
+    let output = PassportVerificationOutput::abi_decode(bytes)?;
+    check_fits_u64(&[
+        ("min_age", output.min_age_check.min_age),
+        ("reference_timestamp", output.min_age_check.reference_timestamp),
+        ("grace_days", output.age_grace.grace_days),
+        ("max_age", output.max_age_bound.max_age),
+        ("current_timestamp", output.current_timestamp),
+        ("valid_until_timestamp", output.audience_binding.valid_until_timestamp),
+    ])?;
+    Ok(output)
+}
+
+pub fn decode_aggregate_values(bytes: &[u8]) -> Result<AggregateAgeOutput, alloy_sol_types::Error> {
+    let output = AggregateAgeOutput::abi_decode(bytes)?;
+    check_fits_u64(&[("oldest_timestamp", output.oldest_timestamp)])?;
+    Ok(output)
+}
+
+// Which program committed a proof's public values, and so which struct they decode as.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputKind {
+    // The passport program: PassportVerificationOutput
+    Passport,
+    // The aggregation program: AggregateAgeOutput
+    Aggregate,
+}
+
+// A proof's public values, decoded by the program that committed them, so verify, decode and
+// indexers handle either program without code of their own per output struct.
+#[derive(Clone)]
+pub enum PublicOutputs {
+    // Boxed: the passport output is several times the size of the aggregate's
+    Passport(Box<PassportVerificationOutput>),
+    Aggregate(AggregateAgeOutput),
+}
+
+impl PublicOutputs {
+    pub fn decode(kind: OutputKind, bytes: &[u8]) -> Result<Self, alloy_sol_types::Error> {
+        Ok(match kind {
+            OutputKind::Passport => PublicOutputs::Passport(Box::new(decode_public_values(bytes)?)),
+            OutputKind::Aggregate => PublicOutputs::Aggregate(decode_aggregate_values(bytes)?),
+        })
+    }
+
+    pub fn kind(&self) -> OutputKind {
+        match self {
+            PublicOutputs::Passport(_) => OutputKind::Passport,
+            PublicOutputs::Aggregate(_) => OutputKind::Aggregate,
+        }
+    }
+
+    pub fn to_json(&self) -> Value {
+        match self {
+            PublicOutputs::Passport(output) => public_values_to_json(output),
+            PublicOutputs::Aggregate(output) => aggregate_values_to_json(output),
+        }
+    }
+
+    // When the checks were evaluated: current_timestamp, or for an aggregate the stalest of its
+    // inner proofs'.
+    pub fn timestamp(&self) -> u64 {
+        match self {
+            PublicOutputs::Passport(output) => output.current_timestamp.to(),
+            PublicOutputs::Aggregate(output) => output.oldest_timestamp.to(),
+        }
+    }
+}
+
+// The plain-language report: render_report for a passport proof, a summary for an aggregate.
+impl std::fmt::Display for PublicOutputs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PublicOutputs::Passport(output) => f.write_str(&render_report(output)),
+            PublicOutputs::Aggregate(output) => {
+                let oldest: u64 = output.oldest_timestamp.to();
+                writeln!(f, "Aggregate of {} passport proofs", output.proof_count)?;
+                writeln!(f, "Distinct holders at least {}: {}", output.min_age, output.over_min_age_count)?;
+                writeln!(f, "Passport program: 0x{}", hex::encode(output.passport_vkey))?;
+                write!(f, "Oldest check as of {} (epoch {})", format_date(oldest), oldest)
+            }
+        }
+    }
+}
+
+sol! {
+    function verifyClaim(bytes publicValues, bytes proofBytes) external;
+}
+
+// Splits AppVerifier.verifyClaim calldata, as submitted on-chain, into its decoded public
+// values and the raw proof bytes.
+pub fn decode_calldata(calldata: &[u8]) -> Result<(PassportVerificationOutput, Vec<u8>), alloy_sol_types::Error> {
+    let call = verifyClaimCall::abi_decode(calldata)?;
+    Ok((decode_public_values(&call.publicValues)?, call.proofBytes.to_vec()))
+}
+
+pub fn format_date(timestamp: u64) -> String {
+    let date = timestamp_to_date(timestamp);
+    format!("{:04}-{:02}-{:02}", date.year, date.month, date.day)
+}
+
+// Addresses are rendered EIP-55 checksummed, the hash as 0x-prefixed hex.
+pub fn public_values_to_json(output: &PassportVerificationOutput) -> Value {
+    let timestamp: u64 = output.current_timestamp.to();
+    let reference_timestamp: u64 = output.min_age_check.reference_timestamp.to();
+    json!({
+        "identity_hash": format!("0x{}", hex::encode(output.identity_hash)),
+        "wallet_address": output.wallet_address.to_checksum(None),
+        "verifier_address": output.verifier_address.to_checksum(None),
+        "is_over_min_age": output.min_age_check.is_over_min_age,
+        "min_age": output.min_age_check.min_age.to::<u64>(),
+        "reference_timestamp": reference_timestamp,
+        "reference_date": format_date(reference_timestamp),
+        "is_nationality_match": output.is_nationality_match,
+        "target_nationality": output.target_nationality,
+        "is_sex_match": output.is_sex_match,
+        "target_sex": output.target_sex,
+        "current_timestamp": timestamp,
+        "current_date": format_date(timestamp),
+        "mrz_checksums_valid": output.document_integrity.mrz_checksums_valid,
+        "within_grace": output.age_grace.within_grace,
+        "grace_days": output.age_grace.grace_days.to::<u64>(),
+        "is_valid_signature": output.document_integrity.is_valid_signature,
+        "signature_status": match output.document_integrity.signature_status {
+            0 => "none",
+            1 => "valid",
+            2 => "unsupported_algorithm",
+            3 => "invalid",
+            _ => "unknown",
+        },
+        "is_data_group_consistent": output.document_integrity.is_data_group_consistent,
+        "is_trusted_issuer": output.document_integrity.is_trusted_issuer,
+        "issuer_root_commitment": format!("0x{}", hex::encode(output.document_integrity.issuer_root_commitment)),
+        "can_drink": output.drinking_check.can_drink,
+        "drinking_destination": output.drinking_check.drinking_destination,
+        "drinking_age_table_hash": format!("0x{}", hex::encode(output.drinking_check.drinking_age_table_hash)),
+        "validity_months_remaining": output.expiry_check.validity_months_remaining,
+        "is_under_max_age": output.max_age_bound.is_under_max_age,
+        "max_age": output.max_age_bound.max_age.to::<u64>(),
+        "age_bracket_flags": output.max_age_bound.age_bracket_flags,
+        "is_over_min_age_months": output.max_age_bound.is_over_min_age_months,
+        "min_age_months": output.max_age_bound.min_age_months,
+        "external_nullifier": format!("0x{}", hex::encode(output.nullifier_binding.external_nullifier)),
+        "nullifier": format!("0x{}", hex::encode(output.nullifier_binding.nullifier)),
+        "commitment_hash": match output.identity_commitment.commitment_hash {
+            0 => "sha256",
+            1 => "poseidon",
+            2 => "keccak256",
+            _ => "unknown",
+        },
+        "issuer_in_alliance": output.issuer_alliance.issuer_in_alliance,
+        "grouping_id": format!("0x{}", hex::encode(output.issuer_alliance.grouping_id)),
+        "audience": format!("0x{}", hex::encode(output.audience_binding.audience)),
+        "audience_commitment": format!("0x{}", hex::encode(output.audience_binding.audience_commitment)),
+        "valid_until_timestamp": output.audience_binding.valid_until_timestamp.to::<u64>(),
+        "time_oracle": format!("0x{}", hex::encode(output.audience_binding.time_oracle)),
+        "commitment_field_mask": format!("0x{}", hex::encode(output.identity_commitment.commitment_field_mask)),
+        "commitment_version": output.identity_commitment.commitment_version,
+        "commitment_bytes": output.identity_commitment.commitment_bytes,
+        "full_record_commitment": format!("0x{}", hex::encode(output.identity_commitment.full_record_commitment)),
+        "any_satisfies": output.identity_commitment.passport_set.any_satisfies,
+        "passport_count": output.identity_commitment.passport_set.passport_count,
+        "passport_set_commitment": format!("0x{}", hex::encode(output.identity_commitment.passport_set.set_commitment)),
+        "disclosed_flags": output.disclosed_flags,
+        "is_expired": output.expiry_check.is_expired,
+        "has_min_validity": output.expiry_check.has_min_validity,
+        "min_validity_days": output.expiry_check.min_validity_days,
+        "allowed_nationalities_commitment": format!("0x{}", hex::encode(output.nationality_policy.allowed.commitment)),
+        "allowed_nationalities_size": output.nationality_policy.allowed.size,
+        "is_not_blocked": output.nationality_policy.is_not_blocked,
+        "blocked_nationalities_commitment": format!("0x{}", hex::encode(output.nationality_policy.blocked.commitment)),
+        "blocked_nationalities_size": output.nationality_policy.blocked.size,
+        "target_nationality_commitment": format!("0x{}", hex::encode(output.nationality_policy.target_commitment)),
+        "stateless_by_issuer": output.nationality_policy.stateless_by_issuer,
+        "is_rebind": output.wallet_rebind.is_rebind,
+        "previous_wallet": output.wallet_rebind.previous_wallet.to_checksum(None),
+        "policy_commitment": format!("0x{}", hex::encode(output.policy_commitment)),
+        "is_allowlisted": output.document_membership.is_member,
+        "allowlist_root": format!("0x{}", hex::encode(output.document_membership.merkle_root)),
+        "wallet_binding_commitment": format!("0x{}", hex::encode(output.linked_wallets.binding_commitment)),
+        "linked_wallets": output
+            .linked_wallets
+            .wallets
+            .iter()
+            .map(|wallet| json!({
+                "chain_id": wallet.chain_id,
+                "address": format!("0x{}", hex::encode(&wallet.wallet)),
+            }))
+            .collect::<Vec<_>>(),
+    })
+}
+
+pub fn aggregate_values_to_json(output: &AggregateAgeOutput) -> Value {
+    let oldest_timestamp: u64 = output.oldest_timestamp.to();
+    json!({
+        "passport_vkey": format!("0x{}", hex::encode(output.passport_vkey)),
+        "min_age": output.min_age,
+        "proof_count": output.proof_count,
+        "over_min_age_count": output.over_min_age_count,
+        "identity_set_commitment": format!("0x{}", hex::encode(output.identity_set_commitment)),
+        "oldest_timestamp": oldest_timestamp,
+        "oldest_date": format_date(oldest_timestamp),
+    })
+}
+
+// Plain-language summary for people reviewing a verified proof. Predicates the holder withheld
+// and checks that weren't requested are left out rather than reported as NO. Expects an output
+// from decode_public_values, whose uint256 fields all fit in a u64.
+pub fn render_report(output: &PassportVerificationOutput) -> String {
+    let yes_no = |value: bool| if value { "YES" } else { "NO" };
+    let disclosed = |flag: u8| output.disclosed_flags & flag != 0;
+    let entries = |size: u16| if size == 1 { "1 entry".to_string() } else { format!("{} entries", size) };
+    let mut lines = Vec::new();
+
+    if disclosed(Disclosures::AGE) {
+        let min_age: u64 = output.min_age_check.min_age.to();
+        if min_age == 18 {
+            lines.push(format!("Adult (≥18): {}", yes_no(output.min_age_check.is_over_min_age)));
+        } else if min_age > 0 {
+            lines.push(format!("Aged {}+: {}", min_age, yes_no(output.min_age_check.is_over_min_age)));
+        }
+        let min_age_months = output.max_age_bound.min_age_months;
+        if min_age_months > 0 {
+            lines.push(format!(
+                "Aged {}y {}m+: {}",
+                min_age_months / 12,
+                min_age_months % 12,
+                yes_no(output.max_age_bound.is_over_min_age_months)
+            ));
+        }
+        let max_age: u64 = output.max_age_bound.max_age.to();
+        if max_age > 0 {
+            lines.push(format!("Aged {} or under: {}", max_age, yes_no(output.max_age_bound.is_under_max_age)));
+        }
+        let brackets: Vec<String> = AGE_BRACKETS
+            .iter()
+            .enumerate()
+            .filter(|(i, _)| output.max_age_bound.age_bracket_flags & (1 << i) != 0)
+            .map(|(_, age)| format!("{}+", age))
+            .collect();
+        if !brackets.is_empty() {
+            lines.push(format!("Age brackets: {}", brackets.join(", ")));
+        }
+        let reference_timestamp: u64 = output.min_age_check.reference_timestamp.to();
+        if reference_timestamp != output.current_timestamp.to::<u64>() {
+            lines.push(format!("Age checked as of {}", format_date(reference_timestamp)));
+        }
+    }
+
+    if disclosed(Disclosures::NATIONALITY) {
+        let policy = &output.nationality_policy;
+        if policy.allowed.size > 0 && !output.target_nationality.is_empty() {
+            lines.push(format!(
+                "Nationality in the accepted list ({}): {}, matched {}",
+                entries(policy.allowed.size),
+                yes_no(output.is_nationality_match),
+                output.target_nationality
+            ));
+        } else if policy.allowed.size > 0 {
+            lines.push(format!(
+                "Nationality in the accepted list ({}): {}",
+                entries(policy.allowed.size),
+                yes_no(output.is_nationality_match)
+            ));
+        } else if policy.target_commitment != [0u8; 32] {
+            lines.push(format!(
+                "Nationality matches the hidden target: {}",
+                yes_no(output.is_nationality_match)
+            ));
+        } else if !output.target_nationality.is_empty() {
+            lines.push(format!(
+                "Nationality matches {}: {}",
+                output.target_nationality,
+                yes_no(output.is_nationality_match)
+            ));
+        }
+        if policy.blocked.size > 0 {
+            lines.push(format!(
+                "Nationality not blocked ({}): {}",
+                entries(policy.blocked.size),
+                yes_no(policy.is_not_blocked)
+            ));
+        }
+        if policy.stateless_by_issuer {
+            lines.push("Stateless holders checked by issuing state: yes".to_string());
+        }
+    }
+
+    if !output.target_sex.is_empty() {
+        lines.push(format!("Sex matches {}: {}", output.target_sex, yes_no(output.is_sex_match)));
+    }
+    // Which of the passports matched stays private; the checks above are that one's.
+    let passport_set = &output.identity_commitment.passport_set;
+    if passport_set.passport_count > 1 {
+        lines.push(format!(
+            "One of {} passports meets the requirements: {}",
+            passport_set.passport_count,
+            yes_no(passport_set.any_satisfies)
+        ));
+    }
+
+    // The guest refuses to prove an expired passport outside expired mode, and vice versa.
+    if output.expiry_check.is_expired {
+        lines.push("Document expired: YES".to_string());
+    } else {
+        lines.push("Document valid: YES".to_string());
+    }
+    let validity_months_remaining = output.expiry_check.validity_months_remaining;
+    if disclosed(Disclosures::EXPIRY) && validity_months_remaining > 0 {
+        let months = if validity_months_remaining > VALIDITY_MONTHS_CAP {
+            format!("more than {}", VALIDITY_MONTHS_CAP)
+        } else {
+            validity_months_remaining.to_string()
+        };
+        lines.push(format!("Months of validity remaining: {}", months));
+    }
+    let min_validity_days = output.expiry_check.min_validity_days;
+    if disclosed(Disclosures::EXPIRY) && min_validity_days > 0 {
+        lines.push(format!(
+            "Valid for at least {} more days: {}",
+            min_validity_days,
+            yes_no(output.expiry_check.has_min_validity)
+        ));
+    }
+
+    lines.push(format!("MRZ check digits verified: {}", yes_no(output.document_integrity.mrz_checksums_valid)));
+    lines.push(format!("Chip signature verified: {}", yes_no(output.document_integrity.is_valid_signature)));
+    if output.document_integrity.signature_status == SignatureStatus::UnsupportedAlgorithm as u8 {
+        lines.push("Chip signature uses an algorithm the prover can't check".to_string());
+    }
+    lines.push(format!("Chip data matches fields: {}", yes_no(output.document_integrity.is_data_group_consistent)));
+    if output.document_integrity.issuer_root_commitment != [0u8; 32] {
+        lines.push(format!("Issued by a trusted state: {}", yes_no(output.document_integrity.is_trusted_issuer)));
+    }
+    if output.document_membership.merkle_root != [0u8; 32] {
+        lines.push(format!("On the document allowlist: {}", yes_no(output.document_membership.is_member)));
+    }
+
+    let valid_until: u64 = output.audience_binding.valid_until_timestamp.to();
+    if valid_until != 0 {
+        lines.push(format!("Proof valid until: {} (unix {})", format_date(valid_until), valid_until));
+    }
+    if output.audience_binding.time_oracle != [0u8; 32] {
+        lines.push(format!("Time signed by oracle: 0x{}", hex::encode(output.audience_binding.time_oracle)));
+    }
+
+    if disclosed(Disclosures::WALLET) {
+        lines.push(format!("Bound wallet: {}", output.wallet_address.to_checksum(None)));
+        if output.wallet_rebind.is_rebind {
+            lines.push(format!("Rebinding from: {}", output.wallet_rebind.previous_wallet.to_checksum(None)));
+        }
+        for wallet in &output.linked_wallets.wallets {
+            lines.push(format!("Linked wallet on chain {}: 0x{}", wallet.chain_id, hex::encode(&wallet.wallet)));
+        }
+    } else {
+        lines.push("Bound wallet: not disclosed".to_string());
+    }
+
+    let timestamp: u64 = output.current_timestamp.to();
+    lines.push(format!("Checked as of {} (epoch {})", format_date(timestamp), timestamp));
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::inputs::{GuestOptions, PassportWitness, VerificationRequirements};
+    use crate::prover::expected_outputs;
+    use passport_verifier_lib::{Date, DocumentNumber, LinkedWallet, Nationality, PassportAttributes};
+
+    fn passport_output() -> PassportVerificationOutput {
+        let passport = PassportAttributes {
+            document_number: DocumentNumber::new("A12345678").unwrap(),
+            date_of_birth: Date::new(1994, 5, 20).unwrap(),
+            date_of_expiry: Date::new(2030, 1, 1).unwrap(),
+            nationality: Nationality::new("MYS").unwrap(),
+            name: "TAN AH KOW".to_string(),
+            sex: "M".to_string(),
+        };
+        let reqs: VerificationRequirements = serde_json::from_value(json!({
+            "walletAddress": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+            "verifierAddress": format!("0x{}", "11".repeat(20)),
+            "requiredAge": 18,
+            "requiredNationality": "MYS",
+            "requiredSex": "",
+        }))
+        .unwrap();
+        expected_outputs(&PassportWitness::from(passport), &reqs, &GuestOptions::default(), 1_761_955_200).unwrap()
+    }
+
+    #[test]
+    fn each_output_kind_decodes_from_its_abi_encoding() {
+        let passport = passport_output();
+        let decoded = PublicOutputs::decode(OutputKind::Passport, &passport.abi_encode()).unwrap();
+        assert_eq!(decoded.kind(), OutputKind::Passport);
+        assert_eq!(decoded.to_json(), public_values_to_json(&passport));
+        assert_eq!(decoded.timestamp(), 1_761_955_200);
+        assert_eq!(decoded.to_string(), render_report(&passport));
+
+        let aggregate = AggregateAgeOutput {
+            passport_vkey: [7u8; 32].into(),
+            min_age: 18,
+            proof_count: 3,
+            over_min_age_count: 2,
+            identity_set_commitment: [9u8; 32].into(),
+            oldest_timestamp: U256::from(1_761_868_800u64),
+        };
+        let decoded = PublicOutputs::decode(OutputKind::Aggregate, &aggregate.abi_encode()).unwrap();
+        assert_eq!(decoded.kind(), OutputKind::Aggregate);
+        assert_eq!(decoded.to_json(), aggregate_values_to_json(&aggregate));
+        assert_eq!(decoded.timestamp(), 1_761_868_800);
+        assert!(decoded.to_string().contains("Distinct holders at least 18: 2"), "{}", decoded);
+
+        // An aggregate's bytes are too short to pass for a passport output.
+        assert!(PublicOutputs::decode(OutputKind::Passport, &aggregate.abi_encode()).is_err());
+    }
+
+    #[test]
+    fn json_names_every_field_with_its_type() {
+        let mut output = passport_output();
+        output.linked_wallets.wallets.push(LinkedWallet { chain_id: 501, wallet: vec![0x0a, 0x0b].into() });
+        let json = public_values_to_json(&output);
+        let fields = json.as_object().unwrap();
+
+        let strings = [
+            "identity_hash", "wallet_address", "verifier_address", "reference_date", "target_nationality", "target_sex",
+            "current_date", "signature_status", "issuer_root_commitment", "drinking_destination", "drinking_age_table_hash",
+            "external_nullifier", "nullifier", "commitment_hash", "grouping_id", "audience", "audience_commitment",
+            "time_oracle", "commitment_field_mask", "full_record_commitment", "allowed_nationalities_commitment",
+            "blocked_nationalities_commitment", "target_nationality_commitment", "previous_wallet", "policy_commitment",
+            "allowlist_root", "wallet_binding_commitment", "passport_set_commitment",
+        ];
+        let bools = [
+            "is_over_min_age", "is_nationality_match", "is_sex_match", "mrz_checksums_valid", "within_grace",
+            "is_valid_signature", "is_data_group_consistent", "is_trusted_issuer", "can_drink", "is_under_max_age",
+            "is_over_min_age_months", "issuer_in_alliance", "is_expired", "has_min_validity", "is_not_blocked",
+            "stateless_by_issuer", "is_rebind", "is_allowlisted", "any_satisfies",
+        ];
+        let numbers = [
+            "min_age", "reference_timestamp", "current_timestamp", "grace_days", "validity_months_remaining", "max_age",
+            "age_bracket_flags", "min_age_months", "valid_until_timestamp", "commitment_version", "commitment_bytes",
+            "disclosed_flags", "min_validity_days", "allowed_nationalities_size", "blocked_nationalities_size",
+            "passport_count",
+        ];
+        for name in strings {
+            assert!(fields.get(name).is_some_and(Value::is_string), "{} should be a string", name);
+        }
+        for name in bools {
+            assert!(fields.get(name).is_some_and(Value::is_boolean), "{} should be a bool", name);
+        }
+        for name in numbers {
+            assert!(fields.get(name).is_some_and(Value::is_u64), "{} should be a number", name);
+        }
+        assert_eq!(fields["linked_wallets"], json!([{ "chain_id": 501, "address": "0x0a0b" }]));
+        // Nothing unlisted, so a new output field has to be added here too.
+        assert_eq!(fields.len(), strings.len() + bools.len() + numbers.len() + 1);
+
+        assert_eq!(fields["wallet_address"], "0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed");
+        assert_eq!(fields["current_date"], "2025-11-01");
+        assert_eq!(fields["min_age"], 18);
+        assert_eq!(fields["commitment_hash"], "sha256");
+    }
+
+    #[test]
+    fn values_past_u64_are_rejected_when_decoding() {
+        let mut output = passport_output();
+        output.current_timestamp = U256::from(u64::MAX) + U256::from(1);
+        let error = decode_public_values(&output.abi_encode()).err().unwrap();
+        assert!(error.to_string().contains("current_timestamp"), "{}", error);
+
+        let mut output = passport_output();
+        output.min_age_check.min_age = U256::MAX;
+        assert!(decode_public_values(&output.abi_encode()).is_err());
+        assert!(PublicOutputs::decode(OutputKind::Passport, &output.abi_encode()).is_err());
+
+        let aggregate = AggregateAgeOutput {
+            passport_vkey: [7u8; 32].into(),
+            min_age: 18,
+            proof_count: 3,
+            over_min_age_count: 2,
+            identity_set_commitment: [9u8; 32].into(),
+            oldest_timestamp: U256::MAX,
+        };
+        assert!(PublicOutputs::decode(OutputKind::Aggregate, &aggregate.abi_encode()).is_err());
+    }
+
+    #[test]
+    fn report_reads_the_same_for_a_known_output() {
+        let output = passport_output();
+        assert_eq!(
+            render_report(&output),
+            "Adult (≥18): YES
+Age brackets: 13+, 16+, 18+, 21+
+Nationality matches MYS: YES
+Document valid: YES
+Months of validity remaining: more than 24
+MRZ check digits verified: NO
+Chip signature verified: NO
+Chip data matches fields: NO
+Bound wallet: 0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAed
+Checked as of 2025-11-01 (epoch 1761955200)"
+        );
+
+        // Withheld predicates are left out, not reported as NO.
+        let withheld = PassportVerificationOutput { disclosed_flags: 0, ..output };
+        assert_eq!(
+            render_report(&withheld),
+            "Document valid: YES
+MRZ check digits verified: NO
+Chip signature verified: NO
+Chip data matches fields: NO
+Bound wallet: not disclosed
+Checked as of 2025-11-01 (epoch 1761955200)"
+        );
+    }
+
+    #[test]
+    fn calldata_round_trips_through_verify_claim() {
+        let output = passport_output();
+        let call = verifyClaimCall { publicValues: output.abi_encode().into(), proofBytes: vec![1, 2, 3].into() };
+        let calldata = call.abi_encode();
+        let (decoded, proof) = decode_calldata(&calldata).unwrap();
+        assert_eq!(decoded.abi_encode(), output.abi_encode());
+        assert_eq!(proof, vec![1, 2, 3]);
+
+        // Calldata for any other function fails on its selector.
+        let mut wrong_selector = calldata.clone();
+        wrong_selector[0] ^= 0xff;
+        assert!(decode_calldata(&wrong_selector).is_err());
+        assert!(decode_calldata(&calldata[..3]).is_err());
+    }
+}
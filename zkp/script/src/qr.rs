@@ -0,0 +1,88 @@
+use crate::inputs::{to_passport_witness, PassportInput};
+use anyhow::{anyhow, bail, Context, Result};
+use image::{GrayImage, Luma};
+use qrcode::QrCode;
+
+// Passport JSON carried in a QR code, so the mobile app can hand a prepared record to the prover
+// by showing it on screen. The payload is the passport JSON evm reads, without whitespace. Host
+// side only: the guest never sees the image, just the passport parsed out of it.
+
+// Binary capacity of the largest QR code (version 40) at the default error correction level M.
+// Chip data (DG1, SOD, certificates) doesn't fit, so a QR passport is MRZ fields only.
+pub const MAX_QR_PAYLOAD_BYTES: usize = 2331;
+
+// Checks the passport JSON the way evm would before encoding it, so a record that can't be
+// proved is caught on the phone rather than at the prover. Returns the compact payload.
+pub fn passport_qr_payload(json: &serde_json::Value) -> Result<String> {
+    let input: PassportInput = serde_json::from_value(json.clone()).context("invalid passport JSON")?;
+    to_passport_witness(input)?;
+    let payload = json.to_string();
+    if payload.len() > MAX_QR_PAYLOAD_BYTES {
+        bail!(
+            "passport JSON is {} bytes, more than a QR code holds ({}); leave out the chip data",
+            payload.len(),
+            MAX_QR_PAYLOAD_BYTES
+        );
+    }
+    Ok(payload)
+}
+
+pub fn encode_qr(payload: &str) -> Result<GrayImage> {
+    let code = QrCode::new(payload.as_bytes()).map_err(|e| anyhow!("failed to encode the QR code: {}", e))?;
+    Ok(code.render::<Luma<u8>>().build())
+}
+
+// The payload of the first QR code found in the image.
+pub fn decode_qr(image: GrayImage) -> Result<String> {
+    let mut prepared = rqrr::PreparedImage::prepare(image);
+    let grid = prepared.detect_grids().into_iter().next().context("no QR code found in the image")?;
+    let (_, payload) = grid.decode().map_err(|e| anyhow!("failed to decode the QR code: {}", e))?;
+    Ok(payload)
+}
+
+pub fn write_passport_qr(json: &serde_json::Value, path: &str) -> Result<()> {
+    let image = encode_qr(&passport_qr_payload(json)?)?;
+    image.save(path).with_context(|| format!("failed to write {}", path))
+}
+
+// The passport in a QR image (PNG or any format the image crate reads), through the same field
+// checks as passport JSON.
+pub fn read_passport_qr(path: &str) -> Result<PassportInput> {
+    let image = image::open(path).with_context(|| format!("failed to open QR image {}", path))?;
+    let payload = decode_qr(image.to_luma8()).with_context(|| format!("failed to read {}", path))?;
+    serde_json::from_str(&payload).with_context(|| format!("{} does not hold passport JSON", path))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn passport_survives_a_qr_round_trip() {
+        let json = json!({
+            "documentNumber": "L898902C3",
+            "birthYear": 1974, "birthMonth": 8, "birthDay": 12,
+            "expiryYear": 2030, "expiryMonth": 4, "expiryDay": 15,
+            "nationality": "UTO",
+            "name": "ANNA MARIA ERIKSSON",
+            "sex": "F",
+        });
+        let path = std::env::temp_dir().join(format!("passport-qr-{}.png", std::process::id()));
+        let path = path.to_str().unwrap();
+        write_passport_qr(&json, path).unwrap();
+        let input = read_passport_qr(path);
+        std::fs::remove_file(path).unwrap();
+
+        let passport = to_passport_witness(input.unwrap()).unwrap().passport;
+        assert_eq!(passport.document_number.as_str(), "L898902C3");
+        assert_eq!(passport.name, "ANNA MARIA ERIKSSON");
+        assert_eq!((passport.date_of_birth.year, passport.date_of_birth.month, passport.date_of_birth.day), (1974, 8, 12));
+
+        let invalid = json!({ "documentNumber": "L898902C3" });
+        assert!(passport_qr_payload(&invalid).is_err());
+        let mut too_large = json;
+        too_large["note"] = "A".repeat(MAX_QR_PAYLOAD_BYTES).into();
+        assert!(passport_qr_payload(&too_large).unwrap_err().to_string().contains("more than a QR code holds"));
+    }
+}
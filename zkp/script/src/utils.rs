@@ -1,35 +1,964 @@
+use crate::envelope::{ProofEnvelope, SavedSize};
+use anyhow::Context;
 use colored::*;
+use passport_verifier_lib::{
+    days_since_epoch, iso3166, validate_eip55, CommitmentFields, Date, Disclosures, WalletEntry, MAX_WALLET_ADDRESS_BYTES,
+};
+use sha2::{Digest, Sha256};
+use sp1_sdk::{EnvProver, HashableKey, ProverClient, SP1Proof, SP1ProvingKey, SP1VerifyingKey};
+use serde::Serialize;
+use std::io::IsTerminal;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+static JSON_OUTPUT: AtomicBool = AtomicBool::new(false);
+static STDOUT_RESERVED: AtomicBool = AtomicBool::new(false);
+
+// In JSON mode stdout carries only the final RunReport: the banner and dividers are dropped and
+// the other print_* helpers write uncoloured lines to stderr.
+pub fn set_json_output(enabled: bool) {
+    JSON_OUTPUT.store(enabled, Ordering::Relaxed);
+    if enabled {
+        colored::control::set_override(false);
+    }
+}
+
+pub fn json_output() -> bool {
+    JSON_OUTPUT.load(Ordering::Relaxed)
+}
+
+// For runs that pipe a proof out on stdout: the banner and the print_* helpers move to stderr,
+// coloured as usual, so nothing else lands in the piped data.
+pub fn reserve_stdout() {
+    STDOUT_RESERVED.store(true, Ordering::Relaxed);
+}
+
+fn stdout_reserved() -> bool {
+    json_output() || STDOUT_RESERVED.load(Ordering::Relaxed)
+}
+
+fn emit(line: String) {
+    if stdout_reserved() {
+        eprintln!("{}", line);
+    } else {
+        println!("{}", line);
+    }
+}
+
+// What a run produced, printed as one JSON object for automation.
+#[derive(Serialize, Default)]
+pub struct RunReport {
+    pub success: bool,
+    pub proof_path: Option<String>,
+    // Bytes in the proof itself, and in the proof file as written
+    pub proof_size: Option<u64>,
+    pub file_size: Option<u64>,
+    pub vkey_hash: Option<String>,
+    pub public_outputs: Option<serde_json::Value>,
+    pub cycles: Option<u64>,
+    pub duration_ms: Option<u64>,
+    // PhaseTimer breakdown of the run, in milliseconds per phase
+    pub phases_ms: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+impl RunReport {
+    // No-op outside JSON mode, where the print_* lines already said everything.
+    pub fn emit(&self) {
+        if json_output() {
+            println!("{}", serde_json::to_string(self).expect("report is serializable"));
+        }
+    }
+}
 
 pub fn print_banner() {
-    println!("{}", r#" 
+    if json_output() {
+        return;
+    }
+    emit(r#" 
     ____                                 __   
    / __ \____ _______________  ____  ____/ /_ 
   / /_/ / __ `/ ___/ ___/ __ \/ __ \/ __  / __|
  / ____/ /_/ (__  |__  ) /_/ / /_/ / /_/ / /_ 
 /_/    \__,_/____/____/ .___/\____/\__,_/\__/ 
                      /_/                      
-    "#.cyan().bold());
-    println!("{}", "Passport Protocol zk-Verifier".bright_blue().italic());
-    println!("{}", "=============================================".bright_black());
-    println!();
+    "#.cyan().bold().to_string());
+    emit("Passport Protocol zk-Verifier".bright_blue().italic().to_string());
+    emit("=============================================".bright_black().to_string());
+    emit(String::new());
 }
 
 pub fn print_step(msg: &str) {
-    println!("{} {}", "➜".cyan().bold(), msg);
+    emit(format!("{} {}", "➜".cyan().bold(), msg));
 }
 
 pub fn print_success(msg: &str) {
-    println!("{} {}", "✔".green().bold(), msg);
+    emit(format!("{} {}", "✔".green().bold(), msg));
 }
 
 pub fn print_error(msg: &str) {
-    println!("{} {}", "✖".red().bold(), msg);
+    emit(format!("{} {}", "✖".red().bold(), msg));
 }
 
 pub fn print_info(key: &str, value: &str) {
-    println!("  {}: {}", key.bright_black(), value.yellow());
+    emit(format!("  {}: {}", key.bright_black(), value.yellow()));
+}
+
+// Runs f while a spinner on stderr ticks through the elapsed time, so a proof that takes minutes
+// doesn't look frozen. SP1 exposes no progress hooks; its log lines, when RUST_LOG enables them,
+// name the phase (core, compress, wrap) it is in. Only drawn on a terminal outside JSON mode.
+pub fn with_spinner<T>(msg: &str, f: impl FnOnce() -> T) -> T {
+    if json_output() || !std::io::stderr().is_terminal() {
+        return f();
+    }
+    const FRAMES: [&str; 10] = ["⠋", "⠙", "⠹", "⠸", "⠼", "⠴", "⠦", "⠧", "⠇", "⠏"];
+    let done = AtomicBool::new(false);
+    let start = Instant::now();
+    std::thread::scope(|scope| {
+        scope.spawn(|| {
+            for frame in FRAMES.iter().cycle() {
+                if done.load(Ordering::Relaxed) {
+                    break;
+                }
+                let elapsed = start.elapsed().as_secs();
+                eprint!("\r{} {} {}", frame.cyan(), msg, format!("{}m{:02}s", elapsed / 60, elapsed % 60).bright_black());
+                std::thread::sleep(Duration::from_millis(100));
+            }
+            // Clear the spinner line so the next print_* starts clean.
+            eprint!("\r\x1b[2K");
+        });
+        let result = f();
+        done.store(true, Ordering::Relaxed);
+        result
+    })
+}
+
+// Wall time per phase of a run, in the order the phases ran, so "took 90s" becomes where the 90s
+// went. SP1 runs core proving, compression and the Groth16 or PLONK wrap inside one prove call
+// with no hooks between them, so those show up together as "prove".
+#[derive(Default)]
+pub struct PhaseTimer {
+    phases: Vec<(&'static str, Duration)>,
+}
+
+impl PhaseTimer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Runs f and records its wall time under phase. A phase timed twice, e.g. across retries,
+    // adds up.
+    pub fn time<T>(&mut self, phase: &'static str, f: impl FnOnce() -> T) -> T {
+        let start = Instant::now();
+        let result = f();
+        self.record(phase, start.elapsed());
+        result
+    }
+
+    pub fn record(&mut self, phase: &'static str, elapsed: Duration) {
+        match self.phases.iter_mut().find(|(name, _)| *name == phase) {
+            Some((_, total)) => *total += elapsed,
+            None => self.phases.push((phase, elapsed)),
+        }
+    }
+
+    pub fn total(&self) -> Duration {
+        self.phases.iter().map(|(_, elapsed)| *elapsed).sum()
+    }
+
+    // One line per phase with its share of the total.
+    pub fn print(&self) {
+        let total = self.total().as_secs_f64().max(f64::EPSILON);
+        for (phase, elapsed) in &self.phases {
+            let share = elapsed.as_secs_f64() / total * 100.0;
+            print_info(phase, &format!("{:.2?} ({:.1}%)", elapsed, share));
+        }
+    }
+
+    // {"phase": milliseconds, ...}, for meta.phases in the proof file.
+    pub fn to_json(&self) -> serde_json::Value {
+        self.phases
+            .iter()
+            .map(|(phase, elapsed)| (phase.to_string(), serde_json::Value::from(elapsed.as_millis() as u64)))
+            .collect::<serde_json::Map<_, _>>()
+            .into()
+    }
 }
 
 pub fn print_divider() {
-    println!("{}", "---------------------------------------------".bright_black());
+    if json_output() {
+        return;
+    }
+    emit("---------------------------------------------".bright_black().to_string());
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum ProofSystem {
+    Core,
+    Compressed,
+    Groth16,
+    Plonk,
+}
+
+impl ProofSystem {
+    pub fn name(self) -> &'static str {
+        match self {
+            ProofSystem::Core => "Core",
+            ProofSystem::Compressed => "Compressed",
+            ProofSystem::Groth16 => "Groth16",
+            ProofSystem::Plonk => "PLONK",
+        }
+    }
+
+    // The mode recorded in proof files and metrics.
+    pub fn mode(self) -> &'static str {
+        match self {
+            ProofSystem::Core => "core",
+            ProofSystem::Compressed => "compressed",
+            ProofSystem::Groth16 => "groth16",
+            ProofSystem::Plonk => "plonk",
+        }
+    }
+}
+
+// Bits of security every SP1 proof system offers. Both wrappers are over BN254, which SP1
+// documents at ~100 bits after the exTNFS attacks, and SP1 targets the same ~100 bits
+// (conjectured) for its core and compressed STARKs. With one figure for all four,
+// --min-security-bits can't pick between them: it accepts every system or none.
+pub const SP1_SECURITY_BITS: u32 = 100;
+
+// The kind of proof SP1 produced, for files that only carry the proof itself.
+pub fn sp1_proof_system(proof: &SP1Proof) -> ProofSystem {
+    match proof {
+        SP1Proof::Core(_) => ProofSystem::Core,
+        SP1Proof::Compressed(_) => ProofSystem::Compressed,
+        SP1Proof::Plonk(_) => ProofSystem::Plonk,
+        SP1Proof::Groth16(_) => ProofSystem::Groth16,
+    }
+}
+
+// SP1 prefixes Groth16 and PLONK proof bytes with the first 4 bytes of SHA-256 of the wrapper's
+// verifying key, which is how the on-chain SP1VerifierGateway routes them. None for bytes with
+// neither prefix, e.g. a mock proof.
+pub fn wrapped_proof_system(proof: &[u8]) -> Option<ProofSystem> {
+    let selector = proof.get(..4)?;
+    if selector == &Sha256::digest(*sp1_verifier::GROTH16_VK_BYTES)[..4] {
+        Some(ProofSystem::Groth16)
+    } else if selector == &Sha256::digest(*sp1_verifier::PLONK_VK_BYTES)[..4] {
+        Some(ProofSystem::Plonk)
+    } else {
+        None
+    }
+}
+
+pub fn check_proof_system(got: ProofSystem, expected: ProofSystem) -> Result<(), String> {
+    if got != expected {
+        return Err(format!("proof system mismatch: got {}, verifier expects {}", got.name(), expected.name()));
+    }
+    Ok(())
+}
+
+pub fn check_security_level(system: ProofSystem, min_bits: u32) -> Result<(), String> {
+    if SP1_SECURITY_BITS < min_bits {
+        return Err(format!(
+            "{} provides ~{} bits of security, below the required minimum of {} bits",
+            system.name(),
+            SP1_SECURITY_BITS,
+            min_bits
+        ));
+    }
+    Ok(())
+}
+
+// An age requirement above this is an input mistake, not a real policy.
+pub const MAX_REQUIRED_AGE: u16 = 150;
+
+// Earliest birth year accepted from passport input; earlier ones are typos in the data.
+pub const MIN_BIRTH_YEAR: u16 = 1900;
+
+// Rejects a minimum (or maximum) age no one could meet. 0 is allowed, since an age check can be
+// deliberately unused, but callers should warn about it.
+pub fn validate_min_age(age: u16) -> Result<(), String> {
+    if age > MAX_REQUIRED_AGE {
+        return Err(format!("age requirement {} is above the maximum of {}", age, MAX_REQUIRED_AGE));
+    }
+    Ok(())
+}
+
+// clap value parser for --min-age flags.
+pub fn parse_min_age(s: &str) -> Result<u16, String> {
+    let age = s.trim().parse().map_err(|_| format!("invalid age {:?}", s))?;
+    validate_min_age(age)?;
+    Ok(age)
+}
+
+pub fn validate_birth_year(year: u16, current_year: u16) -> Result<(), String> {
+    if !(MIN_BIRTH_YEAR..=current_year).contains(&year) {
+        return Err(format!(
+            "birth year {} is outside {}..={}",
+            year, MIN_BIRTH_YEAR, current_year
+        ));
+    }
+    Ok(())
+}
+
+// Exit code the proving binaries use when the prover environment, not the input, is at fault.
+pub const PROVER_FAILURE_EXIT_CODE: i32 = 3;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvingFailure {
+    MissingArtifacts,
+    OutOfMemory,
+    UnsupportedProver,
+    Network,
+    Other,
+}
+
+impl ProvingFailure {
+    pub fn hint(self) -> &'static str {
+        match self {
+            ProvingFailure::MissingArtifacts => {
+                "Groth16 needs its circuit artifacts, which SP1 downloads to ~/.sp1/circuits on first use; \
+                 check network access and disk space, or run with SP1_PROVER=mock to test without them"
+            }
+            ProvingFailure::OutOfMemory => {
+                "The Groth16 wrapper needs roughly 16 GB of RAM; raise the machine or container memory limit, \
+                 or set SP1_PROVER=network to prove remotely"
+            }
+            ProvingFailure::UnsupportedProver => {
+                "This prover can't produce Groth16 proofs here; check SP1_PROVER (cpu, cuda, network or mock) \
+                 and that the native gnark backend was built for this platform"
+            }
+            ProvingFailure::Network => {
+                "The prover network timed out or is rate limiting requests; raise --max-retries or \
+                 --retry-delay, or try again later"
+            }
+            ProvingFailure::Other => "Rerun with RUST_LOG=debug for the full prover log",
+        }
+    }
+
+    // Only network failures can pass on a second attempt; bad input or a vkey mismatch fails the
+    // same way every time.
+    pub fn is_retryable(self) -> bool {
+        self == ProvingFailure::Network
+    }
+}
+
+// Maps an SP1 proving error message onto a failure the user can act on.
+pub fn classify_proving_error(message: &str) -> ProvingFailure {
+    let message = message.to_ascii_lowercase();
+    let mentions = |needles: &[&str]| needles.iter().any(|needle| message.contains(needle));
+
+    if mentions(&["out of memory", "memory allocation", "cannot allocate", "oom-kill", "killed"]) {
+        ProvingFailure::OutOfMemory
+    } else if mentions(&["artifact", "download", "circuits", "no such file"]) {
+        ProvingFailure::MissingArtifacts
+    } else if mentions(&["unsupported", "not supported", "not implemented", "unknown prover"]) {
+        ProvingFailure::UnsupportedProver
+    } else if mentions(&[
+        "timed out",
+        "timeout",
+        "rate limit",
+        "too many requests",
+        "429",
+        "503",
+        "unavailable",
+        "connection reset",
+        "connection refused",
+    ]) {
+        ProvingFailure::Network
+    } else {
+        ProvingFailure::Other
+    }
+}
+
+// Runs a proving attempt, retrying it up to max_retries times while it fails with a retryable
+// error. The wait starts at delay and doubles after every failure.
+pub fn retry_proving<T>(
+    max_retries: u32,
+    delay: Duration,
+    mut attempt: impl FnMut() -> anyhow::Result<T>,
+) -> anyhow::Result<T> {
+    let mut wait = delay;
+    for retry in 1..=max_retries {
+        match attempt() {
+            Err(e) if classify_proving_error(&format!("{:#}", e)).is_retryable() => {
+                print_step(&format!("Proving failed ({:#}); retry {} of {} in {:.0?}", e, retry, max_retries, wait));
+                std::thread::sleep(wait);
+                wait = wait.saturating_mul(2);
+            }
+            result => return result,
+        }
+    }
+    attempt()
+}
+
+// Lock files may carry a trailing newline or differ in hex case from vk.bytes32().
+pub fn vkey_matches_lock(locked: &str, current: &str) -> bool {
+    locked.trim().eq_ignore_ascii_case(current.trim())
+}
+
+// Parses a YYYY-MM-DD date.
+pub fn parse_iso_date(s: &str) -> Result<Date, String> {
+    let invalid = || format!("invalid date {:?}: expected YYYY-MM-DD", s);
+    let mut parts = s.trim().splitn(3, '-');
+    let year = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+    let month = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+    let day = parts.next().and_then(|p| p.parse().ok()).ok_or_else(invalid)?;
+    Date::new(year, month, day).map_err(|e| format!("invalid date {:?}: {}", s, e))
+}
+
+// Unix time the proof is evaluated at: midnight UTC of current_date when one is pinned, so the
+// same inputs give the same proof, or the system clock otherwise.
+pub fn evaluation_timestamp(current_date: Option<&Date>) -> anyhow::Result<u64> {
+    match current_date {
+        Some(date) => u64::try_from(days_since_epoch(date) * 86400).context("current date is before 1970"),
+        None => Ok(std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .context("system clock is before 1970")?
+            .as_secs()),
+    }
+}
+
+// clap value parser for nationality flags: an ICAO code, ISO alpha-2 code or English country name,
+// returned as the ICAO code passports carry.
+pub fn parse_nationality(s: &str) -> Result<String, String> {
+    iso3166::normalize_nationality(s)
+        .map(str::to_string)
+        .ok_or_else(|| format!("unknown nationality {:?}: expected an ICAO code such as MYS, or a country name", s))
+}
+
+// Parses a comma-separated identity field list such as "doc,dob,nationality,names,expiry".
+pub fn parse_identity_fields(s: &str) -> Result<CommitmentFields, String> {
+    let mut fields: Option<CommitmentFields> = None;
+    for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        let field = match name {
+            "doc" => CommitmentFields::DOCUMENT_NUMBER,
+            "dob" => CommitmentFields::DATE_OF_BIRTH,
+            "nationality" => CommitmentFields::NATIONALITY,
+            "names" => CommitmentFields::NAMES,
+            "expiry" => CommitmentFields::DATE_OF_EXPIRY,
+            other => {
+                return Err(format!(
+                    "unknown identity field {:?}: expected doc, dob, nationality, names or expiry",
+                    other
+                ))
+            }
+        };
+        fields = Some(fields.map_or(field, |fields| fields | field));
+    }
+    fields.ok_or_else(|| "at least one identity field is required".to_string())
+}
+
+// Parses a linked wallet given as CHAIN_ID:HEX_ADDRESS, e.g. "501:0x0a1b..." for a Solana key.
+pub fn parse_wallet_entry(s: &str) -> Result<WalletEntry, String> {
+    let invalid = || format!("invalid wallet {:?}: expected CHAIN_ID:HEX_ADDRESS", s);
+    let (chain_id, address) = s.trim().split_once(':').ok_or_else(invalid)?;
+    let chain_id = chain_id.parse().map_err(|_| invalid())?;
+    let address = hex::decode(address.trim_start_matches("0x")).map_err(|_| invalid())?;
+    if !(1..=MAX_WALLET_ADDRESS_BYTES).contains(&address.len()) {
+        return Err(format!("wallet address in {:?} must be 1 to {} bytes", s, MAX_WALLET_ADDRESS_BYTES));
+    }
+    Ok(WalletEntry { chain_id, address })
+}
+
+// Parses a comma-separated list of predicates to reveal, such as "age,wallet". "none" withholds
+// every one of them.
+pub fn parse_disclosures(s: &str) -> Result<Disclosures, String> {
+    let mut disclosures = Disclosures {
+        age: false,
+        nationality: false,
+        expiry: false,
+        wallet: false,
+    };
+    if s.trim() == "none" {
+        return Ok(disclosures);
+    }
+    for name in s.split(',').map(str::trim).filter(|name| !name.is_empty()) {
+        match name {
+            "age" => disclosures.age = true,
+            "nationality" => disclosures.nationality = true,
+            "expiry" => disclosures.expiry = true,
+            "wallet" => disclosures.wallet = true,
+            other => return Err(format!("unknown disclosure {:?}: expected age, nationality, expiry or wallet", other)),
+        }
+    }
+    Ok(disclosures)
+}
+
+const PROVING_DURATION_BUCKETS: [f64; 8] = [60.0, 120.0, 300.0, 600.0, 1200.0, 1800.0, 3600.0, 7200.0];
+const CYCLE_COUNT_BUCKETS: [f64; 6] = [1e5, 1e6, 1e7, 5e7, 1e8, 1e9];
+
+// One proving run, written out for node_exporter's textfile collector. Metric names and the
+// duration buckets match the REST server's GET /metrics so both feed the same dashboards.
+pub struct ProofMetrics {
+    pub mode: &'static str,
+    pub succeeded: bool,
+    pub duration_secs: f64,
+    pub cycles: Option<u64>,
+}
+
+impl ProofMetrics {
+    pub fn to_prometheus(&self) -> String {
+        let label = format!("mode=\"{}\"", self.mode);
+        let mut out = String::new();
+        out.push_str("# HELP passport_proofs_generated_total Proofs generated successfully.\n");
+        out.push_str("# TYPE passport_proofs_generated_total counter\n");
+        out.push_str(&format!("passport_proofs_generated_total{{{}}} {}\n", label, self.succeeded as u8));
+        out.push_str("# HELP passport_proof_failures_total Proof jobs whose prover run failed or timed out.\n");
+        out.push_str("# TYPE passport_proof_failures_total counter\n");
+        out.push_str(&format!("passport_proof_failures_total{{{}}} {}\n", label, !self.succeeded as u8));
+        push_histogram(
+            &mut out,
+            "passport_proving_duration_seconds",
+            "Wall-clock time of the evm prover run.",
+            &label,
+            &PROVING_DURATION_BUCKETS,
+            Some(self.duration_secs),
+        );
+        push_histogram(
+            &mut out,
+            "passport_proof_cycles",
+            "RISC-V cycles executed by the guest program.",
+            &label,
+            &CYCLE_COUNT_BUCKETS,
+            self.cycles.map(|cycles| cycles as f64),
+        );
+        out
+    }
+}
+
+// A histogram holding at most one observation.
+fn push_histogram(out: &mut String, name: &str, help: &str, label: &str, buckets: &[f64], value: Option<f64>) {
+    out.push_str(&format!("# HELP {} {}\n# TYPE {} histogram\n", name, help, name));
+    for bound in buckets {
+        let count = value.is_some_and(|value| value <= *bound) as u8;
+        out.push_str(&format!("{}_bucket{{{},le=\"{}\"}} {}\n", name, label, bound, count));
+    }
+    let count = value.is_some() as u8;
+    out.push_str(&format!("{}_bucket{{{},le=\"+Inf\"}} {}\n", name, label, count));
+    out.push_str(&format!("{}_sum{{{}}} {}\n", name, label, value.unwrap_or(0.0)));
+    out.push_str(&format!("{}_count{{{}}} {}\n", name, label, count));
+}
+
+// PASSPORT_MOCK_PROVER=1 runs SP1's mock prover, which skips proving and returns a proof only
+// the mock verifier accepts, so stdin wiring, decoding and file output can be exercised in
+// seconds. SP1_PROVER=mock has the same effect.
+pub fn mock_prover() -> bool {
+    std::env::var("PASSPORT_MOCK_PROVER").is_ok_and(|value| value == "1")
+        || std::env::var("SP1_PROVER").is_ok_and(|value| value == "mock")
+}
+
+// ProverClient::from_env panics on a prover it can't build, often after setup or the user's input.
+// This catches the same misconfigurations as an error: an unknown SP1_PROVER, network mode
+// without NETWORK_PRIVATE_KEY, or cuda without a visible GPU. An unset SP1_PROVER means cpu.
+pub fn check_prover_config(
+    prover: Option<&str>,
+    network_private_key: Option<&str>,
+    gpu_available: impl FnOnce() -> bool,
+) -> Result<(), String> {
+    match prover.unwrap_or("cpu") {
+        "cpu" | "mock" => Ok(()),
+        "network" if network_private_key.is_none_or(|key| key.trim().is_empty()) => Err(
+            "SP1_PROVER=network needs NETWORK_PRIVATE_KEY set to a key registered with the Succinct \
+             Prover Network"
+                .to_string(),
+        ),
+        "network" => Ok(()),
+        "cuda" if !gpu_available() => Err(
+            "SP1_PROVER=cuda but no NVIDIA GPU is visible (nvidia-smi -L failed); use cpu or network instead"
+                .to_string(),
+        ),
+        "cuda" => Ok(()),
+        other => Err(format!("SP1_PROVER is {:?}; expected cpu, cuda, network or mock", other)),
+    }
+}
+
+// Runs check_prover_config on the environment and exits with PROVER_FAILURE_EXIT_CODE on a bad
+// prover. Proving binaries call it after loading .env and before reading any input.
+pub fn assert_prover_config() {
+    if mock_prover() {
+        return;
+    }
+    let prover = std::env::var("SP1_PROVER").ok();
+    let network_private_key = std::env::var("NETWORK_PRIVATE_KEY").ok();
+    let gpu_available = || {
+        std::process::Command::new("nvidia-smi")
+            .arg("-L")
+            .output()
+            .is_ok_and(|output| output.status.success())
+    };
+    if let Err(e) = check_prover_config(prover.as_deref(), network_private_key.as_deref(), gpu_available) {
+        print_error(&e);
+        std::process::exit(PROVER_FAILURE_EXIT_CODE);
+    }
+}
+
+// The prover client every binary uses. The mock prover is selected through SP1_PROVER rather than
+// ProverClient::builder().mock(), so callers keep the one EnvProver type. Call it before spawning
+// threads, since it may set an environment variable.
+pub fn prover_client() -> EnvProver {
+    assert_prover_config();
+    if mock_prover() {
+        std::env::set_var("SP1_PROVER", "mock");
+    }
+    ProverClient::from_env()
+}
+
+const ELF_MAGIC: [u8; 4] = [0x7f, b'E', b'L', b'F'];
+
+// The embedded guest must be a real ELF. include_bytes! only proves the file existed when the
+// script was compiled, and an interrupted guest build can leave it empty or truncated.
+pub fn check_elf(elf: &[u8]) -> Result<(), String> {
+    if elf.is_empty() {
+        return Err("the guest ELF is empty".to_string());
+    }
+    if !elf.starts_with(&ELF_MAGIC) {
+        return Err("the guest ELF does not start with the ELF magic bytes".to_string());
+    }
+    Ok(())
+}
+
+// Called first thing in every binary that embeds a guest, so a bad build fails with a fix rather
+// than a setup error from deep inside SP1.
+pub fn assert_valid_elf(elf: &[u8]) {
+    if let Err(e) = check_elf(elf) {
+        print_error(&e);
+        eprintln!(
+            "Rebuild the guest programs: `cargo build` in script runs build.rs, which compiles \
+             program and aggregation into target/elf-compilation. Check its output for errors."
+        );
+        std::process::exit(1);
+    }
+}
+
+// client.setup(elf), cached under ~/.cache/passport-verifier/<sha256 of elf>/ so only the first
+// run after a guest change pays for it. PASSPORT_NO_CACHE=1 forces regeneration. A missing or
+// unreadable cache falls back to setup; failing to write it only costs the next run.
+pub fn setup_cached(client: &EnvProver, elf: &[u8]) -> (SP1ProvingKey, SP1VerifyingKey) {
+    let Some(dir) = key_cache_dir(elf) else {
+        return client.setup(elf);
+    };
+    let no_cache = std::env::var("PASSPORT_NO_CACHE").is_ok_and(|value| value == "1");
+    if !no_cache {
+        if let Some(keys) = read_cached_keys(&dir) {
+            print_info("Keys", &format!("loaded from {}", dir.display()));
+            return keys;
+        }
+    }
+
+    let (pk, vk) = client.setup(elf);
+    match write_cached_keys(&dir, &pk, &vk) {
+        Ok(()) => print_info("Keys", &format!("cached in {}", dir.display())),
+        Err(e) => print_info("Keys", &format!("not cached ({})", e)),
+    }
+    (pk, vk)
+}
+
+// vk.bytes32() for elf, the key contracts are deployed with. The vkey depends only on the ELF, so
+// the hash is cached next to the keys and later calls read it without setup or loading the
+// proving key. Shares setup_cached's cache and its PASSPORT_NO_CACHE switch.
+pub fn vkey_hash(client: &EnvProver, elf: &[u8]) -> String {
+    let no_cache = std::env::var("PASSPORT_NO_CACHE").is_ok_and(|value| value == "1");
+    let dir = key_cache_dir(elf);
+    if let (false, Some(dir)) = (no_cache, &dir) {
+        if let Some(hash) = read_cached_vkey_hash(dir) {
+            return hash;
+        }
+    }
+    let (_, vk) = setup_cached(client, elf);
+    let hash = vk.bytes32();
+    if let Some(dir) = &dir {
+        // setup_cached has created the directory unless it couldn't, which only costs the next run.
+        let _ = std::fs::write(dir.join(VKEY_HASH_FILE), &hash);
+    }
+    hash
+}
+
+const VKEY_HASH_FILE: &str = "vkey_hash.txt";
+
+// The cached hash, or one derived from a cached vk.bin written before the hash was cached.
+fn read_cached_vkey_hash(dir: &Path) -> Option<String> {
+    if let Ok(hash) = std::fs::read_to_string(dir.join(VKEY_HASH_FILE)) {
+        return Some(hash.trim().to_string());
+    }
+    let vk: SP1VerifyingKey = bincode::deserialize(&std::fs::read(dir.join("vk.bin")).ok()?).ok()?;
+    let hash = vk.bytes32();
+    let _ = std::fs::write(dir.join(VKEY_HASH_FILE), &hash);
+    Some(hash)
+}
+
+fn key_cache_dir(elf: &[u8]) -> Option<PathBuf> {
+    let home = std::env::var_os("HOME")?;
+    Some(PathBuf::from(home).join(".cache/passport-verifier").join(hex::encode(Sha256::digest(elf))))
+}
+
+fn read_cached_keys(dir: &Path) -> Option<(SP1ProvingKey, SP1VerifyingKey)> {
+    let pk = bincode::deserialize(&std::fs::read(dir.join("pk.bin")).ok()?).ok()?;
+    let vk = bincode::deserialize(&std::fs::read(dir.join("vk.bin")).ok()?).ok()?;
+    Some((pk, vk))
+}
+
+// Each key goes through a temporary file so an interrupted run never leaves a truncated key behind.
+fn write_cached_keys(dir: &Path, pk: &SP1ProvingKey, vk: &SP1VerifyingKey) -> std::io::Result<()> {
+    std::fs::create_dir_all(dir)?;
+    let keys = [("pk.bin", bincode::serialize(pk)), ("vk.bin", bincode::serialize(vk))];
+    for (name, bytes) in keys {
+        let bytes = bytes.map_err(std::io::Error::other)?;
+        let tmp = dir.join(format!("{}.tmp", name));
+        std::fs::write(&tmp, bytes)?;
+        std::fs::rename(&tmp, dir.join(name))?;
+    }
+    std::fs::write(dir.join(VKEY_HASH_FILE), vk.bytes32())?;
+    Ok(())
+}
+
+// A batch record's id names its proof file but comes from the input file, so it is limited to
+// letters, digits, - and _, which can't form a path separator or "..".
+pub fn check_file_id(id: &str) -> Result<(), String> {
+    if id.is_empty() || !id.bytes().all(|c| c.is_ascii_alphanumeric() || c == b'-' || c == b'_') {
+        return Err(format!("id {:?} may only contain letters, digits, - and _", id));
+    }
+    Ok(())
+}
+
+// Writes a proof file into dir, creating it if needed, and returns the path written and its size.
+// An existing file is only replaced with overwrite, and gzip appends .gz to the name; see
+// ProofEnvelope::save. file_name must be a single path component, so the file can't land
+// outside dir.
+pub fn save_artifact(
+    dir: &str,
+    file_name: &str,
+    artifact: &ProofEnvelope,
+    overwrite: bool,
+    gzip: bool,
+) -> anyhow::Result<(String, SavedSize)> {
+    let mut components = Path::new(file_name).components();
+    if !matches!((components.next(), components.next()), (Some(Component::Normal(_)), None)) {
+        anyhow::bail!("proof file name {:?} is not a plain file name", file_name);
+    }
+    std::fs::create_dir_all(dir).with_context(|| format!("failed to create {}", dir))?;
+    let file_name = if gzip { format!("{}.gz", file_name) } else { file_name.to_string() };
+    let path = Path::new(dir).join(file_name).to_string_lossy().into_owned();
+    let size = artifact.save(&path, overwrite)?;
+    Ok((path, size))
+}
+
+// Names a proof file after what it proves: the first 8 hex digits of SHA-256 over its public
+// values. Proofs of different passports or requirements get different names, and reproving the
+// same inputs at the same date lands on the existing file instead of a new one.
+pub fn artifact_id(public_values: &[u8]) -> String {
+    hex::encode(&Sha256::digest(public_values)[..4])
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WalletError {
+    InvalidHex,
+    InvalidLength(usize),
+    BadChecksum,
+}
+
+impl std::fmt::Display for WalletError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WalletError::InvalidHex => write!(f, "address is not valid hex"),
+            WalletError::InvalidLength(len) => write!(f, "address must be 20 bytes, got {}", len),
+            WalletError::BadChecksum => write!(f, "address has an invalid EIP-55 checksum; check for a typo"),
+        }
+    }
+}
+
+impl std::error::Error for WalletError {}
+
+// Parses a 20-byte hex address with or without 0x. All-lowercase and all-uppercase addresses
+// carry no checksum; mixed case must match EIP-55, so a mistyped character is caught.
+pub fn parse_wallet_address(s: &str) -> Result<[u8; 20], WalletError> {
+    let bytes = hex::decode(s.trim().trim_start_matches("0x")).map_err(|_| WalletError::InvalidHex)?;
+    let address: [u8; 20] = bytes
+        .as_slice()
+        .try_into()
+        .map_err(|_| WalletError::InvalidLength(bytes.len()))?;
+    if !validate_eip55(s) {
+        return Err(WalletError::BadChecksum);
+    }
+    Ok(address)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn prover_config_is_checked_before_proving() {
+        assert!(check_prover_config(None, None, || false).is_ok());
+        assert!(check_prover_config(Some("mock"), None, || false).is_ok());
+        assert!(check_prover_config(Some("network"), Some("0xabc"), || false).is_ok());
+        assert!(check_prover_config(Some("network"), Some(" "), || false).is_err());
+        assert!(check_prover_config(Some("cuda"), None, || true).is_ok());
+        assert!(check_prover_config(Some("cuda"), None, || false).is_err());
+        assert!(check_prover_config(Some("gpu"), None, || true).is_err());
+    }
+
+    #[test]
+    fn only_network_failures_are_retried() {
+        let mut attempts = 0;
+        let result = retry_proving(3, Duration::ZERO, || {
+            attempts += 1;
+            if attempts < 3 {
+                anyhow::bail!("request timed out")
+            }
+            Ok(attempts)
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        attempts = 0;
+        let result: anyhow::Result<()> = retry_proving(2, Duration::ZERO, || {
+            attempts += 1;
+            anyhow::bail!("429 Too Many Requests")
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 3);
+
+        attempts = 0;
+        let result: anyhow::Result<()> = retry_proving(3, Duration::ZERO, || {
+            attempts += 1;
+            anyhow::bail!("vkey mismatch")
+        });
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+    }
+
+    #[test]
+    fn wallet_entry_takes_chain_and_hex_address() {
+        let entry = parse_wallet_entry("501:0x0a0b").unwrap();
+        assert_eq!(entry, WalletEntry { chain_id: 501, address: vec![0x0a, 0x0b] });
+        assert_eq!(parse_wallet_entry(" 1:ff").unwrap().chain_id, 1);
+        assert!(parse_wallet_entry("0x0a0b").is_err());
+        assert!(parse_wallet_entry("eth:0x0a0b").is_err());
+        assert!(parse_wallet_entry("1:0xzz").is_err());
+        assert!(parse_wallet_entry("1:").is_err());
+        assert!(parse_wallet_entry(&format!("1:{}", "00".repeat(65))).is_err());
+    }
+
+    #[test]
+    fn repeated_phases_add_up() {
+        let mut timer = PhaseTimer::new();
+        timer.record("setup", Duration::from_millis(40));
+        timer.record("prove", Duration::from_millis(100));
+        timer.record("prove", Duration::from_millis(50));
+        assert_eq!(timer.total(), Duration::from_millis(190));
+        assert_eq!(timer.to_json(), serde_json::json!({"setup": 40, "prove": 150}));
+    }
+
+    #[test]
+    fn proof_system_is_read_from_the_proof_bytes() {
+        let groth16_selector = &Sha256::digest(*sp1_verifier::GROTH16_VK_BYTES)[..4];
+        let plonk_selector = &Sha256::digest(*sp1_verifier::PLONK_VK_BYTES)[..4];
+        assert_eq!(wrapped_proof_system(&[groth16_selector, &[7u8; 64]].concat()), Some(ProofSystem::Groth16));
+        assert_eq!(wrapped_proof_system(&[plonk_selector, &[7u8; 64]].concat()), Some(ProofSystem::Plonk));
+        assert_eq!(wrapped_proof_system(&[0]), None);
+
+        assert!(check_proof_system(ProofSystem::Plonk, ProofSystem::Plonk).is_ok());
+        assert_eq!(
+            check_proof_system(ProofSystem::Groth16, ProofSystem::Plonk).unwrap_err(),
+            "proof system mismatch: got Groth16, verifier expects PLONK"
+        );
+    }
+
+    #[test]
+    fn proof_below_the_security_threshold_is_rejected() {
+        for system in [ProofSystem::Core, ProofSystem::Compressed, ProofSystem::Groth16, ProofSystem::Plonk] {
+            assert!(check_security_level(system, 0).is_ok());
+            assert!(check_security_level(system, SP1_SECURITY_BITS).is_ok());
+            assert!(check_security_level(system, SP1_SECURITY_BITS + 1).is_err());
+        }
+        assert_eq!(
+            check_security_level(ProofSystem::Groth16, 128).unwrap_err(),
+            "Groth16 provides ~100 bits of security, below the required minimum of 128 bits"
+        );
+    }
+
+    #[test]
+    fn proof_files_stay_inside_the_output_dir() {
+        assert!(check_file_id("record-7_a").is_ok());
+        for id in ["", "../../x", "/tmp/x", "a/b", "a.b", "..", "a\\b"] {
+            assert!(check_file_id(id).is_err(), "{:?} accepted", id);
+        }
+
+        let dir = std::env::temp_dir().join(format!("save-artifact-{}", std::process::id()));
+        let dir = dir.to_str().unwrap();
+        let envelope = ProofEnvelope::groth16(&[0], &[], "0x00".to_string(), 0);
+        for name in ["../escaped.json", "/tmp/escaped.json", "sub/escaped.json", ".."] {
+            let err = save_artifact(dir, name, &envelope, false, false).unwrap_err();
+            assert!(err.to_string().contains("not a plain file name"), "{}", err);
+        }
+        assert!(!Path::new(dir).exists());
+    }
+
+    #[test]
+    fn elf_check_wants_the_magic_bytes() {
+        assert!(check_elf(&[0x7f, b'E', b'L', b'F', 1, 1, 1, 0]).is_ok());
+        assert!(check_elf(&[]).is_err());
+        assert!(check_elf(b"not an elf").is_err());
+    }
+
+    #[test]
+    fn wallet_address_accepts_lowercase_with_or_without_prefix() {
+        let address = parse_wallet_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed").unwrap();
+        assert_eq!(address[0], 0x5a);
+        assert_eq!(parse_wallet_address("5aaeb6053f3e94c9b9a09f33669435e7ef1beaed"), Ok(address));
+        assert_eq!(parse_wallet_address("0x5AAEB6053F3E94C9B9A09F33669435E7EF1BEAED"), Ok(address));
+    }
+
+    #[test]
+    fn wallet_address_rejects_wrong_length() {
+        assert_eq!(
+            parse_wallet_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1bea"),
+            Err(WalletError::InvalidLength(19))
+        );
+        assert_eq!(
+            parse_wallet_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed00"),
+            Err(WalletError::InvalidLength(21))
+        );
+        // A dropped digit leaves an odd number of them, which isn't hex bytes at all.
+        assert_eq!(
+            parse_wallet_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beae"),
+            Err(WalletError::InvalidHex)
+        );
+    }
+
+    #[test]
+    fn wallet_address_rejects_non_hex() {
+        assert_eq!(
+            parse_wallet_address("0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaeg"),
+            Err(WalletError::InvalidHex)
+        );
+    }
+
+    #[test]
+    fn wallet_address_checks_mixed_case_checksum() {
+        assert!(parse_wallet_address("0xfB6916095ca1df60bB79Ce92cE3Ea74c37c5d359").is_ok());
+        assert_eq!(
+            parse_wallet_address("0x5aAeb6053F3E94C9b9A09f33669435E7Ef1BeAeD"),
+            Err(WalletError::BadChecksum)
+        );
+    }
+
+    #[test]
+    fn min_age_is_bounded() {
+        assert!(validate_min_age(0).is_ok());
+        assert!(validate_min_age(MAX_REQUIRED_AGE).is_ok());
+        assert!(validate_min_age(MAX_REQUIRED_AGE + 1).is_err());
+        assert_eq!(parse_min_age("18"), Ok(18));
+        assert!(parse_min_age("70000").is_err());
+    }
+
+    #[test]
+    fn birth_year_must_be_plausible() {
+        assert!(validate_birth_year(1974, 2026).is_ok());
+        assert!(validate_birth_year(2026, 2026).is_ok());
+        assert!(validate_birth_year(1899, 2026).is_err());
+        assert!(validate_birth_year(2027, 2026).is_err());
+    }
 }
@@ -0,0 +1,58 @@
+// Folds host-predicted passport outputs the way the aggregation program does once it has verified
+// the inner proofs, and checks which sets of proofs it refuses.
+
+use passport_verifier_lib::aggregate::aggregate_age_outputs;
+use passport_verifier_lib::{CommitmentHash, Date, DocumentNumber, Nationality, PassportAttributes, PassportVerificationOutput};
+use passport_verifier_script::inputs::{GuestOptions, PassportWitness, VerificationRequirements};
+use passport_verifier_script::prover::expected_outputs;
+use passport_verifier_script::utils::evaluation_timestamp;
+use serde_json::json;
+
+// One adult's proof as of 2025-11-01, at minimum age 18; an expired one is proved in expired mode.
+fn output(commitment_hash: CommitmentHash, expired: bool) -> PassportVerificationOutput {
+    let passport = PassportAttributes {
+        document_number: DocumentNumber::new("A12345678").unwrap(),
+        date_of_birth: Date::new(1994, 5, 20).unwrap(),
+        date_of_expiry: Date::new(if expired { 2024 } else { 2030 }, 1, 1).unwrap(),
+        nationality: Nationality::new("MYS").unwrap(),
+        name: "TAN AH KOW".to_string(),
+        sex: "M".to_string(),
+    };
+    let reqs: VerificationRequirements = serde_json::from_value(json!({
+        "walletAddress": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        "verifierAddress": format!("0x{}", "11".repeat(20)),
+        "requiredAge": 18,
+        "requiredNationality": "",
+        "requiredSex": "",
+        "commitmentHash": commitment_hash,
+    }))
+    .unwrap();
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let options = GuestOptions { expired, ..GuestOptions::default() };
+    expected_outputs(&PassportWitness::from(passport), &reqs, &options, now).unwrap()
+}
+
+#[test]
+fn same_passport_twice_counts_as_one_holder() {
+    let proof = output(CommitmentHash::Sha256, false);
+    let aggregate = aggregate_age_outputs([0u8; 32], 18, &[proof.clone(), proof]);
+    assert_eq!(aggregate.proof_count, 2);
+    assert_eq!(aggregate.over_min_age_count, 1);
+}
+
+#[test]
+#[should_panic(expected = "non-default configuration")]
+fn same_passport_under_two_hashes_is_refused() {
+    let sha256 = output(CommitmentHash::Sha256, false);
+    let keccak256 = output(CommitmentHash::Keccak256, false);
+    assert_ne!(sha256.identity_hash, keccak256.identity_hash);
+    aggregate_age_outputs([0u8; 32], 18, &[sha256, keccak256]);
+}
+
+#[test]
+#[should_panic(expected = "expired passport")]
+fn expired_mode_proof_is_refused() {
+    let expired = output(CommitmentHash::Sha256, true);
+    assert!(expired.expiry_check.is_expired);
+    aggregate_age_outputs([0u8; 32], 18, &[output(CommitmentHash::Sha256, false), expired]);
+}
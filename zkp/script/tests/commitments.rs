@@ -0,0 +1,53 @@
+// Runs the commitments binary the way a user would and checks every value it prints against the
+// lib function that derives it, so the CLI can't drift from what a proof commits.
+
+use passport_verifier_lib::{
+    derive_audience_commitment, derive_identity_hash, derive_identity_hash_poseidon, derive_identity_hash_with,
+    derive_nullifier, derive_wallet_binding, full_record_commitment, CommitmentFields, CommitmentHash, Date,
+    DocumentNumber, Nationality, PassportAttributes, WalletEntry,
+};
+use serde_json::Value;
+use std::process::Command;
+
+fn hex32(value: [u8; 32]) -> String {
+    format!("0x{}", hex::encode(value))
+}
+
+#[test]
+fn printed_commitments_match_the_library() {
+    let passport = PassportAttributes {
+        document_number: DocumentNumber::new("A12345678").unwrap(),
+        date_of_birth: Date::new(1994, 5, 20).unwrap(),
+        date_of_expiry: Date::new(2030, 1, 1).unwrap(),
+        nationality: Nationality::new("MYS").unwrap(),
+        name: "TAN AH KOW".to_string(),
+        sex: "M".to_string(),
+    };
+    let scope = [0x11u8; 32];
+    let verifier_id = [0x22u8; 32];
+    let wallet = WalletEntry { chain_id: 501, address: vec![0xab; 32] };
+
+    let output = Command::new(env!("CARGO_BIN_EXE_commitments"))
+        .args(["--json", "--document-number", "A12345678", "--birth-date", "1994-05-20"])
+        .args(["--expiry-date", "2030-01-01", "--nationality", "MYS", "--name", "TAN AH KOW", "--sex", "M"])
+        .args(["--external-nullifier", &hex32(scope), "--verifier-id", &hex32(verifier_id)])
+        .args(["--link-wallet", &format!("501:0x{}", "ab".repeat(32))])
+        .env_clear()
+        .output()
+        .expect("failed to run commitments");
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    let printed: Value = serde_json::from_slice(&output.stdout).expect("--json prints one JSON object");
+
+    let fields = CommitmentFields::default();
+    let keccak = derive_identity_hash_with(&passport, CommitmentHash::Keccak256, fields).unwrap();
+    let expected = serde_json::json!({
+        "identity_hash": hex32(derive_identity_hash(&passport)),
+        "identity_hash_keccak256": hex32(keccak),
+        "identity_hash_poseidon": hex32(derive_identity_hash_poseidon(&passport)),
+        "full_record_commitment": hex32(full_record_commitment(&passport)),
+        "nullifier": hex32(derive_nullifier(&passport, &scope)),
+        "audience_commitment": hex32(derive_audience_commitment(&passport, &verifier_id)),
+        "binding_commitment": hex32(derive_wallet_binding(&passport, &[wallet])),
+    });
+    assert_eq!(printed, expected);
+}
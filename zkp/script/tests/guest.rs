@@ -0,0 +1,634 @@
+// Runs the passport program in SP1's executor, without proving, and checks what it commits. The
+// input goes through guest_stdin, so this covers the host side of GuestInput as well.
+
+use alloy_sol_types::SolValue;
+use passport_verifier_lib::evaluate::evaluate_passport;
+use passport_verifier_lib::groupings::MAX_NATIONALITY_SET_SIZE;
+use passport_verifier_lib::oracle;
+use passport_verifier_lib::{
+    days_since_epoch, derive_identity_hash_with, derive_wallet_binding, full_record_commitment, identity_set_commitment,
+    target_nationality_commitment, CommitmentFields, CommitmentHash, Date, Disclosures, DocumentNumber, Nationality,
+    PassportAttributes, PassportVerificationOutput, WalletEntry, MAX_OTHER_PASSPORTS,
+};
+use passport_verifier_script::inputs::{
+    check_options, guest_input, guest_stdin, mrz_witness, verification_policy, GuestOptions, PassportWitness, SignedTimestamp,
+    VerificationRequirements,
+};
+use passport_verifier_script::prover::{expected_outputs, unmet_requirements};
+use passport_verifier_script::public_values::decode_public_values;
+use passport_verifier_script::utils::{evaluation_timestamp, prover_client};
+use serde_json::json;
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+const WALLET: &str = "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed";
+
+fn passport(nationality: &str, date_of_birth: Date) -> PassportAttributes {
+    PassportAttributes {
+        document_number: DocumentNumber::new("A12345678").unwrap(),
+        date_of_birth,
+        date_of_expiry: Date::new(2030, 1, 1).unwrap(),
+        nationality: Nationality::new(nationality).unwrap(),
+        name: "TAN AH KOW".to_string(),
+        sex: "M".to_string(),
+    }
+}
+
+fn requirements(required_age: u16, required_nationality: &str) -> VerificationRequirements {
+    serde_json::from_value(json!({
+        "walletAddress": WALLET,
+        "verifierAddress": format!("0x{}", "11".repeat(20)),
+        "requiredAge": required_age,
+        "requiredNationality": required_nationality,
+        "requiredSex": "",
+    }))
+    .unwrap()
+}
+
+// Executes as of 2025-11-01 and decodes the committed output.
+fn execute(
+    passport: PassportAttributes,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+) -> PassportVerificationOutput {
+    execute_witness(PassportWitness::from(passport), reqs, options)
+}
+
+fn execute_witness(
+    witness: PassportWitness,
+    reqs: &VerificationRequirements,
+    options: &GuestOptions,
+) -> PassportVerificationOutput {
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let stdin = guest_stdin(&witness, reqs, options, now).unwrap();
+    let (public_values, _) = prover_client().execute(PASSPORT_ELF, &stdin).run().unwrap();
+    decode_public_values(public_values.as_slice()).unwrap()
+}
+
+#[test]
+fn adult_passes_min_age() {
+    let output = execute(
+        passport("MYS", Date::new(1994, 5, 20).unwrap()),
+        &requirements(18, ""),
+        &GuestOptions::default(),
+    );
+    assert!(output.min_age_check.is_over_min_age);
+    assert_eq!(output.min_age_check.min_age.to::<u64>(), 18);
+    assert_eq!(output.min_age_check.reference_timestamp, output.current_timestamp);
+}
+
+#[test]
+fn nationality_mismatch_is_committed_as_false() {
+    let output = execute(
+        passport("MYS", Date::new(1994, 5, 20).unwrap()),
+        &requirements(18, "USA"),
+        &GuestOptions::default(),
+    );
+    assert!(!output.is_nationality_match);
+    assert_eq!(output.target_nationality, "USA");
+}
+
+#[test]
+fn wallet_and_timestamp_are_bound() {
+    let output = execute(
+        passport("MYS", Date::new(1994, 5, 20).unwrap()),
+        &requirements(18, ""),
+        &GuestOptions::default(),
+    );
+    assert_eq!(output.wallet_address.to_checksum(None).to_lowercase(), WALLET);
+    assert_eq!(output.current_timestamp.to::<u64>(), 1_761_955_200);
+}
+
+#[test]
+fn validity_window_is_echoed_as_an_expiry() {
+    let options = GuestOptions {
+        valid_for_seconds: Some(3600),
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &options);
+    assert_eq!(output.audience_binding.valid_until_timestamp.to::<u64>(), 1_761_955_200 + 3600);
+
+    let unbounded = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &GuestOptions::default());
+    assert_eq!(unbounded.audience_binding.valid_until_timestamp.to::<u64>(), 0);
+}
+
+#[test]
+fn time_oracle_key_is_committed_with_the_signed_time() {
+    let signed = SignedTimestamp::sign(&[0x44; 32], 1_761_955_200).unwrap();
+    let options = GuestOptions {
+        time_oracle: Some(signed.oracle_signature().unwrap()),
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &options);
+    let key_hash = oracle::oracle_key_hash(&options.time_oracle.as_ref().unwrap().public_key);
+    assert_eq!(output.audience_binding.time_oracle, key_hash);
+
+    // A signature over another time can't carry a proof dated today.
+    let stale = GuestOptions {
+        time_oracle: SignedTimestamp::sign(&[0x44; 32], 1_700_000_000).unwrap().oracle_signature().ok(),
+        ..GuestOptions::default()
+    };
+    let witness = PassportWitness::from(passport("MYS", Date::new(1994, 5, 20).unwrap()));
+    assert!(guest_stdin(&witness, &requirements(18, ""), &stale, 1_761_955_200).is_err());
+
+    let unsigned = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &GuestOptions::default());
+    assert_eq!(unsigned.audience_binding.time_oracle, [0u8; 32]);
+}
+
+#[test]
+fn zero_wallet_is_never_committed_as_a_binding() {
+    let mut reqs = requirements(18, "");
+    reqs.wallet_address = format!("0x{}", "00".repeat(20));
+    let holder = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    assert!(expected_outputs(&PassportWitness::from(holder.clone()), &reqs, &GuestOptions::default(), 1_761_955_200).is_err());
+
+    let output = execute(holder, &reqs, &GuestOptions::default());
+    assert_eq!(output.disclosed_flags & Disclosures::WALLET, 0);
+    assert!(output.min_age_check.is_over_min_age);
+}
+
+#[test]
+fn age_is_checked_at_the_reference_date() {
+    let options = GuestOptions {
+        reference_date: Some(Date::new(2020, 1, 1).unwrap()),
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(2005, 6, 1).unwrap()), &requirements(18, ""), &options);
+    // 18 by 2025-11-01, but only 14 on the reference date.
+    assert!(!output.min_age_check.is_over_min_age);
+    assert_eq!(output.min_age_check.reference_timestamp.to::<u64>(), 1_577_836_800);
+}
+
+#[test]
+fn rebind_commits_the_previous_wallet() {
+    let previous_wallet = [0x22; 20];
+    let options = GuestOptions {
+        previous_wallet: Some(previous_wallet),
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &options);
+    assert!(output.wallet_rebind.is_rebind);
+    assert_eq!(output.wallet_rebind.previous_wallet.0, previous_wallet);
+
+    let plain = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &GuestOptions::default());
+    assert!(!plain.wallet_rebind.is_rebind);
+}
+
+#[test]
+fn identity_hash_is_truncated_to_commitment_bytes() {
+    let mut reqs = requirements(18, "");
+    let full = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &GuestOptions::default());
+    reqs.commitment_bytes = Some(16);
+    let short = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &GuestOptions::default());
+    assert_eq!(short.identity_commitment.commitment_bytes, 16);
+    assert_eq!(short.identity_hash[..16], full.identity_hash[..16]);
+    assert_eq!(short.identity_hash[16..], [0u8; 16]);
+}
+
+// registry looks identities up with lib's derive_identity_hash_with, so it must reproduce what the
+// guest commits. The three hashes give one passport three different identities on purpose.
+#[test]
+fn host_identity_hash_matches_the_committed_one_for_each_hash() {
+    let holder = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    let mut reqs = requirements(18, "");
+    let mut committed = Vec::new();
+    for hash in [CommitmentHash::Sha256, CommitmentHash::Keccak256, CommitmentHash::Poseidon] {
+        reqs.commitment_hash = hash;
+        let output = execute(holder.clone(), &reqs, &GuestOptions::default());
+        let host = derive_identity_hash_with(&holder, hash, CommitmentFields::default()).unwrap();
+        assert_eq!(output.identity_hash.0, host, "{:?}", hash);
+        committed.push(host);
+    }
+    assert!(committed[0] != committed[1] && committed[1] != committed[2] && committed[0] != committed[2]);
+}
+
+#[test]
+fn age_bracket_is_committed_with_the_age_check() {
+    // 19 on 2025-11-01: over 13, 16 and 18, not 21.
+    let output = execute(passport("MYS", Date::new(2006, 5, 20).unwrap()), &requirements(18, ""), &GuestOptions::default());
+    assert_eq!(output.max_age_bound.age_bracket_flags, 0b00111);
+}
+
+#[test]
+fn max_age_admits_the_holder_aged_exactly_max_age() {
+    // Exactly 31 on 2025-11-01 (the birthday was 2025-05-20).
+    let holder = || passport("MYS", Date::new(1994, 5, 20).unwrap());
+    let bounded = |max_age| GuestOptions { max_age: Some(max_age), ..GuestOptions::default() };
+    let output = execute(holder(), &requirements(18, ""), &bounded(31));
+    assert!(output.max_age_bound.is_under_max_age);
+    assert_eq!(output.max_age_bound.max_age.to::<u16>(), 31);
+    assert!(!execute(holder(), &requirements(18, ""), &bounded(30)).max_age_bound.is_under_max_age);
+    // No upper bound: true, with max_age committed as 0.
+    let output = execute(holder(), &requirements(18, ""), &GuestOptions::default());
+    assert!(output.max_age_bound.is_under_max_age);
+    assert_eq!(output.max_age_bound.max_age.to::<u16>(), 0);
+}
+
+#[test]
+fn policy_commitment_matches_the_host_policy() {
+    let reqs = requirements(18, "MYS");
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &GuestOptions::default());
+    let policy = verification_policy(&reqs, &GuestOptions::default());
+    assert_eq!(policy.allowed_nationalities, ["MYS"]);
+    assert_eq!(output.policy_commitment.0, policy.commitment());
+}
+
+#[test]
+fn min_age_months_is_checked_at_month_granularity() {
+    // 16 years and 6 months on 2025-11-01 for a holder born 2009-05-01, a day short if born 2009-05-02.
+    let mut reqs = requirements(16, "");
+    reqs.min_age_months = 198;
+    let output = execute(passport("MYS", Date::new(2009, 5, 1).unwrap()), &reqs, &GuestOptions::default());
+    assert!(output.max_age_bound.is_over_min_age_months);
+    assert_eq!(output.max_age_bound.min_age_months, 198);
+    let output = execute(passport("MYS", Date::new(2009, 5, 2).unwrap()), &reqs, &GuestOptions::default());
+    assert!(!output.max_age_bound.is_over_min_age_months);
+    assert!(output.min_age_check.is_over_min_age);
+}
+
+#[test]
+fn allowlisted_document_is_a_member() {
+    let allowlisted = GuestOptions {
+        document_allowlist: vec!["X0000001".to_string(), "a12345678".to_string(), "Z9999999".to_string()],
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &allowlisted);
+    assert!(output.document_membership.is_member);
+    assert_ne!(output.document_membership.merkle_root.0, [0u8; 32]);
+
+    let other_list = GuestOptions {
+        document_allowlist: vec!["X0000001".to_string()],
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &other_list);
+    assert!(!output.document_membership.is_member);
+}
+
+#[test]
+fn matched_nationality_is_committed_only_when_revealed() {
+    let list = GuestOptions {
+        target_nationalities: vec!["FRA".to_string(), "MYS".to_string(), "SGP".to_string()],
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &list);
+    assert!(output.is_nationality_match);
+    assert_eq!(output.target_nationality, "");
+
+    let revealed = GuestOptions {
+        reveal_matched_nationality: true,
+        ..list
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &revealed);
+    assert!(output.is_nationality_match);
+    assert_eq!(output.target_nationality, "MYS");
+    assert_eq!(output.nationality_policy.allowed.size, 3);
+
+    // No match, nothing to reveal.
+    let output = execute(passport("GBR", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &revealed);
+    assert!(!output.is_nationality_match);
+    assert_eq!(output.target_nationality, "");
+}
+
+#[test]
+fn hidden_target_nationality_commits_only_its_hash() {
+    let mut reqs = requirements(18, "MYS");
+    reqs.target_nationality_salt = Some(format!("0x{}", "42".repeat(32)));
+    let options = GuestOptions {
+        hide_target_nationality: true,
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &options);
+    assert!(output.is_nationality_match);
+    assert_eq!(output.target_nationality, "");
+    assert_eq!(output.nationality_policy.target_commitment.0, target_nationality_commitment("MYS", &[0x42; 32]));
+
+    let output = execute(passport("SGP", Date::new(1994, 5, 20).unwrap()), &reqs, &options);
+    assert!(!output.is_nationality_match);
+}
+
+#[test]
+fn nationality_lists_are_accepted_up_to_the_limit() {
+    // MAX_NATIONALITY_SET_SIZE distinct codes, the holder's last.
+    let mut codes: Vec<String> = (0..MAX_NATIONALITY_SET_SIZE - 1)
+        .map(|i| format!("Q{}{}", (b'A' + (i / 26) as u8) as char, (b'A' + (i % 26) as u8) as char))
+        .collect();
+    codes.push("MYS".to_string());
+    let reqs = requirements(18, "");
+    let at_limit = GuestOptions {
+        target_nationalities: codes.clone(),
+        blocked_nationalities: codes[..MAX_NATIONALITY_SET_SIZE - 1].to_vec(),
+        ..GuestOptions::default()
+    };
+    let output = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &at_limit);
+    assert!(output.is_nationality_match);
+    assert_eq!(usize::from(output.nationality_policy.allowed.size), MAX_NATIONALITY_SET_SIZE);
+
+    // One over: the host refuses before proving, and the guest refuses on its own should a host
+    // skip that check.
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let witness = PassportWitness::from(passport("MYS", Date::new(1994, 5, 20).unwrap()));
+    for blocked in [false, true] {
+        let mut over_limit = GuestOptions { target_nationalities: codes.clone(), ..GuestOptions::default() };
+        let mut input = guest_input(&witness, &reqs, &over_limit, now).unwrap();
+        let list = if blocked { &mut over_limit.blocked_nationalities } else { &mut over_limit.target_nationalities };
+        list.resize(MAX_NATIONALITY_SET_SIZE + 1, "SGP".to_string());
+        let error = check_options(&reqs, &over_limit).unwrap_err().to_string();
+        assert!(error.contains(&format!("at most {}", MAX_NATIONALITY_SET_SIZE)), "{}", error);
+
+        let list = if blocked { &mut input.blocked_nationalities } else { &mut input.allowed_nationalities };
+        list.resize(MAX_NATIONALITY_SET_SIZE + 1, "SGP".to_string());
+        let Err(panic) = std::panic::catch_unwind(|| evaluate_passport(input)) else {
+            panic!("the guest accepted {} entries", MAX_NATIONALITY_SET_SIZE + 1);
+        };
+        let message = panic.downcast_ref::<&str>().copied().unwrap_or_default();
+        assert!(message.starts_with("too many"), "{:?}", message);
+    }
+}
+
+#[test]
+fn mrz_with_unspecified_sex_proves_and_matches_x() {
+    let line1 = "P<UTOERIKSSON<<ANNA<MARIA<<<<<<<<<<<<<<<<<<<";
+    // The ICAO specimen with '<' for the sex and its expiry moved to 2030-04-15.
+    let line2 = "L898902C36UTO7408122<3004157ZE184226B<<<<<16";
+    let witness = mrz_witness(line1, line2, &Date::new(2025, 11, 1).unwrap()).unwrap();
+    assert_eq!(witness.passport.sex, "X");
+
+    let mut reqs = requirements(18, "UTO");
+    reqs.required_sex = "X".to_string();
+    let output = execute_witness(witness, &reqs, &GuestOptions::default());
+    assert!(output.is_sex_match);
+    assert!(output.is_nationality_match);
+}
+
+// A dual national's passports, proved with the first as the passport and the rest as others.
+fn with_other_passports(others: &[&PassportAttributes]) -> GuestOptions {
+    GuestOptions {
+        other_passports: others.iter().map(|other| PassportWitness::from((*other).clone())).collect(),
+        ..GuestOptions::default()
+    }
+}
+
+#[test]
+fn one_of_two_passports_satisfies_without_revealing_which() {
+    let malaysian = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    let singaporean = passport("SGP", Date::new(1994, 5, 20).unwrap());
+    let reqs = requirements(18, "MYS");
+
+    let output = execute(singaporean.clone(), &reqs, &with_other_passports(&[&malaysian]));
+    let passport_set = &output.identity_commitment.passport_set;
+    assert!(passport_set.any_satisfies);
+    assert_eq!(passport_set.passport_count, 2);
+    let identity = |passport: &PassportAttributes| {
+        derive_identity_hash_with(passport, CommitmentHash::default(), CommitmentFields::default()).unwrap()
+    };
+    assert_eq!(passport_set.set_commitment.0, identity_set_commitment(&[identity(&malaysian), identity(&singaporean)]).1);
+    // The committed checks are the Malaysian passport's.
+    assert!(output.is_nationality_match);
+    assert_eq!(output.identity_hash.0, identity(&malaysian));
+
+    // The order the passports are given in doesn't show in the proof.
+    let reversed = execute(malaysian.clone(), &reqs, &with_other_passports(&[&singaporean]));
+    assert_eq!(output.abi_encode(), reversed.abi_encode());
+
+    // One passport is a set of one.
+    let single = execute(malaysian, &reqs, &GuestOptions::default());
+    assert!(single.identity_commitment.passport_set.any_satisfies);
+    assert_eq!(single.identity_commitment.passport_set.passport_count, 1);
+}
+
+#[test]
+fn two_passports_neither_satisfying_commit_false() {
+    let malaysian = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    let singaporean = passport("SGP", Date::new(1994, 5, 20).unwrap());
+
+    let output = execute(malaysian.clone(), &requirements(18, "THA"), &with_other_passports(&[&singaporean]));
+    assert!(!output.identity_commitment.passport_set.any_satisfies);
+    assert_eq!(output.identity_commitment.passport_set.passport_count, 2);
+    assert!(!output.is_nationality_match);
+
+    // Each passport meets one requirement, but neither meets both.
+    let minor = passport("SGP", Date::new(2010, 5, 20).unwrap());
+    let output = execute(minor, &requirements(18, "SGP"), &with_other_passports(&[&malaysian]));
+    assert!(!output.identity_commitment.passport_set.any_satisfies);
+
+    let too_many = with_other_passports(&[&singaporean; MAX_OTHER_PASSPORTS + 1]);
+    let error = check_options(&requirements(18, "MYS"), &too_many).unwrap_err().to_string();
+    assert!(error.contains("at most 4"), "{}", error);
+}
+
+#[test]
+fn stateless_holder_is_checked_by_issuer_only_when_asked() {
+    // A document issued by Germany to a holder of the given nationality.
+    let issued_by_germany = |nationality: &str| {
+        let mut witness = PassportWitness::from(passport(nationality, Date::new(1994, 5, 20).unwrap()));
+        witness.issuing_country = Some(Nationality::new("D").unwrap());
+        witness
+    };
+    for code in ["XXA", "XXB", "XXC"] {
+        let output = execute_witness(issued_by_germany(code), &requirements(18, "D"), &GuestOptions::default());
+        assert!(!output.is_nationality_match, "{}", code);
+        assert!(!output.nationality_policy.stateless_by_issuer);
+
+        let by_issuer = GuestOptions {
+            stateless_by_issuer: true,
+            blocked_nationalities: vec!["RUS".to_string()],
+            ..GuestOptions::default()
+        };
+        let output = execute_witness(issued_by_germany(code), &requirements(18, "D"), &by_issuer);
+        assert!(output.is_nationality_match, "{}", code);
+        assert!(output.nationality_policy.is_not_blocked);
+        assert!(output.nationality_policy.stateless_by_issuer);
+        // The identity still commits to the nationality printed in the document.
+        assert_eq!(
+            output.identity_hash.0,
+            derive_identity_hash_with(&issued_by_germany(code).passport, CommitmentHash::default(), CommitmentFields::default())
+                .unwrap()
+        );
+    }
+
+    // A holder with a nationality is checked as one, option or not.
+    let by_issuer = GuestOptions {
+        stateless_by_issuer: true,
+        ..GuestOptions::default()
+    };
+    assert!(!execute_witness(issued_by_germany("FRA"), &requirements(18, "D"), &by_issuer).is_nationality_match);
+}
+
+#[test]
+fn audit_commits_the_full_record_only_when_asked() {
+    let holder = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    let output = execute(holder.clone(), &requirements(18, ""), &GuestOptions::default());
+    assert_eq!(output.identity_commitment.full_record_commitment.0, [0u8; 32]);
+
+    let audit = GuestOptions {
+        audit: true,
+        ..GuestOptions::default()
+    };
+    let output = execute(holder.clone(), &requirements(18, ""), &audit);
+    assert_eq!(output.identity_commitment.full_record_commitment.0, full_record_commitment(&holder));
+    // The identity hash is the same either way; only the audit hash is added.
+    assert_eq!(
+        output.identity_hash.0,
+        derive_identity_hash_with(&holder, CommitmentHash::default(), CommitmentFields::default()).unwrap()
+    );
+
+    // The audit hash covers the name even when no other commitment needs it.
+    let without_names = GuestOptions {
+        identity_fields: CommitmentFields::DATE_OF_BIRTH,
+        ..audit
+    };
+    let output = execute(holder.clone(), &requirements(18, ""), &without_names);
+    assert_eq!(output.identity_commitment.full_record_commitment.0, full_record_commitment(&holder));
+}
+
+#[test]
+fn linked_wallets_commit_the_same_binding_in_any_order() {
+    let evm = WalletEntry { chain_id: 1, address: vec![0xaa; 20] };
+    let solana = WalletEntry { chain_id: 501, address: vec![0xbb; 32] };
+    let linked = |wallets: Vec<WalletEntry>| {
+        let options = GuestOptions {
+            linked_wallets: wallets,
+            ..GuestOptions::default()
+        };
+        execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, ""), &options).linked_wallets
+    };
+    let forward = linked(vec![evm.clone(), solana.clone()]);
+    let reversed = linked(vec![solana.clone(), evm.clone()]);
+    assert_eq!(forward.binding_commitment, reversed.binding_commitment);
+    assert_eq!(
+        forward.binding_commitment.0,
+        derive_wallet_binding(&passport("MYS", Date::new(1994, 5, 20).unwrap()), &[evm, solana])
+    );
+    let chains: Vec<u64> = reversed.wallets.iter().map(|wallet| wallet.chain_id).collect();
+    assert_eq!(chains, [1, 501]);
+    assert_eq!(reversed.wallets[1].wallet.to_vec(), vec![0xbb; 32]);
+}
+
+#[test]
+fn min_validity_holds_up_to_the_exact_day_count() {
+    // The passport expires 2030-01-01 and the guest runs as of 2025-11-01.
+    let days_left = days_since_epoch(&Date::new(2030, 1, 1).unwrap()) - days_since_epoch(&Date::new(2025, 11, 1).unwrap());
+    let check = |min_validity_days: i64| {
+        let mut reqs = requirements(18, "");
+        reqs.min_validity_days = min_validity_days as u16;
+        execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &GuestOptions::default()).expiry_check
+    };
+    let exact = check(days_left);
+    assert!(exact.has_min_validity);
+    assert_eq!(i64::from(exact.min_validity_days), days_left);
+    assert!(!check(days_left + 1).has_min_validity);
+}
+
+#[test]
+fn committed_output_never_contains_the_name() {
+    // Every plaintext string output at once, with the name hashed into the identity, the
+    // nullifier and the audience binding.
+    let mut reqs = requirements(18, "MYS");
+    reqs.required_sex = "M".to_string();
+    reqs.drinking_destination = "USA".to_string();
+    reqs.external_nullifier = Some(format!("0x{}", "22".repeat(32)));
+    let options = GuestOptions {
+        verifier_id: Some([3u8; 32]),
+        ..GuestOptions::default()
+    };
+    let bytes = execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &reqs, &options).abi_encode();
+    for part in ["TAN AH KOW", "TAN", "KOW"] {
+        assert!(!bytes.windows(part.len()).any(|window| window == part.as_bytes()), "output contains {:?}", part);
+    }
+}
+
+#[test]
+fn host_prediction_matches_the_guest() {
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let mut reqs = requirements(21, "SGP");
+    reqs.external_nullifier = Some(format!("0x{}", "22".repeat(32)));
+    let cases = [
+        GuestOptions::default(),
+        GuestOptions {
+            verifier_id: Some([3u8; 32]),
+            target_nationalities: vec!["MYS".to_string(), "SGP".to_string()],
+            max_age: Some(40),
+            ..GuestOptions::default()
+        },
+    ];
+    for options in &cases {
+        let passport = passport("MYS", Date::new(2005, 5, 20).unwrap());
+        let expected = expected_outputs(&PassportWitness::from(passport.clone()), &reqs, options, now).unwrap();
+        assert_eq!(expected.abi_encode(), execute(passport, &reqs, options).abi_encode());
+    }
+
+    let mut expired = passport("MYS", Date::new(1994, 5, 20).unwrap());
+    expired.date_of_expiry = Date::new(2025, 1, 1).unwrap();
+    let Err(error) = expected_outputs(&PassportWitness::from(expired), &reqs, &GuestOptions::default(), now) else {
+        panic!("an expired passport was accepted");
+    };
+    assert!(error.to_string().contains("passport is expired"), "{}", error);
+}
+
+#[test]
+fn expired_mode_commits_is_expired_only_after_the_expiry_day() {
+    let reqs = requirements(18, "MYS");
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let expiring = |date_of_expiry: Date| PassportAttributes {
+        date_of_expiry,
+        ..passport("MYS", Date::new(1994, 5, 20).unwrap())
+    };
+    let expired_mode = GuestOptions {
+        expired: true,
+        ..GuestOptions::default()
+    };
+    let refused = |passport: PassportAttributes, options: &GuestOptions| {
+        let Err(error) = expected_outputs(&PassportWitness::from(passport), &reqs, options, now) else {
+            panic!("the passport was proved in the wrong expiry mode");
+        };
+        error.to_string()
+    };
+
+    // Expired the day before the proof.
+    let expired = expiring(Date::new(2025, 10, 31).unwrap());
+    assert!(execute(expired.clone(), &reqs, &expired_mode).expiry_check.is_expired);
+    assert!(refused(expired.clone(), &GuestOptions::default()).contains("passport is expired"));
+
+    // Still valid on its expiry day, and after it.
+    for valid in [expiring(Date::new(2025, 11, 1).unwrap()), expiring(Date::new(2030, 1, 1).unwrap())] {
+        assert!(!execute(valid.clone(), &reqs, &GuestOptions::default()).expiry_check.is_expired);
+        assert!(refused(valid, &expired_mode).contains("passport is not expired"));
+    }
+
+    // The mode stays visible when expiry is withheld, so AppVerifier can refuse the proof.
+    let withheld = GuestOptions {
+        disclosures: Disclosures { expiry: false, ..Disclosures::default() },
+        ..expired_mode
+    };
+    let output = execute(expired, &reqs, &withheld);
+    assert!(output.expiry_check.is_expired);
+    assert_eq!(output.expiry_check.validity_months_remaining, 0);
+}
+
+#[test]
+fn strict_check_names_each_unmet_requirement() {
+    let witness = PassportWitness::from(passport("MYS", Date::new(1994, 5, 20).unwrap()));
+    let unmet = |reqs: &VerificationRequirements, options: &GuestOptions| {
+        unmet_requirements(&witness, reqs, options, 1_761_955_200).unwrap()
+    };
+    assert!(unmet(&requirements(18, "MYS"), &GuestOptions::default()).is_empty());
+    assert_eq!(unmet(&requirements(40, "GBR"), &GuestOptions::default()), ["minimum age", "nationality"]);
+
+    // A withheld check still counts: not disclosing the nationality doesn't make it match.
+    let withheld = GuestOptions {
+        disclosures: Disclosures { nationality: false, ..Disclosures::default() },
+        ..GuestOptions::default()
+    };
+    assert_eq!(unmet(&requirements(18, "GBR"), &withheld), ["nationality"]);
+}
+
+#[test]
+fn same_inputs_commit_the_same_bytes() {
+    // The guest has no clock or randomness of its own, so a repeat run must commit identical
+    // public values; anything else would give one passport several identity hashes.
+    let run = || {
+        execute(passport("MYS", Date::new(1994, 5, 20).unwrap()), &requirements(18, "MYS"), &GuestOptions::default())
+            .abi_encode()
+    };
+    assert_eq!(run(), run());
+}
@@ -0,0 +1,66 @@
+// Runs script's real build.rs into a fresh target directory, then loads the passport program it
+// compiled and executes it on a minimal input. guest.rs executes whatever ELF is already on disk;
+// this catches a build.rs that stops producing a loadable program, or a guest that no longer
+// reads what guest_stdin writes, before a user's first proof does.
+// Ignored by default because the guest build takes minutes; run it with
+// `cargo test --test guest_build -- --ignored`. Needs the SP1 toolchain installed.
+
+use passport_verifier_lib::{Date, DocumentNumber, Nationality, PassportAttributes};
+use passport_verifier_script::inputs::{guest_stdin, GuestOptions, PassportWitness, VerificationRequirements};
+use passport_verifier_script::public_values::decode_public_values;
+use passport_verifier_script::utils::{check_elf, evaluation_timestamp, prover_client};
+use serde_json::json;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ELF_NAME: &str = "passport-verifier-program";
+
+// Checks the script package with its own target directory, so cargo has to run build.rs, which
+// compiles the guest programs under that directory's elf-compilation/. Returns the passport ELF.
+fn build_guest(target_dir: &Path) -> Vec<u8> {
+    let status = Command::new(env!("CARGO"))
+        .args(["check", "--lib", "--package", env!("CARGO_PKG_NAME")])
+        .current_dir(env!("CARGO_MANIFEST_DIR"))
+        .env("CARGO_TARGET_DIR", target_dir)
+        .status()
+        .expect("failed to run cargo check");
+    assert!(status.success(), "build.rs failed to build the guest programs");
+
+    let elf_path: PathBuf = target_dir.join("elf-compilation/riscv32im-succinct-zkvm-elf/release").join(ELF_NAME);
+    std::fs::read(&elf_path).unwrap_or_else(|e| panic!("build.rs wrote no ELF at {}: {}", elf_path.display(), e))
+}
+
+#[test]
+#[ignore]
+fn built_guest_executes_and_commits_decodable_output() {
+    let target_dir = std::env::temp_dir().join(format!("passport-guest-build-{}", std::process::id()));
+    let elf = build_guest(&target_dir);
+    std::fs::remove_dir_all(&target_dir).ok();
+    check_elf(&elf).unwrap();
+
+    let passport = PassportAttributes {
+        document_number: DocumentNumber::new("A12345678").unwrap(),
+        date_of_birth: Date::new(1994, 5, 20).unwrap(),
+        date_of_expiry: Date::new(2030, 1, 1).unwrap(),
+        nationality: Nationality::new("MYS").unwrap(),
+        name: "TAN AH KOW".to_string(),
+        sex: "M".to_string(),
+    };
+    let reqs: VerificationRequirements = serde_json::from_value(json!({
+        "walletAddress": "0x5aaeb6053f3e94c9b9a09f33669435e7ef1beaed",
+        "verifierAddress": format!("0x{}", "11".repeat(20)),
+        "requiredAge": 18,
+        "requiredNationality": "MYS",
+        "requiredSex": "",
+    }))
+    .unwrap();
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let stdin = guest_stdin(&PassportWitness::from(passport), &reqs, &GuestOptions::default(), now).unwrap();
+
+    let (public_values, report) = prover_client().execute(&elf, &stdin).run().expect("the built guest failed to execute");
+    assert!(report.total_instruction_count() > 0);
+    let output = decode_public_values(public_values.as_slice()).expect("the built guest committed undecodable public values");
+    assert!(output.min_age_check.is_over_min_age);
+    assert!(output.is_nationality_match);
+    assert_eq!(output.current_timestamp.to::<u64>(), now);
+}
@@ -0,0 +1,106 @@
+// Proves with SP1's mock prover, writes the proof the way the binaries do, reads it back and
+// verifies it, so a change to the file format can't break verification unnoticed. Kept out of
+// guest.rs because it switches the whole process to the mock prover.
+
+use passport_verifier_lib::{derive_identity_hash, Date, DocumentNumber, Nationality, PassportAttributes};
+use passport_verifier_script::envelope::ProofEnvelope;
+use passport_verifier_script::inputs::{guest_stdin, GuestOptions, PassportWitness, VerificationRequirements};
+use passport_verifier_script::prover::verify_commitment_match;
+use passport_verifier_script::public_values::decode_public_values;
+use passport_verifier_script::utils::{evaluation_timestamp, prover_client, setup_cached};
+use serde_json::json;
+use sp1_sdk::{EnvProver, HashableKey, SP1ProofWithPublicValues, SP1ProvingKey, SP1PublicValues, SP1Stdin, SP1VerifyingKey};
+
+const PASSPORT_ELF: &[u8] = include_bytes!("../../target/elf-compilation/riscv32im-succinct-zkvm-elf/release/passport-verifier-program");
+
+fn passport() -> PassportAttributes {
+    PassportAttributes {
+        document_number: DocumentNumber::new("A12345678").unwrap(),
+        date_of_birth: Date::new(1994, 5, 20).unwrap(),
+        date_of_expiry: Date::new(2030, 1, 1).unwrap(),
+        nationality: Nationality::new("MYS").unwrap(),
+        name: "TAN AH KOW".to_string(),
+        sex: "M".to_string(),
+    }
+}
+
+// A mock prover with the passport program set up, and stdin for an age-only check as of
+// 2025-11-01.
+fn age_proof_setup() -> (EnvProver, SP1ProvingKey, SP1VerifyingKey, SP1Stdin, u64) {
+    std::env::set_var("SP1_PROVER", "mock");
+    let client = prover_client();
+    let (pk, vk) = setup_cached(&client, PASSPORT_ELF);
+    let reqs: VerificationRequirements = serde_json::from_value(json!({
+        "walletAddress": format!("0x{}", "00".repeat(20)),
+        "verifierAddress": format!("0x{}", "00".repeat(20)),
+        "requiredAge": 18,
+        "requiredNationality": "",
+        "requiredSex": "",
+    }))
+    .unwrap();
+    let now = evaluation_timestamp(Some(&Date::new(2025, 11, 1).unwrap())).unwrap();
+    let stdin = guest_stdin(&PassportWitness::from(passport()), &reqs, &GuestOptions::default(), now).unwrap();
+    (client, pk, vk, stdin, now)
+}
+
+fn scratch_path(name: &str) -> std::path::PathBuf {
+    std::env::temp_dir().join(format!("passport-proof-file-{}-{}", std::process::id(), name))
+}
+
+#[test]
+fn proof_survives_the_file_round_trip() {
+    let (client, pk, vk, stdin, now) = age_proof_setup();
+
+    // Core and compressed proofs are stored in the envelope as bincode, as evm writes them.
+    let proof = client.prove(&pk, &stdin).core().run().unwrap();
+    let envelope = ProofEnvelope::new(
+        "core",
+        &bincode::serialize(&proof).unwrap(),
+        proof.public_values.as_slice(),
+        vk.bytes32(),
+        now,
+    );
+    let path = scratch_path("envelope.json");
+    envelope.save(path.to_str().unwrap(), true).unwrap();
+    let loaded = ProofEnvelope::load(path.to_str().unwrap()).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    loaded.header().check(&vk.bytes32()).unwrap();
+    let reloaded: SP1ProofWithPublicValues = bincode::deserialize(&loaded.proof_bytes().unwrap()).unwrap();
+    client.verify(&reloaded, &vk).unwrap();
+    assert_eq!(reloaded.public_values.as_slice(), loaded.public_values_bytes().unwrap().as_slice());
+    let output = decode_public_values(reloaded.public_values.as_slice()).unwrap();
+    assert!(output.min_age_check.is_over_min_age);
+}
+
+#[test]
+fn tampered_public_values_fail_verification() {
+    let (client, pk, vk, stdin, _) = age_proof_setup();
+
+    // The mock verifier still checks a Groth16 proof's public values hash, so tampering shows.
+    let proof = client.prove(&pk, &stdin).groth16().run().unwrap();
+    let path = scratch_path("proof.bin");
+    proof.save(&path).unwrap();
+    let mut loaded = SP1ProofWithPublicValues::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    client.verify(&loaded, &vk).unwrap();
+
+    let mut public_values = loaded.public_values.to_vec();
+    public_values[31] ^= 1;
+    loaded.public_values = SP1PublicValues::from(&public_values);
+    assert!(client.verify(&loaded, &vk).is_err());
+}
+
+#[test]
+fn commitment_match_tells_identities_apart() {
+    let (client, pk, vk, stdin, _) = age_proof_setup();
+    let proof = client.prove(&pk, &stdin).compressed().run().unwrap();
+
+    assert!(verify_commitment_match(&proof, &vk, &derive_identity_hash(&passport())));
+    // The identity hash survives renewal, so another person differs in more than the document.
+    let someone_else = PassportAttributes {
+        name: "LIM MEI LING".to_string(),
+        ..passport()
+    };
+    assert!(!verify_commitment_match(&proof, &vk, &derive_identity_hash(&someone_else)));
+}
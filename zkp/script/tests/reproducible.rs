@@ -0,0 +1,49 @@
+// Builds the passport program twice from the same source, each time into a fresh target
+// directory, and checks both ELFs hash the same. A guest dependency that embeds the build time,
+// a random seed or a path in the binary shows up here before it changes the verification key.
+// Ignored by default because each build takes minutes; run it with
+// `cargo test --test reproducible -- --ignored`. Needs `cargo prove` on PATH.
+
+use sha2::{Digest, Sha256};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+const ELF_NAME: &str = "passport-verifier-program";
+
+fn scratch_dir(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("passport-reproducible-{}-{}", std::process::id(), name))
+}
+
+// Runs `cargo prove build` for the program with its own target directory, so nothing is reused
+// from an earlier build, and returns the SHA-256 of the ELF it wrote.
+fn build_elf_hash(program_dir: &Path, name: &str) -> [u8; 32] {
+    let target_dir = scratch_dir(&format!("{}-target", name));
+    let output_dir = scratch_dir(&format!("{}-elf", name));
+    let status = Command::new("cargo")
+        .args(["prove", "build", "--output-directory"])
+        .arg(&output_dir)
+        .args(["--elf-name", ELF_NAME])
+        .current_dir(program_dir)
+        .env("CARGO_TARGET_DIR", &target_dir)
+        .status()
+        .expect("failed to run cargo prove build");
+    assert!(status.success(), "cargo prove build failed for the {} build", name);
+
+    let elf = std::fs::read(output_dir.join(ELF_NAME)).expect("cargo prove build wrote no ELF");
+    std::fs::remove_dir_all(&target_dir).ok();
+    std::fs::remove_dir_all(&output_dir).ok();
+    Sha256::digest(&elf).into()
+}
+
+#[test]
+#[ignore]
+fn guest_elf_is_reproducible() {
+    let program_dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("../program");
+    let first = build_elf_hash(&program_dir, "first");
+    let second = build_elf_hash(&program_dir, "second");
+    assert_eq!(
+        hex::encode(first),
+        hex::encode(second),
+        "two builds of the same guest source produced different ELFs"
+    );
+}
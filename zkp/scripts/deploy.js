@@ -2,10 +2,35 @@ const hre = require("hardhat");
 const fs = require("fs");
 const path = require("path");
 
+// Succinct's SP1VerifierGateway for Groth16, which routes each proof to the verifier for its
+// SP1 version. It is deployed at the same address on every supported chain.
+const SP1_VERIFIER_GATEWAYS = {
+  mainnet: "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B",
+  sepolia: "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B",
+  holesky: "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B",
+};
+
+// The sp1-sdk version the proofs are generated with, so a deployment records which verifier ABI
+// it targets.
+function sp1Version() {
+  const manifest = fs.readFileSync(path.join(__dirname, "../script/Cargo.toml"), "utf8");
+  const match = manifest.match(/^sp1-sdk = "([^"]+)"/m);
+  return match ? match[1] : "unknown";
+}
+
 async function main() {
-  console.log("Deploying Passport Protocol contracts to Sepolia...\n");
+  const network = hre.network.name;
+  const sp1Verifier = SP1_VERIFIER_GATEWAYS[network];
+  if (!sp1Verifier) {
+    console.error(
+      `No SP1 verifier gateway known for network "${network}". ` +
+        `Use one of: ${Object.keys(SP1_VERIFIER_GATEWAYS).join(", ")}.`
+    );
+    process.exit(1);
+  }
+  const sp1VersionString = sp1Version();
 
-  const SP1_VERIFIER_SEPOLIA = "0x397A5f7f3dBd538f23DE225B51f532c34448dA9B";
+  console.log(`Deploying Passport Protocol contracts to ${network}...\n`);
 
   let passportVKey;
   const proofPath = path.join(__dirname, "../proofs/passport_proof_evm.json");
@@ -41,14 +66,16 @@ async function main() {
   console.log("\n=== Deployment Summary ===");
   console.log("PassportRegistry:", registryAddress);
   console.log("VerifierFactory :", factoryAddress);
-  console.log("SP1 Verifier    :", SP1_VERIFIER_SEPOLIA);
+  console.log("SP1 Verifier    :", sp1Verifier);
+  console.log("SP1 Version     :", sp1VersionString);
   console.log("Passport VKey   :", passportVKey);
   const deployment = {
-    network: hre.network.name,
+    network,
     deployedAt: new Date().toISOString(),
     PassportRegistry: registryAddress,
     VerifierFactory: factoryAddress,
-    SP1Verifier: SP1_VERIFIER_SEPOLIA,
+    SP1Verifier: sp1Verifier,
+    sp1Version: sp1VersionString,
     passportVKey,
   };
   const deploymentPath = path.join(__dirname, "../deployment.json");
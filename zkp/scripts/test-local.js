@@ -43,7 +43,7 @@ async function main() {
     const firstWord = BigInt("0x" + pubHex.slice(0, 64));
     const decodeHex = "0x" + (firstWord === 32n ? pubHex.slice(64) : pubHex);
     const decoded = ABI.decode(
-        ["bytes32", "address", "address", "bool", "uint256", "bool", "string", "bool", "string", "uint256"],
+        ["bytes32", "address", "address", "bool", "uint256", "uint256", "bool", "string", "bool", "string", "uint256"],
         decodeHex
     );
     console.log("\nProof claims:");
@@ -52,11 +52,12 @@ async function main() {
     console.log("  verifierAddress   :", decoded[2]);
     console.log("  isOverMinAge      :", decoded[3]);
     console.log("  minAge            :", decoded[4].toString());
-    console.log("  isNationalityMatch:", decoded[5]);
-    console.log("  targetNationality :", decoded[6]);
-    console.log("  isSexMatch        :", decoded[7]);
-    console.log("  targetSex         :", decoded[8]);
-    console.log("  timestamp         :", decoded[9].toString(), "→", new Date(Number(decoded[9]) * 1000).toISOString());
+    console.log("  ageReferenceTime  :", decoded[5].toString(), "→", new Date(Number(decoded[5]) * 1000).toISOString());
+    console.log("  isNationalityMatch:", decoded[6]);
+    console.log("  targetNationality :", decoded[7]);
+    console.log("  isSexMatch        :", decoded[8]);
+    console.log("  targetSex         :", decoded[9]);
+    console.log("  timestamp         :", decoded[10].toString(), "→", new Date(Number(decoded[10]) * 1000).toISOString());
 
     // 2. Deploy MockSP1Verifier
     console.log("\nDeploying MockSP1Verifier...");
@@ -163,3 +163,27 @@ class TestAttestation:
             r = client.get("/attestation")
         assert r.status_code == 500
         assert "error" in r.get_json()
+
+
+# ---------------------------------------------------------------------------
+# /metrics
+# ---------------------------------------------------------------------------
+
+class TestMetrics:
+    def test_exposes_proof_metrics_after_a_proof(self, client):
+        import app as app_module
+        app_module._record_proof_result(True, 90.0)
+        r = client.get("/metrics")
+        assert r.status_code == 200
+        assert r.mimetype == "text/plain"
+        text = r.get_data(as_text=True)
+        assert 'passport_proofs_generated_total{mode="groth16"}' in text
+        assert 'passport_proof_failures_total{mode="groth16"}' in text
+        assert 'passport_proving_duration_seconds_bucket{mode="groth16",le="120"}' in text
+        assert 'passport_proving_duration_seconds_count{mode="groth16"}' in text
+
+    def test_failure_is_counted(self, client):
+        import app as app_module
+        before = app_module._metrics["proof_failures"]
+        app_module._record_proof_result(False, 5.0)
+        assert app_module._metrics["proof_failures"] == before + 1